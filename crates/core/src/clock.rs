@@ -0,0 +1,74 @@
+//! Time Sources
+//!
+//! Abstracts "now" so that storage and wallet timestamps can be injected,
+//! making time-dependent behavior deterministic in tests.
+
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Source of the current wall-clock time
+pub trait Clock: Send + Sync {
+    /// Current time
+    fn now(&self) -> SystemTime;
+}
+
+/// Clock backed by the operating system
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Manually controlled clock for tests
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<SystemTime>,
+}
+
+impl MockClock {
+    /// Create a clock frozen at the given time
+    pub fn new(time: SystemTime) -> Self {
+        MockClock {
+            now: Mutex::new(time),
+        }
+    }
+    
+    /// Create a clock frozen at a unix timestamp (seconds)
+    pub fn from_unix(secs: u64) -> Self {
+        Self::new(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+    
+    /// Set the current time
+    pub fn set(&self, time: SystemTime) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = time;
+    }
+    
+    /// Move the clock forward
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.now.lock().unwrap_or_else(|e| e.into_inner());
+        *now += by;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_mock_clock_advance() {
+        let clock = MockClock::from_unix(1_700_000_000);
+        clock.advance(Duration::from_secs(60));
+        
+        let secs = clock.now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        assert_eq!(secs, 1_700_000_060);
+    }
+}
@@ -0,0 +1,235 @@
+//! Compact Output Filters
+//!
+//! Golomb-coded sets (as in BIP158) over one-time output keys. A light
+//! client sends the filter to a server, which returns only transactions
+//! whose outputs match instead of the whole chain.
+
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use crate::{CoreError, Result};
+use super::point::{compress_point, CompressedPoint};
+use super::stealth::StealthTransaction;
+
+/// Golomb-Rice parameter (remainder bits per entry)
+const FILTER_P: u8 = 19;
+
+/// Inverse false-positive rate
+const FILTER_M: u64 = 784_931;
+
+/// Length of the per-filter hashing key
+const FILTER_KEY_LEN: usize = 16;
+
+/// Probabilistic set of one-time output keys
+///
+/// Never misses a member; a non-member matches with probability about
+/// `1 / 784931`. Each filter hashes with its own random key so that
+/// filters from the same wallet cannot be correlated by their contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputFilter {
+    key: [u8; FILTER_KEY_LEN],
+    n: u32,
+    data: Vec<u8>,
+}
+
+impl OutputFilter {
+    /// Build a filter over the given one-time public keys
+    pub fn new(keys: &[CompressedPoint]) -> Self {
+        Self::with_key(rand::thread_rng().gen(), keys)
+    }
+    
+    /// Build a filter with a fixed hashing key
+    fn with_key(key: [u8; FILTER_KEY_LEN], keys: &[CompressedPoint]) -> Self {
+        let n = keys.len() as u32;
+        let range = n as u64 * FILTER_M;
+        
+        let mut values: Vec<u64> = keys.iter()
+            .map(|item| hash_to_range(&key, item, range))
+            .collect();
+        values.sort_unstable();
+        
+        let mut writer = BitWriter::default();
+        let mut last = 0;
+        for value in values {
+            writer.write_golomb(value - last);
+            last = value;
+        }
+        
+        OutputFilter {
+            key,
+            n,
+            data: writer.bytes,
+        }
+    }
+    
+    /// Number of keys in the filter
+    pub fn len(&self) -> usize {
+        self.n as usize
+    }
+    
+    /// Whether the filter is empty
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+    
+    /// Whether the transaction's output is (probably) in the filter
+    pub fn matches(&self, tx: &StealthTransaction) -> bool {
+        self.matches_key(&compress_point(&tx.one_time_public))
+    }
+    
+    /// Whether a compressed one-time public key is (probably) in the filter
+    pub fn matches_key(&self, item: &CompressedPoint) -> bool {
+        if self.n == 0 {
+            return false;
+        }
+        
+        let target = hash_to_range(&self.key, item, self.n as u64 * FILTER_M);
+        let mut reader = BitReader::new(&self.data);
+        let mut value = 0u64;
+        
+        for _ in 0..self.n {
+            match reader.read_golomb() {
+                Some(delta) => value += delta,
+                None => return false,
+            }
+            if value == target {
+                return true;
+            }
+            if value > target {
+                return false;
+            }
+        }
+        
+        false
+    }
+    
+    /// Serialize as `key (16) || n (u32 LE) || golomb data`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(FILTER_KEY_LEN + 4 + self.data.len());
+        bytes.extend_from_slice(&self.key);
+        bytes.extend_from_slice(&self.n.to_le_bytes());
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+    
+    /// Deserialize from `to_bytes` output
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < FILTER_KEY_LEN + 4 {
+            return Err(CoreError::Serialization("Output filter too short".into()));
+        }
+        
+        let (key, rest) = bytes.split_at(FILTER_KEY_LEN);
+        let (n, data) = rest.split_at(4);
+        
+        Ok(OutputFilter {
+            key: key.try_into().expect("16-byte key"),
+            n: u32::from_le_bytes(n.try_into().expect("4-byte count")),
+            data: data.to_vec(),
+        })
+    }
+}
+
+/// Map an item uniformly into `0..range`
+fn hash_to_range(key: &[u8; FILTER_KEY_LEN], item: &[u8], range: u64) -> u64 {
+    let hash = Sha256::new().chain_update(key).chain_update(item).finalize();
+    let h = u64::from_le_bytes(hash[..8].try_into().expect("8-byte prefix"));
+    ((h as u128 * range as u128) >> 64) as u64
+}
+
+/// MSB-first bit writer
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    used: u8,
+}
+
+impl BitWriter {
+    fn write_bit(&mut self, bit: bool) {
+        if self.used == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().expect("byte pushed above") |= 0x80 >> self.used;
+        }
+        self.used = (self.used + 1) % 8;
+    }
+    
+    /// Golomb-Rice code: unary quotient, then `FILTER_P` remainder bits
+    fn write_golomb(&mut self, value: u64) {
+        for _ in 0..(value >> FILTER_P) {
+            self.write_bit(true);
+        }
+        self.write_bit(false);
+        for i in (0..FILTER_P).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+}
+
+/// MSB-first bit reader
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        BitReader { bytes, pos: 0 }
+    }
+    
+    fn read_bit(&mut self) -> Option<bool> {
+        let byte = *self.bytes.get(self.pos / 8)?;
+        let bit = byte & (0x80 >> (self.pos % 8)) != 0;
+        self.pos += 1;
+        Some(bit)
+    }
+    
+    fn read_golomb(&mut self) -> Option<u64> {
+        let mut quotient = 0u64;
+        while self.read_bit()? {
+            quotient += 1;
+        }
+        
+        let mut remainder = 0u64;
+        for _ in 0..FILTER_P {
+            remainder = (remainder << 1) | self.read_bit()? as u64;
+        }
+        
+        Some((quotient << FILTER_P) | remainder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::stealth::{StealthMasterKey, StealthScanner};
+    
+    #[test]
+    fn test_filter_matches_owned_outputs() {
+        let wallet = StealthMasterKey::generate();
+        let address = wallet.get_stealth_address();
+        
+        let owned: Vec<StealthTransaction> = (0..20).map(|_| address.generate_one_time_address()).collect();
+        let mut scanner = StealthScanner::new(wallet);
+        assert_eq!(scanner.scan_transactions(&owned).len(), 20);
+        
+        let filter = OutputFilter::from_bytes(&scanner.build_filter().to_bytes()).unwrap();
+        assert_eq!(filter.len(), 20);
+        assert!(owned.iter().all(|tx| filter.matches(tx)));
+        
+        // Random keys stand in for other wallets' outputs
+        let false_positives = (0..1000)
+            .filter(|_| filter.matches_key(&rand::random()))
+            .count();
+        assert!(false_positives < 5, "{} false positives", false_positives);
+    }
+    
+    #[test]
+    fn test_empty_filter_matches_nothing() {
+        let filter = OutputFilter::new(&[]);
+        let tx = StealthMasterKey::generate().get_stealth_address().generate_one_time_address();
+        
+        assert!(filter.is_empty());
+        assert!(!filter.matches(&tx));
+        assert!(OutputFilter::from_bytes(&[0u8; 3]).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+//! Commitment Merkle Trees
+//!
+//! Binary SHA-256 Merkle trees over 32-byte leaves such as compressed
+//! commitments. A published root (anchor) lets a light client check that a
+//! commitment belongs to the set from a logarithmic-size proof.
+//!
+//! Leaves and interior nodes are hashed with distinct prefixes so an interior
+//! node can never be presented as a leaf, and the leaf count is padded to a
+//! power of two with all-zero nodes.
+
+use sha2::{Digest, Sha256};
+
+/// Hash prefix for leaves
+const LEAF_TAG: u8 = 0x00;
+
+/// Hash prefix for interior nodes
+const NODE_TAG: u8 = 0x01;
+
+/// Node value used to pad the tree to a power of two
+const EMPTY_NODE: [u8; 32] = [0u8; 32];
+
+/// Merkle tree over a fixed set of leaves
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    /// Levels from the hashed leaves (0) up to the root
+    levels: Vec<Vec<[u8; 32]>>,
+    leaf_count: usize,
+}
+
+/// Proof that a leaf is in the tree with a given root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// Position of the leaf in the tree
+    pub leaf_index: u64,
+    /// Sibling hashes from the leaf level up to just below the root
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl MerkleTree {
+    /// Build a tree over `leaves`
+    pub fn new(leaves: &[[u8; 32]]) -> Self {
+        let width = leaves.len().max(1).next_power_of_two();
+        let mut level: Vec<[u8; 32]> = leaves.iter().map(hash_leaf).collect();
+        level.resize(width, EMPTY_NODE);
+        
+        let mut levels = vec![level];
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1]
+                .chunks_exact(2)
+                .map(|pair| hash_node(&pair[0], &pair[1]))
+                .collect();
+            levels.push(next);
+        }
+        
+        MerkleTree {
+            levels,
+            leaf_count: leaves.len(),
+        }
+    }
+    
+    /// Root hash committing to every leaf
+    pub fn root(&self) -> [u8; 32] {
+        self.levels[self.levels.len() - 1][0]
+    }
+    
+    /// Number of leaves the tree was built from
+    pub fn len(&self) -> usize {
+        self.leaf_count
+    }
+    
+    /// Whether the tree has no leaves
+    pub fn is_empty(&self) -> bool {
+        self.leaf_count == 0
+    }
+    
+    /// Membership proof for the leaf at `index`
+    pub fn proof(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.leaf_count {
+            return None;
+        }
+        
+        let siblings = self.levels[..self.levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(depth, level)| level[(index >> depth) ^ 1])
+            .collect();
+        
+        Some(MerkleProof {
+            leaf_index: index as u64,
+            siblings,
+        })
+    }
+}
+
+impl MerkleProof {
+    /// Whether `leaf` is at `leaf_index` in the tree with root `root`
+    pub fn verify(&self, root: &[u8; 32], leaf: &[u8; 32]) -> bool {
+        if self.siblings.len() >= 64 || self.leaf_index >> self.siblings.len() != 0 {
+            return false;
+        }
+        
+        let mut node = hash_leaf(leaf);
+        for (depth, sibling) in self.siblings.iter().enumerate() {
+            node = if (self.leaf_index >> depth) & 1 == 0 {
+                hash_node(&node, sibling)
+            } else {
+                hash_node(sibling, &node)
+            };
+        }
+        
+        node == *root
+    }
+}
+
+fn hash_leaf(leaf: &[u8; 32]) -> [u8; 32] {
+    Sha256::new().chain_update([LEAF_TAG]).chain_update(leaf).finalize().into()
+}
+
+fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([NODE_TAG])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_every_leaf_proves_membership() {
+        let leaves: Vec<[u8; 32]> = (0..5u8).map(|i| [i; 32]).collect();
+        let tree = MerkleTree::new(&leaves);
+        let root = tree.root();
+        
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = tree.proof(i).unwrap();
+            assert_eq!(proof.siblings.len(), 3);
+            assert!(proof.verify(&root, leaf));
+            assert!(!proof.verify(&root, &[9u8; 32]));
+        }
+        
+        assert!(tree.proof(5).is_none());
+        
+        // A proof does not carry over to another position
+        let mut moved = tree.proof(0).unwrap();
+        moved.leaf_index = 1;
+        assert!(!moved.verify(&root, &leaves[0]));
+    }
+}
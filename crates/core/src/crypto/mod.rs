@@ -1,7 +1,15 @@
 //! Cryptographic modules
 
+pub mod filter;
+pub mod merkle;
+pub mod point;
 pub mod primitives;
 pub mod stealth;
+#[cfg(test)]
+mod stealth_vectors;
 
+pub use filter::*;
+pub use merkle::*;
+pub use point::*;
 pub use primitives::*;
 pub use stealth::*;
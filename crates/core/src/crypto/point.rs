@@ -0,0 +1,84 @@
+//! Ristretto Point Encoding
+//!
+//! Centralizes compression and decompression of curve points so that every
+//! caller maps malformed encodings to the same error.
+
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    traits::Identity,
+};
+use crate::{CoreError, Result};
+
+/// Compressed Ristretto point (32 bytes)
+pub type CompressedPoint = [u8; 32];
+
+/// Compress a point to its 32-byte encoding
+pub fn compress_point(point: &RistrettoPoint) -> CompressedPoint {
+    point.compress().to_bytes()
+}
+
+/// Decompress a public key point
+///
+/// Rejects invalid encodings and the identity point, which is never a valid key.
+pub fn decompress_point(bytes: &CompressedPoint) -> Result<RistrettoPoint> {
+    let point = decompress_point_allow_identity(bytes)?;
+    
+    if point == RistrettoPoint::identity() {
+        return Err(CoreError::Serialization("Identity point not allowed".into()));
+    }
+    
+    Ok(point)
+}
+
+/// Decompress a point, accepting the identity
+///
+/// Used for commitments, where the identity is a legitimate (zero) value.
+pub fn decompress_point_allow_identity(bytes: &CompressedPoint) -> Result<RistrettoPoint> {
+    CompressedRistretto(*bytes)
+        .decompress()
+        .ok_or_else(|| CoreError::Serialization("Invalid point encoding".into()))
+}
+
+/// Decompress a public key point from a slice that must be exactly 32 bytes
+pub fn decompress_point_slice(bytes: &[u8]) -> Result<RistrettoPoint> {
+    let array: CompressedPoint = bytes
+        .try_into()
+        .map_err(|_| CoreError::Serialization("Invalid point length".into()))?;
+    decompress_point(&array)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT as G;
+    
+    #[test]
+    fn test_point_roundtrip() {
+        let bytes = compress_point(&G);
+        assert_eq!(decompress_point(&bytes).unwrap(), G);
+    }
+    
+    #[test]
+    fn test_invalid_point_errors() {
+        let invalid = [0xffu8; 32];
+        assert!(matches!(decompress_point(&invalid), Err(CoreError::Serialization(_))));
+        assert!(matches!(decompress_point_slice(&invalid[..31]), Err(CoreError::Serialization(_))));
+        
+        // Identity is only accepted where explicitly allowed
+        let identity = compress_point(&RistrettoPoint::identity());
+        assert!(matches!(decompress_point(&identity), Err(CoreError::Serialization(_))));
+        assert!(decompress_point_allow_identity(&identity).is_ok());
+        
+        // Higher-level types surface the same error
+        let mut address_bytes = [0u8; 64];
+        address_bytes[..32].copy_from_slice(&invalid);
+        assert!(matches!(
+            crate::crypto::stealth::StealthAddress::from_bytes(&address_bytes),
+            Err(CoreError::Serialization(_))
+        ));
+        assert!(matches!(
+            crate::commitments::Commitment::from_bytes(&invalid),
+            Err(CoreError::Serialization(_))
+        ));
+    }
+}
@@ -7,7 +7,7 @@ use aes_gcm::{
     Aes256Gcm, Nonce,
 };
 use chacha20poly1305::{
-    ChaCha20Poly1305, Key as ChaChaKey,
+    ChaCha20Poly1305, XChaCha20Poly1305, Key as ChaChaKey,
 };
 use sha2::{Sha256, Sha512, Digest};
 use blake2::{Blake2b512, Blake2s256};
@@ -141,6 +141,57 @@ impl ChaCha20Cipher {
     }
 }
 
+/// XChaCha20-Poly1305 encryption with 192-bit nonces
+///
+/// Random 24-byte nonces are safe to generate for the lifetime of a
+/// long-lived key, unlike the 12-byte nonces of `ChaCha20Cipher`.
+pub struct XChaCha20Poly1305Cipher {
+    cipher: XChaCha20Poly1305,
+}
+
+impl XChaCha20Poly1305Cipher {
+    /// Create a new cipher with a 256-bit key
+    pub fn new(key: &[u8; 32]) -> Self {
+        let cipher = XChaCha20Poly1305::new(ChaChaKey::from_slice(key));
+        XChaCha20Poly1305Cipher { cipher }
+    }
+    
+    /// Generate a random encryption key
+    pub fn generate_key() -> [u8; 32] {
+        AesGcmCipher::generate_key()
+    }
+    
+    /// Encrypt data (24-byte nonce prepended to the ciphertext)
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce_bytes = [0u8; 24];
+        rand::thread_rng().fill(&mut nonce_bytes);
+        let nonce = chacha20poly1305::XNonce::from_slice(&nonce_bytes);
+        
+        let ciphertext = self.cipher
+            .encrypt(nonce, plaintext)
+            .map_err(|e| CoreError::Crypto(format!("Encryption failed: {}", e)))?;
+        
+        let mut result = nonce_bytes.to_vec();
+        result.extend_from_slice(&ciphertext);
+        
+        Ok(result)
+    }
+    
+    /// Decrypt data produced by `encrypt`
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        if ciphertext.len() < 24 {
+            return Err(CoreError::Crypto("Ciphertext too short".into()));
+        }
+        
+        let (nonce_bytes, encrypted) = ciphertext.split_at(24);
+        let nonce = chacha20poly1305::XNonce::from_slice(nonce_bytes);
+        
+        self.cipher
+            .decrypt(nonce, encrypted)
+            .map_err(|e| CoreError::Crypto(format!("Decryption failed: {}", e)))
+    }
+}
+
 /// PBKDF2 key derivation
 pub fn pbkdf2_derive_key(
     password: &[u8],
@@ -182,6 +233,24 @@ pub fn argon2_derive_key(
     Ok(key)
 }
 
+/// HKDF-SHA256 (RFC 5869) producing a single 32-byte key
+///
+/// For deriving subkeys from a key that is already uniformly random; use
+/// `argon2_derive_key` for passwords.
+pub fn hkdf_sha256(ikm: &[u8], salt: &[u8], info: &[u8]) -> [u8; 32] {
+    use hmac::{Hmac, Mac};
+    type HmacSha256 = Hmac<Sha256>;
+    
+    let mut extract = <HmacSha256 as Mac>::new_from_slice(salt).expect("HMAC accepts any key length");
+    extract.update(ikm);
+    let prk = extract.finalize().into_bytes();
+    
+    let mut expand = <HmacSha256 as Mac>::new_from_slice(&prk).expect("HMAC accepts any key length");
+    expand.update(info);
+    expand.update(&[1]);
+    expand.finalize().into_bytes().into()
+}
+
 /// Stealth address derivation helpers
 pub mod stealth {
     use super::*;
@@ -311,6 +380,34 @@ mod tests {
         assert_eq!(plaintext, decrypted.as_slice());
     }
     
+    #[test]
+    fn test_xchacha20_encrypt_decrypt() {
+        let key = XChaCha20Poly1305Cipher::generate_key();
+        let cipher = XChaCha20Poly1305Cipher::new(&key);
+        
+        let plaintext = b"secret message";
+        let first = cipher.encrypt(plaintext).unwrap();
+        let second = cipher.encrypt(plaintext).unwrap();
+        
+        // Fresh 24-byte nonce per message
+        assert_eq!(first.len(), 24 + plaintext.len() + 16);
+        assert_ne!(first[..24], second[..24]);
+        
+        assert_eq!(cipher.decrypt(&first).unwrap(), plaintext);
+        assert_eq!(cipher.decrypt(&second).unwrap(), plaintext);
+    }
+    
+    #[test]
+    fn test_xchacha20_rejects_tampered_tag() {
+        let cipher = XChaCha20Poly1305Cipher::new(&XChaCha20Poly1305Cipher::generate_key());
+        
+        let mut ciphertext = cipher.encrypt(b"secret message").unwrap();
+        let last = ciphertext.len() - 1;
+        ciphertext[last] ^= 0x01;
+        
+        assert!(cipher.decrypt(&ciphertext).is_err());
+    }
+    
     #[test]
     fn test_pbkdf2() {
         let password = b"correct horse battery staple";
@@ -324,6 +421,19 @@ mod tests {
         assert_eq!(key, key2);
     }
     
+    #[test]
+    fn test_hkdf_sha256_rfc5869() {
+        // RFC 5869 test case 1, first 32 bytes of OKM
+        let ikm = [0x0b; 22];
+        let salt: Vec<u8> = (0x00..=0x0c).collect();
+        let info: Vec<u8> = (0xf0..=0xf9).collect();
+        
+        assert_eq!(
+            hex::encode(hkdf_sha256(&ikm, &salt, &info)),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf"
+        );
+    }
+    
     #[test]
     fn test_stealth_address() {
         use stealth::StealthKeypair;
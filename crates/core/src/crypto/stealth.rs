@@ -4,21 +4,229 @@
 //! This allows senders to generate one-time addresses for recipients without interaction.
 
 use curve25519_dalek::{
-    ristretto::{CompressedRistretto, RistrettoPoint},
+    ristretto::RistrettoPoint,
     scalar::Scalar,
     constants::RISTRETTO_BASEPOINT_POINT as G,
+    traits::Identity,
 };
 use serde::{Deserialize, Serialize};
-use zeroize::ZeroizeOnDrop;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 use rand::Rng;
 use crate::{CoreError, Result};
+use crate::storage::StealthOutput;
+use super::filter::OutputFilter;
+use super::primitives::ChaCha20Cipher;
+use super::point::{compress_point, decompress_point_allow_identity, decompress_point_slice, CompressedPoint};
+
+/// First byte of a versioned stealth address encoding
+///
+/// Legacy addresses are the bare 64-byte `S || V` encoding; versioned
+/// addresses are `0x80 || version || S || V`, followed by the amount key
+/// `A` when the version byte says so.
+const ADDRESS_FORMAT_MARKER: u8 = 0x80;
+
+/// Version byte bits 0-1: hash-to-scalar algorithm
+const VERSION_HASH_MASK: u8 = 0b0000_0011;
+
+/// Version byte bits 2-3: output counter width
+const VERSION_COUNTER_WIDTH_MASK: u8 = 0b0000_1100;
+
+/// Version byte bit 4: output counter is big-endian
+const VERSION_COUNTER_BIG_ENDIAN_FLAG: u8 = 0b0001_0000;
+
+/// Version byte bit 5: address is a subaddress (sender uses R = r·D)
+const VERSION_SUBADDRESS_FLAG: u8 = 0b0010_0000;
+
+/// Version byte bit 6: address carries an amount key A after S and V
+const VERSION_AMOUNT_KEY_FLAG: u8 = 0b0100_0000;
+
+/// All version byte bits understood by this implementation
+const VERSION_KNOWN_BITS: u8 = VERSION_HASH_MASK
+    | VERSION_COUNTER_WIDTH_MASK
+    | VERSION_COUNTER_BIG_ENDIAN_FLAG
+    | VERSION_SUBADDRESS_FLAG
+    | VERSION_AMOUNT_KEY_FLAG;
+
+/// Length of a versioned address followed by its base58 checksum
+const CHECKED_ADDRESS_LEN: usize = 70;
+
+/// Length of a checksummed versioned address that carries an amount key
+const CHECKED_AMOUNT_ADDRESS_LEN: usize = CHECKED_ADDRESS_LEN + 32;
+
+/// Why a stealth address failed validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum AddressError {
+    #[error("not valid base58")]
+    Encoding,
+    #[error("wrong length")]
+    Length,
+    #[error("checksum mismatch")]
+    Checksum,
+    #[error("unsupported version")]
+    Version,
+    #[error("key is not a valid curve point")]
+    InvalidPoint,
+    #[error("key is the identity point")]
+    IdentityPoint,
+}
+
+/// Subaddress index reserved for change outputs sent to ourselves
+pub const CHANGE_SUBADDRESS_INDEX: u32 = u32::MAX;
+
+/// Hash function used to map the ECDH shared secret to a scalar
+///
+/// Sender and recipient must agree; the choice is carried in the address
+/// version byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StealthHashAlgo {
+    #[default]
+    Blake2b,
+    Sha512,
+    Keccak512,
+}
+
+impl StealthHashAlgo {
+    /// Encode as version byte bits
+    fn to_bits(self) -> u8 {
+        match self {
+            StealthHashAlgo::Blake2b => 0,
+            StealthHashAlgo::Sha512 => 1,
+            StealthHashAlgo::Keccak512 => 2,
+        }
+    }
+    
+    /// Decode from version byte bits
+    fn from_bits(bits: u8) -> Result<Self> {
+        match bits & VERSION_HASH_MASK {
+            0 => Ok(StealthHashAlgo::Blake2b),
+            1 => Ok(StealthHashAlgo::Sha512),
+            2 => Ok(StealthHashAlgo::Keccak512),
+            other => Err(CoreError::Serialization(format!("Unknown stealth hash algorithm: {}", other))),
+        }
+    }
+    
+    /// Hash bytes to a scalar with this algorithm (64-byte digest reduced mod l)
+    pub fn hash_to_scalar(self, data: &[u8]) -> Scalar {
+        use sha2::Digest;
+        
+        let mut hash_bytes = [0u8; 64];
+        match self {
+            StealthHashAlgo::Blake2b => hash_bytes.copy_from_slice(&blake2::Blake2b512::digest(data)),
+            StealthHashAlgo::Sha512 => hash_bytes.copy_from_slice(&sha2::Sha512::digest(data)),
+            StealthHashAlgo::Keccak512 => hash_bytes.copy_from_slice(&sha3::Keccak512::digest(data)),
+        }
+        Scalar::from_bytes_mod_order_wide(&hash_bytes)
+    }
+}
+
+/// Width of the output index hashed into multi-output one-time keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CounterWidth {
+    One,
+    #[default]
+    Four,
+    Eight,
+}
+
+/// Encoding of the output index in `H(σ || index)`
+///
+/// Lets outputs interoperate with implementations that encode the index
+/// differently. Sender and recipient must agree; the encoding is carried in
+/// the address version byte, and a recipient expecting another encoding
+/// simply does not recognize the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct OutputCounter {
+    pub width: CounterWidth,
+    pub big_endian: bool,
+}
+
+impl OutputCounter {
+    /// Encode `index`, failing if it does not fit the width
+    pub fn encode(self, index: u64) -> Result<Vec<u8>> {
+        let too_large = || CoreError::InvalidParameter(
+            format!("Output index {} does not fit a {:?}-byte counter", index, self.width)
+        );
+        
+        Ok(match (self.width, self.big_endian) {
+            (CounterWidth::One, _) => vec![u8::try_from(index).map_err(|_| too_large())?],
+            (CounterWidth::Four, false) => u32::try_from(index).map_err(|_| too_large())?.to_le_bytes().to_vec(),
+            (CounterWidth::Four, true) => u32::try_from(index).map_err(|_| too_large())?.to_be_bytes().to_vec(),
+            (CounterWidth::Eight, false) => index.to_le_bytes().to_vec(),
+            (CounterWidth::Eight, true) => index.to_be_bytes().to_vec(),
+        })
+    }
+    
+    /// Encode as version byte bits
+    fn to_bits(self) -> u8 {
+        let width = match self.width {
+            CounterWidth::Four => 0b0000_0000,
+            CounterWidth::One => 0b0000_0100,
+            CounterWidth::Eight => 0b0000_1000,
+        };
+        if self.big_endian { width | VERSION_COUNTER_BIG_ENDIAN_FLAG } else { width }
+    }
+    
+    /// Decode from version byte bits
+    fn from_bits(bits: u8) -> Result<Self> {
+        let width = match bits & VERSION_COUNTER_WIDTH_MASK {
+            0b0000_0000 => CounterWidth::Four,
+            0b0000_0100 => CounterWidth::One,
+            0b0000_1000 => CounterWidth::Eight,
+            _ => return Err(CoreError::Serialization("Unknown stealth output counter width".into())),
+        };
+        Ok(OutputCounter {
+            width,
+            big_endian: bits & VERSION_COUNTER_BIG_ENDIAN_FLAG != 0,
+        })
+    }
+}
+
+/// Scalar h = H(σ || index) for output `index` of a multi-output transaction
+fn output_hash(hash_algo: StealthHashAlgo, shared_secret: &RistrettoPoint, counter: &[u8]) -> Scalar {
+    let mut data = compress_point(shared_secret).to_vec();
+    data.extend_from_slice(counter);
+    hash_algo.hash_to_scalar(&data)
+}
+
+/// AEAD and KDF used for an encrypted amount
+///
+/// Carried as the first byte of the blob so recipients can decrypt amounts
+/// from senders on other crate versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AmountScheme {
+    /// ChaCha20-Poly1305 under SHA-256("Zetaris-Amount-v1" || r·S || P)
+    #[default]
+    ChaCha20Sha256,
+    /// ChaCha20-Poly1305 under SHA-256("Zetaris-Amount-v2" || r·A || P),
+    /// readable with the view key
+    ChaCha20AmountKey,
+}
+
+impl AmountScheme {
+    /// Identifier byte prefixed to the ciphertext
+    fn id(self) -> u8 {
+        match self {
+            AmountScheme::ChaCha20Sha256 => 1,
+            AmountScheme::ChaCha20AmountKey => 2,
+        }
+    }
+    
+    /// Scheme for an identifier byte
+    fn from_id(id: u8) -> Result<Self> {
+        match id {
+            1 => Ok(AmountScheme::ChaCha20Sha256),
+            2 => Ok(AmountScheme::ChaCha20AmountKey),
+            other => Err(CoreError::Serialization(format!("Unknown amount encryption scheme: {}", other))),
+        }
+    }
+}
 
 /// Stealth address master keypair
 /// 
 /// Consists of:
 /// - Spend keypair (s, S = s·G) - Used to spend funds
 /// - View keypair (v, V = v·G) - Used to scan for incoming transactions
-#[derive(Clone, ZeroizeOnDrop)]
+#[derive(Clone, Zeroize)]
 pub struct StealthMasterKey {
     /// Spend private key (kept secret, used to spend)
     pub spend_private: Scalar,
@@ -28,8 +236,28 @@ pub struct StealthMasterKey {
     pub view_private: Scalar,
     /// View public key (shared publicly)
     pub view_public: RistrettoPoint,
+    /// Hash-to-scalar algorithm advertised in our address
+    #[zeroize(skip)]
+    pub hash_algo: StealthHashAlgo,
+    /// Output index encoding advertised in our address
+    #[zeroize(skip)]
+    pub output_counter: OutputCounter,
+}
+
+impl Drop for StealthMasterKey {
+    fn drop(&mut self) {
+        self.zeroize();
+        
+        #[cfg(debug_assertions)]
+        crate::zeroize_verify::record::<Self>(&[
+            self.spend_private.as_bytes(),
+            self.view_private.as_bytes(),
+        ]);
+    }
 }
 
+impl ZeroizeOnDrop for StealthMasterKey {}
+
 impl StealthMasterKey {
     /// Generate a new stealth master keypair
     pub fn generate() -> Self {
@@ -49,6 +277,8 @@ impl StealthMasterKey {
             spend_public,
             view_private,
             view_public,
+            hash_algo: StealthHashAlgo::default(),
+            output_counter: OutputCounter::default(),
         }
     }
     
@@ -65,9 +295,23 @@ impl StealthMasterKey {
             spend_public,
             view_private,
             view_public,
+            hash_algo: StealthHashAlgo::default(),
+            output_counter: OutputCounter::default(),
         }
     }
     
+    /// Use a different hash-to-scalar algorithm (default is BLAKE2b)
+    pub fn with_hash_algo(mut self, hash_algo: StealthHashAlgo) -> Self {
+        self.hash_algo = hash_algo;
+        self
+    }
+    
+    /// Use a different output index encoding (default is 4 bytes, little-endian)
+    pub fn with_output_counter(mut self, output_counter: OutputCounter) -> Self {
+        self.output_counter = output_counter;
+        self
+    }
+    
     /// Export spend private key (for backup)
     pub fn export_spend_private(&self) -> [u8; 32] {
         self.spend_private.to_bytes()
@@ -78,11 +322,157 @@ impl StealthMasterKey {
         self.view_private.to_bytes()
     }
     
-    /// Get stealth address (S, V) to share with senders
+    /// Get stealth address (S, V, A) to share with senders
     pub fn get_stealth_address(&self) -> StealthAddress {
         StealthAddress {
             spend_public: self.spend_public,
             view_public: self.view_public,
+            hash_algo: self.hash_algo,
+            output_counter: self.output_counter,
+            is_subaddress: false,
+            amount_public: Some(self.amount_private() * G),
+        }
+    }
+    
+    /// Amount key a = H("Zetaris-AmountKey-v1" || v || s)
+    ///
+    /// Part of the view key but not derivable from v alone, so a
+    /// detection-only key cannot read amounts.
+    fn amount_private(&self) -> Scalar {
+        use sha2::{Digest, Sha512};
+        
+        let mut hash: [u8; 64] = Sha512::new()
+            .chain_update(b"Zetaris-AmountKey-v1")
+            .chain_update(self.view_private.as_bytes())
+            .chain_update(self.spend_private.as_bytes())
+            .finalize()
+            .into();
+        let amount_private = Scalar::from_bytes_mod_order_wide(&hash);
+        hash.zeroize();
+        amount_private
+    }
+    
+    /// Get the subaddress at `index`
+    ///
+    /// Subaddresses are unlinkable to the main address and to each other:
+    /// D = S + m·G and C = v·D, where m = H(v || index).
+    pub fn get_subaddress(&self, index: u32) -> StealthAddress {
+        let spend_public = self.spend_public + self.subaddress_offset(index) * G;
+        
+        StealthAddress {
+            spend_public,
+            view_public: self.view_private * spend_public,
+            hash_algo: self.hash_algo,
+            output_counter: self.output_counter,
+            is_subaddress: true,
+            amount_public: None,
+        }
+    }
+    
+    /// Get the subaddress used for change outputs
+    ///
+    /// Keeps change in a one-time key space separate from incoming payments.
+    pub fn get_change_address(&self) -> StealthAddress {
+        self.get_subaddress(CHANGE_SUBADDRESS_INDEX)
+    }
+    
+    /// Scan a transaction sent to the subaddress at `index`
+    ///
+    /// Returns the one-time private key h + s + m if the output is ours.
+    pub fn scan_subaddress(
+        &self,
+        ephemeral_public: &RistrettoPoint,
+        output_public: &RistrettoPoint,
+        index: u32,
+    ) -> Option<Scalar> {
+        let shared_secret = self.view_private * ephemeral_public;
+        let hash_scalar = self.hash_algo.hash_to_scalar(shared_secret.compress().as_bytes());
+        
+        let offset = self.subaddress_offset(index);
+        let expected_public = hash_scalar * G + self.spend_public + offset * G;
+        
+        if expected_public == *output_public {
+            Some(hash_scalar + self.spend_private + offset)
+        } else {
+            None
+        }
+    }
+    
+    /// Scan a transaction sent to our change subaddress
+    pub fn scan_change(
+        &self,
+        ephemeral_public: &RistrettoPoint,
+        output_public: &RistrettoPoint,
+    ) -> Option<Scalar> {
+        self.scan_subaddress(ephemeral_public, output_public, CHANGE_SUBADDRESS_INDEX)
+    }
+    
+    /// Subaddress spend-key offset for `index`
+    fn subaddress_offset(&self, index: u32) -> Scalar {
+        subaddress_offset(self.hash_algo, &self.view_private, index)
+    }
+    
+    /// Export a token giving read-only access to this wallet
+    ///
+    /// The token carries the view private key, amount key and spend public
+    /// key: everything needed to detect incoming outputs and read their
+    /// amounts, nothing that can spend them. Encoded as base58 with a
+    /// version byte and 4-byte checksum.
+    pub fn export_auditor_token(&self) -> String {
+        self.view_key().to_token()
+    }
+    
+    /// Key that detects incoming outputs without revealing their amounts
+    ///
+    /// The view key without its amount key: a holder learns that a payment
+    /// arrived but not how much it was.
+    pub fn export_detection_only_key(&self) -> ViewKey {
+        ViewKey {
+            amount_private: None,
+            ..self.view_key()
+        }
+    }
+    
+    /// Decrypt an amount encrypted to our main address for `tx`
+    pub fn decrypt_amount(&self, tx: &StealthTransaction, encrypted: &[u8]) -> Result<u64> {
+        let (&id, ciphertext) = encrypted.split_first()
+            .ok_or_else(|| CoreError::Serialization("Empty encrypted amount".into()))?;
+        
+        let scheme = AmountScheme::from_id(id)?;
+        let secret = match scheme {
+            AmountScheme::ChaCha20Sha256 => self.spend_private,
+            AmountScheme::ChaCha20AmountKey => self.amount_private(),
+        };
+        decrypt_amount_with(scheme, &(secret * tx.ephemeral_public), tx, ciphertext)
+    }
+    
+    /// View-only key for this master key
+    pub fn view_key(&self) -> ViewKey {
+        ViewKey {
+            view_private: self.view_private,
+            amount_private: Some(self.amount_private()),
+            spend_public: self.spend_public,
+            hash_algo: self.hash_algo,
+        }
+    }
+    
+    /// Scan output `output_index` of a multi-output transaction
+    ///
+    /// Uses our `output_counter` encoding; an output built with another
+    /// encoding is not recognized. Returns the one-time private key h + s.
+    pub fn scan_output(
+        &self,
+        ephemeral_public: &RistrettoPoint,
+        output_public: &RistrettoPoint,
+        output_index: u64,
+    ) -> Option<Scalar> {
+        let counter = self.output_counter.encode(output_index).ok()?;
+        let hash_scalar = output_hash(self.hash_algo, &(self.view_private * ephemeral_public), &counter);
+        
+        if hash_scalar * G + self.spend_public == *output_public {
+            Some(hash_scalar + self.spend_private)
+        } else {
+            None
         }
     }
     
@@ -103,7 +493,7 @@ impl StealthMasterKey {
         let shared_secret = self.view_private * ephemeral_public;
         
         // Hash shared secret to scalar
-        let hash_scalar = Self::hash_to_scalar(shared_secret.compress().as_bytes());
+        let hash_scalar = self.hash_algo.hash_to_scalar(shared_secret.compress().as_bytes());
         
         // Check if output belongs to us
         let expected_public = hash_scalar * G + self.spend_public;
@@ -116,15 +506,215 @@ impl StealthMasterKey {
             None
         }
     }
+}
+
+/// Subaddress spend-key offset m = H("Zetaris-SubAddr-v1" || v || index)
+fn subaddress_offset(hash_algo: StealthHashAlgo, view_private: &Scalar, index: u32) -> Scalar {
+    let mut data = Vec::with_capacity(18 + 32 + 4);
+    data.extend_from_slice(b"Zetaris-SubAddr-v1");
+    data.extend_from_slice(view_private.as_bytes());
+    data.extend_from_slice(&index.to_le_bytes());
+    hash_algo.hash_to_scalar(&data)
+}
+
+/// Version byte of an auditor token without an amount key
+const DETECTION_TOKEN_VERSION: u8 = 0x01;
+
+/// Version byte of an auditor token that carries the amount key
+const AUDITOR_TOKEN_VERSION: u8 = 0x02;
+
+/// View-only key: detects incoming outputs but cannot spend them
+///
+/// This is what an auditor receives via `StealthMasterKey::export_auditor_token`,
+/// and it reads amounts encrypted to the address's amount key. A
+/// detection-only key (`StealthMasterKey::export_detection_only_key`) lacks
+/// the amount key and can only flag outputs.
+#[derive(Clone, ZeroizeOnDrop)]
+pub struct ViewKey {
+    view_private: Scalar,
+    /// Amount key a, absent from detection-only keys
+    amount_private: Option<Scalar>,
+    /// Spend public key S of the watched wallet
+    pub spend_public: RistrettoPoint,
+    #[zeroize(skip)]
+    pub hash_algo: StealthHashAlgo,
+}
+
+impl ViewKey {
+    /// Encode as a versioned, checksummed base58 token
+    pub fn to_token(&self) -> String {
+        let mut payload = Vec::with_capacity(2 + 32 + 32 + 32 + 4);
+        payload.push(if self.amount_private.is_some() { AUDITOR_TOKEN_VERSION } else { DETECTION_TOKEN_VERSION });
+        payload.push(self.hash_algo.to_bits());
+        payload.extend_from_slice(self.view_private.as_bytes());
+        payload.extend_from_slice(&compress_point(&self.spend_public));
+        if let Some(amount_private) = &self.amount_private {
+            payload.extend_from_slice(amount_private.as_bytes());
+        }
+        
+        let checksum = token_checksum(&payload);
+        payload.extend_from_slice(&checksum);
+        
+        let token = bs58::encode(&payload).into_string();
+        payload.zeroize();
+        token
+    }
     
-    /// Hash bytes to scalar using BLAKE2b
-    fn hash_to_scalar(data: &[u8]) -> Scalar {
-        use blake2::{Blake2b512, Digest};
-        let hash = Blake2b512::digest(data);
-        let mut hash_bytes = [0u8; 64];
-        hash_bytes.copy_from_slice(hash.as_slice());
-        Scalar::from_bytes_mod_order_wide(&hash_bytes)
+    /// Decode a token produced by `to_token`
+    pub fn from_token(token: &str) -> Result<Self> {
+        let mut bytes = bs58::decode(token)
+            .into_vec()
+            .map_err(|e| CoreError::Serialization(format!("Invalid base58: {}", e)))?;
+        
+        let result = Self::parse_token_bytes(&bytes);
+        bytes.zeroize();
+        result
+    }
+    
+    fn parse_token_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 2 + 32 + 32 + 4 {
+            return Err(CoreError::Serialization("Invalid auditor token length".into()));
+        }
+        
+        let (payload, checksum) = bytes.split_at(bytes.len() - 4);
+        if token_checksum(payload) != checksum {
+            return Err(CoreError::Serialization("Auditor token checksum mismatch".into()));
+        }
+        
+        let expected_len = match payload[0] {
+            DETECTION_TOKEN_VERSION => 2 + 32 + 32,
+            AUDITOR_TOKEN_VERSION => 2 + 32 + 32 + 32,
+            other => return Err(CoreError::Serialization(format!("Unsupported auditor token version: {}", other))),
+        };
+        if payload.len() != expected_len {
+            return Err(CoreError::Serialization("Invalid auditor token length".into()));
+        }
+        
+        let hash_algo = StealthHashAlgo::from_bits(payload[1])?;
+        let view_private = parse_private_scalar(&payload[2..34], "view private key")?;
+        let amount_private = match payload.get(66..98) {
+            Some(bytes) => Some(parse_private_scalar(bytes, "amount key")?),
+            None => None,
+        };
+        
+        Ok(ViewKey {
+            view_private,
+            amount_private,
+            spend_public: decompress_point_slice(&payload[34..66])?,
+            hash_algo,
+        })
+    }
+    
+    /// Whether this key can read amounts, or only detect outputs
+    pub fn can_read_amounts(&self) -> bool {
+        self.amount_private.is_some()
     }
+    
+    /// Decrypt an amount encrypted to the watched address's amount key
+    ///
+    /// Fails for detection-only keys, and for amounts encrypted under the
+    /// spend key by senders that did not have the amount key.
+    pub fn decrypt_amount(&self, tx: &StealthTransaction, encrypted: &[u8]) -> Result<u64> {
+        let (&id, ciphertext) = encrypted.split_first()
+            .ok_or_else(|| CoreError::Serialization("Empty encrypted amount".into()))?;
+        
+        let scheme = AmountScheme::from_id(id)?;
+        let amount_private = match (scheme, &self.amount_private) {
+            (AmountScheme::ChaCha20AmountKey, Some(amount_private)) => amount_private,
+            (AmountScheme::ChaCha20AmountKey, None) => {
+                return Err(CoreError::Crypto("Detection-only key cannot read amounts".into()));
+            }
+            (AmountScheme::ChaCha20Sha256, _) => {
+                return Err(CoreError::Crypto("Amount is encrypted to the spend key".into()));
+            }
+        };
+        decrypt_amount_with(scheme, &(amount_private * tx.ephemeral_public), tx, ciphertext)
+    }
+    
+    /// Check whether an output was sent to the watched main address
+    pub fn owns_output(&self, ephemeral_public: &RistrettoPoint, output_public: &RistrettoPoint) -> bool {
+        let shared_secret = self.view_private * ephemeral_public;
+        let hash_scalar = self.hash_algo.hash_to_scalar(shared_secret.compress().as_bytes());
+        
+        hash_scalar * G + self.spend_public == *output_public
+    }
+    
+    /// Check whether an output was sent to the watched subaddress at `index`
+    pub fn owns_subaddress_output(
+        &self,
+        ephemeral_public: &RistrettoPoint,
+        output_public: &RistrettoPoint,
+        index: u32,
+    ) -> bool {
+        let shared_secret = self.view_private * ephemeral_public;
+        let hash_scalar = self.hash_algo.hash_to_scalar(shared_secret.compress().as_bytes());
+        let offset = subaddress_offset(self.hash_algo, &self.view_private, index);
+        
+        hash_scalar * G + self.spend_public + offset * G == *output_public
+    }
+    
+    /// Return the positions of transactions paying the watched main address
+    pub fn scan_transactions(&self, transactions: &[StealthTransaction]) -> Vec<usize> {
+        transactions.iter()
+            .enumerate()
+            .filter(|(_, tx)| self.owns_output(&tx.ephemeral_public, &tx.one_time_public))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// Parse a canonical private scalar from a token, zeroizing the copy
+fn parse_private_scalar(bytes: &[u8], what: &str) -> Result<Scalar> {
+    let mut scalar_bytes = [0u8; 32];
+    scalar_bytes.copy_from_slice(bytes);
+    let scalar = Option::<Scalar>::from(Scalar::from_canonical_bytes(scalar_bytes))
+        .ok_or_else(|| CoreError::Serialization(format!("Invalid {}", what)));
+    scalar_bytes.zeroize();
+    scalar
+}
+
+/// Amount encryption key K = SHA-256(label || shared secret || P)
+///
+/// The shared secret is r·S for `ChaCha20Sha256` (spend key only) and r·A
+/// for `ChaCha20AmountKey` (view key); the labels keep the two apart.
+/// Binding P stops a ciphertext being replayed onto another output.
+fn amount_key(scheme: AmountScheme, shared_secret: &RistrettoPoint, one_time_public: &RistrettoPoint) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+    let label: &[u8] = match scheme {
+        AmountScheme::ChaCha20Sha256 => b"Zetaris-Amount-v1",
+        AmountScheme::ChaCha20AmountKey => b"Zetaris-Amount-v2",
+    };
+    Sha256::new()
+        .chain_update(label)
+        .chain_update(compress_point(shared_secret))
+        .chain_update(compress_point(one_time_public))
+        .finalize()
+        .into()
+}
+
+/// Decrypt an amount ciphertext given the recipient's side of the shared secret
+fn decrypt_amount_with(
+    scheme: AmountScheme,
+    shared_secret: &RistrettoPoint,
+    tx: &StealthTransaction,
+    ciphertext: &[u8],
+) -> Result<u64> {
+    let mut key = amount_key(scheme, shared_secret, &tx.one_time_public);
+    let decrypted = ChaCha20Cipher::new(&key).decrypt(ciphertext);
+    key.zeroize();
+    
+    let bytes: [u8; 8] = decrypted?.try_into()
+        .map_err(|_| CoreError::Crypto("Invalid encrypted amount length".into()))?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// First 4 bytes of SHA-256(SHA-256(payload))
+fn token_checksum(payload: &[u8]) -> [u8; 4] {
+    use sha2::{Digest, Sha256};
+    let hash = Sha256::digest(Sha256::digest(payload));
+    let mut checksum = [0u8; 4];
+    checksum.copy_from_slice(&hash[..4]);
+    checksum
 }
 
 /// Stealth address (public keys only, safe to share)
@@ -132,6 +722,17 @@ impl StealthMasterKey {
 pub struct StealthAddress {
     pub spend_public: RistrettoPoint,
     pub view_public: RistrettoPoint,
+    #[serde(default)]
+    pub hash_algo: StealthHashAlgo,
+    /// Encoding of the output index in multi-output transactions
+    #[serde(default)]
+    pub output_counter: OutputCounter,
+    /// Subaddresses require the ephemeral key to be built on the spend key
+    #[serde(default)]
+    pub is_subaddress: bool,
+    /// Amount key A; amounts encrypted to it are readable with the view key
+    #[serde(default)]
+    pub amount_public: Option<RistrettoPoint>,
 }
 
 impl StealthAddress {
@@ -143,18 +744,30 @@ impl StealthAddress {
     /// 3. Hash to scalar: h = H(σ)
     /// 4. Derive one-time public key: P = h·G + S
     /// 5. Return (R, P) - sender includes R in transaction, sends to P
+    ///
+    /// For subaddresses the ephemeral public key is R = r·D instead, so the
+    /// recipient's v·R matches the sender's r·C.
     pub fn generate_one_time_address(&self) -> StealthTransaction {
         // Generate ephemeral keypair
         let mut ephemeral_bytes = [0u8; 32];
         rand::thread_rng().fill(&mut ephemeral_bytes);
-        let ephemeral_private = Scalar::from_bytes_mod_order(ephemeral_bytes);
-        let ephemeral_public = ephemeral_private * G;
+        self.one_time_address_from_ephemeral(Scalar::from_bytes_mod_order(ephemeral_bytes))
+    }
+    
+    /// Derive the one-time address for a caller-chosen ephemeral private key
+    ///
+    /// Deterministic counterpart of `generate_one_time_address`; reusing an
+    /// ephemeral key links outputs, so only use this with fresh randomness
+    /// or for test vectors.
+    pub fn one_time_address_from_ephemeral(&self, ephemeral_private: Scalar) -> StealthTransaction {
+        let ephemeral_base = if self.is_subaddress { self.spend_public } else { G };
+        let ephemeral_public = ephemeral_private * ephemeral_base;
         
         // Compute shared secret
         let shared_secret = ephemeral_private * self.view_public;
         
         // Hash to scalar
-        let hash_scalar = StealthMasterKey::hash_to_scalar(shared_secret.compress().as_bytes());
+        let hash_scalar = self.hash_algo.hash_to_scalar(shared_secret.compress().as_bytes());
         
         // Derive one-time public key
         let one_time_public = hash_scalar * G + self.spend_public;
@@ -166,46 +779,215 @@ impl StealthAddress {
         }
     }
     
-    /// Serialize to bytes (66 bytes: 33 + 33 compressed points)
+    /// Derive the one-time address of output `output_index` in a
+    /// multi-output transaction sharing one ephemeral key
+    ///
+    /// P = H(σ || index)·G + S, with the index encoded as the address's
+    /// `output_counter` says. Fails if the index does not fit the counter.
+    pub fn one_time_address_for_output(&self, ephemeral_private: Scalar, output_index: u64) -> Result<StealthTransaction> {
+        let ephemeral_base = if self.is_subaddress { self.spend_public } else { G };
+        let shared_secret = ephemeral_private * self.view_public;
+        let hash_scalar = output_hash(self.hash_algo, &shared_secret, &self.output_counter.encode(output_index)?);
+        
+        Ok(StealthTransaction {
+            ephemeral_public: ephemeral_private * ephemeral_base,
+            one_time_public: hash_scalar * G + self.spend_public,
+            ephemeral_private: Some(ephemeral_private),
+        })
+    }
+    
+    /// Encrypt `amount` for the recipient of `tx`, an output we generated
+    ///
+    /// Uses the address's amount key when it has one, so the recipient's
+    /// view key can read it; otherwise the spend key. Only main addresses
+    /// are supported: a subaddress's ephemeral key R = r·D is public, so
+    /// r·D cannot serve as a secret.
+    pub fn encrypt_amount(&self, tx: &StealthTransaction, amount: u64) -> Result<Vec<u8>> {
+        let scheme = if self.amount_public.is_some() {
+            AmountScheme::ChaCha20AmountKey
+        } else {
+            AmountScheme::default()
+        };
+        self.encrypt_amount_with(tx, amount, scheme)
+    }
+    
+    /// Encrypt `amount` like `encrypt_amount`, with a chosen scheme
+    pub fn encrypt_amount_with(&self, tx: &StealthTransaction, amount: u64, scheme: AmountScheme) -> Result<Vec<u8>> {
+        if self.is_subaddress {
+            return Err(CoreError::InvalidParameter("Amount encryption to subaddresses is not supported".into()));
+        }
+        let ephemeral_private = tx.ephemeral_private
+            .ok_or_else(|| CoreError::InvalidParameter("Transaction has no ephemeral private key".into()))?;
+        
+        let recipient_key = match scheme {
+            AmountScheme::ChaCha20Sha256 => self.spend_public,
+            AmountScheme::ChaCha20AmountKey => self.amount_public
+                .ok_or_else(|| CoreError::InvalidParameter("Address has no amount key".into()))?,
+        };
+        let mut key = amount_key(scheme, &(ephemeral_private * recipient_key), &tx.one_time_public);
+        let encrypted = ChaCha20Cipher::new(&key).encrypt(&amount.to_le_bytes());
+        key.zeroize();
+        let encrypted = encrypted?;
+        
+        let mut blob = vec![scheme.id()];
+        blob.extend_from_slice(&encrypted);
+        Ok(blob)
+    }
+    
+    /// Serialize to bytes (marker, version, 32 + 32 compressed points, and
+    /// 32 more for the amount key: 66 or 98 bytes)
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(64);
-        bytes.extend_from_slice(self.spend_public.compress().as_bytes());
-        bytes.extend_from_slice(self.view_public.compress().as_bytes());
+        let mut bytes = Vec::with_capacity(98);
+        bytes.push(ADDRESS_FORMAT_MARKER);
+        bytes.push(self.version_byte());
+        bytes.extend_from_slice(&compress_point(&self.spend_public));
+        bytes.extend_from_slice(&compress_point(&self.view_public));
+        if let Some(amount_public) = &self.amount_public {
+            bytes.extend_from_slice(&compress_point(amount_public));
+        }
         bytes
     }
     
     /// Deserialize from bytes
+    ///
+    /// Accepts both the versioned encoding and legacy 64-byte addresses,
+    /// which always use BLAKE2b.
     pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
-        if bytes.len() != 64 {
-            return Err(CoreError::Serialization("Invalid stealth address length".into()));
-        }
+        let (version, keys) = match bytes.len() {
+            64 => (0, bytes),
+            66 | 98 if bytes[0] == ADDRESS_FORMAT_MARKER => (bytes[1], &bytes[2..]),
+            _ => return Err(CoreError::Serialization("Invalid stealth address length".into())),
+        };
         
-        let spend_compressed = CompressedRistretto::from_slice(&bytes[0..32])
-            .map_err(|_| CoreError::Serialization("Invalid spend public key".into()))?;
-        let view_compressed = CompressedRistretto::from_slice(&bytes[32..64])
-            .map_err(|_| CoreError::Serialization("Invalid view public key".into()))?;
+        if version & !VERSION_KNOWN_BITS != 0 {
+            return Err(CoreError::Serialization(format!("Unsupported stealth address version: {:#04x}", version)));
+        }
+        if (version & VERSION_AMOUNT_KEY_FLAG != 0) != (keys.len() == 96) {
+            return Err(CoreError::Serialization("Stealth address length does not match its version".into()));
+        }
         
-        let spend_public = spend_compressed.decompress()
-            .ok_or_else(|| CoreError::Serialization("Failed to decompress spend key".into()))?;
-        let view_public = view_compressed.decompress()
-            .ok_or_else(|| CoreError::Serialization("Failed to decompress view key".into()))?;
+        let spend_public = decompress_point_slice(&keys[0..32])?;
+        let view_public = decompress_point_slice(&keys[32..64])?;
+        let amount_public = match keys.get(64..96) {
+            Some(key) => Some(decompress_point_slice(key)?),
+            None => None,
+        };
         
         Ok(StealthAddress {
             spend_public,
             view_public,
+            hash_algo: StealthHashAlgo::from_bits(version)?,
+            output_counter: OutputCounter::from_bits(version)?,
+            is_subaddress: version & VERSION_SUBADDRESS_FLAG != 0,
+            amount_public,
         })
     }
     
+    /// Pack address options into the version byte
+    fn version_byte(&self) -> u8 {
+        let mut version = self.hash_algo.to_bits() | self.output_counter.to_bits();
+        if self.is_subaddress {
+            version |= VERSION_SUBADDRESS_FLAG;
+        }
+        if self.amount_public.is_some() {
+            version |= VERSION_AMOUNT_KEY_FLAG;
+        }
+        version
+    }
+    
     /// Encode as base58 string (for display/sharing)
+    ///
+    /// The versioned bytes are followed by a 4-byte double-SHA256 checksum.
     pub fn to_base58(&self) -> String {
-        bs58::encode(self.to_bytes()).into_string()
+        let mut bytes = self.to_bytes();
+        let checksum = token_checksum(&bytes);
+        bytes.extend_from_slice(&checksum);
+        bs58::encode(bytes).into_string()
     }
     
     /// Decode from base58 string
+    ///
+    /// Checksummed addresses are validated first; older encodings without a
+    /// checksum are still accepted.
     pub fn from_base58(s: &str) -> Result<Self> {
         let bytes = bs58::decode(s)
             .into_vec()
             .map_err(|e| CoreError::Serialization(format!("Invalid base58: {}", e)))?;
+        
+        if bytes.len() == CHECKED_ADDRESS_LEN || bytes.len() == CHECKED_AMOUNT_ADDRESS_LEN {
+            Self::validate(s)?;
+            return Self::from_bytes(&bytes[..bytes.len() - 4]);
+        }
+        
+        Self::from_bytes(&bytes)
+    }
+    
+    /// Check that a pasted base58 address is safe to pay to
+    ///
+    /// Requires the checksummed encoding, a supported version, and valid
+    /// non-identity points. Each failure maps to its own `AddressError`.
+    pub fn validate(s: &str) -> Result<()> {
+        let bytes = bs58::decode(s)
+            .into_vec()
+            .map_err(|_| CoreError::InvalidAddress(AddressError::Encoding))?;
+        
+        if bytes.len() != CHECKED_ADDRESS_LEN && bytes.len() != CHECKED_AMOUNT_ADDRESS_LEN {
+            return Err(CoreError::InvalidAddress(AddressError::Length));
+        }
+        
+        let (payload, checksum) = bytes.split_at(bytes.len() - 4);
+        if token_checksum(payload) != checksum {
+            return Err(CoreError::InvalidAddress(AddressError::Checksum));
+        }
+        
+        let version = payload[1];
+        let has_amount_key = bytes.len() == CHECKED_AMOUNT_ADDRESS_LEN;
+        if (version & VERSION_AMOUNT_KEY_FLAG != 0) != has_amount_key {
+            return Err(CoreError::InvalidAddress(AddressError::Length));
+        }
+        if payload[0] != ADDRESS_FORMAT_MARKER
+            || version & !VERSION_KNOWN_BITS != 0
+            || StealthHashAlgo::from_bits(version).is_err()
+            || OutputCounter::from_bits(version).is_err()
+        {
+            return Err(CoreError::InvalidAddress(AddressError::Version));
+        }
+        
+        for key in payload[2..].chunks_exact(32) {
+            let key: CompressedPoint = key.try_into().expect("32-byte chunk");
+            let point = decompress_point_allow_identity(&key)
+                .map_err(|_| CoreError::InvalidAddress(AddressError::InvalidPoint))?;
+            if point == RistrettoPoint::identity() {
+                return Err(CoreError::InvalidAddress(AddressError::IdentityPoint));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Encode as lowercase hex
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+    
+    /// Decode from hex
+    pub fn from_hex(s: &str) -> Result<Self> {
+        let bytes = hex::decode(s)
+            .map_err(|e| CoreError::Serialization(format!("Invalid hex: {}", e)))?;
+        Self::from_bytes(&bytes)
+    }
+    
+    /// Encode as standard base64
+    pub fn to_base64(&self) -> String {
+        use base64::Engine;
+        base64::engine::general_purpose::STANDARD.encode(self.to_bytes())
+    }
+    
+    /// Decode from standard base64
+    pub fn from_base64(s: &str) -> Result<Self> {
+        use base64::Engine;
+        let bytes = base64::engine::general_purpose::STANDARD.decode(s)
+            .map_err(|e| CoreError::Serialization(format!("Invalid base64: {}", e)))?;
         Self::from_bytes(&bytes)
     }
 }
@@ -226,8 +1008,8 @@ impl StealthTransaction {
     /// Serialize transaction data (for blockchain inclusion)
     pub fn to_transaction_data(&self) -> Vec<u8> {
         let mut data = Vec::with_capacity(64);
-        data.extend_from_slice(self.ephemeral_public.compress().as_bytes());
-        data.extend_from_slice(self.one_time_public.compress().as_bytes());
+        data.extend_from_slice(&compress_point(&self.ephemeral_public));
+        data.extend_from_slice(&compress_point(&self.one_time_public));
         data
     }
     
@@ -237,15 +1019,8 @@ impl StealthTransaction {
             return Err(CoreError::Serialization("Invalid transaction data length".into()));
         }
         
-        let ephemeral_compressed = CompressedRistretto::from_slice(&data[0..32])
-            .map_err(|_| CoreError::Serialization("Invalid ephemeral key".into()))?;
-        let output_compressed = CompressedRistretto::from_slice(&data[32..64])
-            .map_err(|_| CoreError::Serialization("Invalid output key".into()))?;
-        
-        let ephemeral_public = ephemeral_compressed.decompress()
-            .ok_or_else(|| CoreError::Serialization("Failed to decompress ephemeral key".into()))?;
-        let one_time_public = output_compressed.decompress()
-            .ok_or_else(|| CoreError::Serialization("Failed to decompress output key".into()))?;
+        let ephemeral_public = decompress_point_slice(&data[0..32])?;
+        let one_time_public = decompress_point_slice(&data[32..64])?;
         
         Ok(StealthTransaction {
             ephemeral_public,
@@ -258,13 +1033,46 @@ impl StealthTransaction {
     pub fn destination_address(&self) -> RistrettoPoint {
         self.one_time_public
     }
+    
+    /// Canonical identifier for deduplicating scanned outputs
+    ///
+    /// BLAKE2b-256 over the canonical compressed encodings of R and P, so
+    /// equal points give equal keys however the transaction was encoded.
+    pub fn cache_key(&self) -> [u8; 32] {
+        use blake2::{Blake2b, Digest, digest::consts::U32};
+        Blake2b::<U32>::new()
+            .chain_update(b"Zetaris-Stealth-Cache-v1")
+            .chain_update(compress_point(&self.ephemeral_public))
+            .chain_update(compress_point(&self.one_time_public))
+            .finalize()
+            .into()
+    }
+}
+
+/// A scanned output held in the scanner's cache
+struct CachedOutput {
+    one_time_public: CompressedPoint,
+    private_key: Scalar,
+    /// When the output was last scanned, in scanner ticks
+    last_scanned: u64,
+    /// Exempt from eviction
+    pinned: bool,
 }
 
 /// Stealth address scanner for wallet
 pub struct StealthScanner {
     master_key: StealthMasterKey,
-    /// Cache of scanned outputs (tx_hash -> private_key)
-    scanned_outputs: std::collections::HashMap<Vec<u8>, Scalar>,
+    /// Cache of scanned outputs by cache_key
+    scanned_outputs: std::collections::HashMap<[u8; 32], CachedOutput>,
+    /// Unpinned cache keys, least recently scanned first
+    eviction_order: std::collections::BTreeMap<u64, [u8; 32]>,
+    /// Counter ordering scans for eviction
+    tick: u64,
+    /// Most outputs kept in the cache; unbounded when unset
+    cache_capacity: Option<usize>,
+    /// Pool for parallel scans; the global rayon pool when unset
+    #[cfg(feature = "parallel")]
+    thread_pool: Option<rayon::ThreadPool>,
 }
 
 impl StealthScanner {
@@ -273,7 +1081,96 @@ impl StealthScanner {
         StealthScanner {
             master_key,
             scanned_outputs: std::collections::HashMap::new(),
+            eviction_order: std::collections::BTreeMap::new(),
+            tick: 0,
+            cache_capacity: None,
+            #[cfg(feature = "parallel")]
+            thread_pool: None,
+        }
+    }
+    
+    /// Keep at most `capacity` outputs in the cache
+    ///
+    /// The least recently scanned output is evicted first; pinned outputs
+    /// (see `pin`) are never evicted and may push the cache past
+    /// `capacity`. Evicted outputs stay in the database and can be loaded
+    /// again with `load`.
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache_capacity = Some(capacity);
+        self.evict();
+        self
+    }
+    
+    /// Exempt a cached output from eviction, e.g. while it is unspent
+    ///
+    /// Returns false if the output is not in the cache.
+    pub fn pin(&mut self, tx: &StealthTransaction) -> bool {
+        let Some(cached) = self.scanned_outputs.get_mut(&tx.cache_key()) else {
+            return false;
+        };
+        if !cached.pinned {
+            cached.pinned = true;
+            self.eviction_order.remove(&cached.last_scanned);
+        }
+        true
+    }
+    
+    /// Make a pinned output evictable again, e.g. once it is spent
+    pub fn unpin(&mut self, tx: &StealthTransaction) {
+        let cache_key = tx.cache_key();
+        if let Some(cached) = self.scanned_outputs.get_mut(&cache_key) {
+            if cached.pinned {
+                cached.pinned = false;
+                self.eviction_order.insert(cached.last_scanned, cache_key);
+                self.evict();
+            }
+        }
+    }
+    
+    /// Number of outputs in the cache
+    pub fn cache_len(&self) -> usize {
+        self.scanned_outputs.len()
+    }
+    
+    /// Cache an output as the most recently scanned, then evict if over capacity
+    fn cache_output(&mut self, cache_key: [u8; 32], one_time_public: CompressedPoint, private_key: Scalar) {
+        self.tick += 1;
+        let last_scanned = self.tick;
+        
+        let pinned = match self.scanned_outputs.get(&cache_key) {
+            Some(previous) => {
+                self.eviction_order.remove(&previous.last_scanned);
+                previous.pinned
+            }
+            None => false,
+        };
+        if !pinned {
+            self.eviction_order.insert(last_scanned, cache_key);
         }
+        self.scanned_outputs.insert(cache_key, CachedOutput { one_time_public, private_key, last_scanned, pinned });
+        
+        self.evict();
+    }
+    
+    /// Drop least recently scanned unpinned outputs until within capacity
+    fn evict(&mut self) {
+        let Some(capacity) = self.cache_capacity else {
+            return;
+        };
+        while self.scanned_outputs.len() > capacity {
+            let Some((_, cache_key)) = self.eviction_order.pop_first() else {
+                break;
+            };
+            self.scanned_outputs.remove(&cache_key);
+        }
+    }
+    
+    /// Run parallel scans on `pool` instead of the global rayon pool
+    ///
+    /// Bounds the cores a scan can take, e.g. on a shared scanning server.
+    #[cfg(feature = "parallel")]
+    pub fn set_thread_pool(&mut self, pool: rayon::ThreadPool) {
+        self.thread_pool = Some(pool);
     }
     
     /// Scan a batch of transactions
@@ -281,26 +1178,120 @@ impl StealthScanner {
         &mut self,
         transactions: &[StealthTransaction],
     ) -> Vec<(usize, Scalar)> {
-        let mut owned = Vec::new();
-        
-        for (i, tx) in transactions.iter().enumerate() {
-            if let Some(private_key) = self.master_key.scan_transaction(
-                &tx.ephemeral_public,
-                &tx.one_time_public,
-            ) {
-                // Cache the result
-                let tx_id = tx.to_transaction_data();
-                self.scanned_outputs.insert(tx_id, private_key);
-                owned.push((i, private_key));
-            }
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "scan_batch",
+            transactions = transactions.len(),
+            owned = tracing::field::Empty,
+        );
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+        
+        let owned = self.find_owned(transactions);
+        
+        // Cache the results
+        for &(i, private_key) in &owned {
+            let tx = &transactions[i];
+            self.cache_output(tx.cache_key(), compress_point(&tx.one_time_public), private_key);
         }
         
+        #[cfg(feature = "tracing")]
+        span.record("owned", owned.len());
+        
         owned
     }
     
+    /// Indices and one-time private keys of the owned transactions
+    #[cfg(not(feature = "parallel"))]
+    fn find_owned(&self, transactions: &[StealthTransaction]) -> Vec<(usize, Scalar)> {
+        transactions.iter()
+            .enumerate()
+            .filter_map(|(i, tx)| self.scan_one(i, tx))
+            .collect()
+    }
+    
+    /// Indices and one-time private keys of the owned transactions
+    #[cfg(feature = "parallel")]
+    fn find_owned(&self, transactions: &[StealthTransaction]) -> Vec<(usize, Scalar)> {
+        use rayon::prelude::*;
+        
+        let scan = || transactions.par_iter()
+            .enumerate()
+            .filter_map(|(i, tx)| self.scan_one(i, tx))
+            .collect();
+        match &self.thread_pool {
+            Some(pool) => pool.install(scan),
+            None => scan(),
+        }
+    }
+    
+    fn scan_one(&self, index: usize, tx: &StealthTransaction) -> Option<(usize, Scalar)> {
+        self.master_key
+            .scan_transaction(&tx.ephemeral_public, &tx.one_time_public)
+            .map(|private_key| (index, private_key))
+    }
+    
+    /// Rebuild the cache from stored outputs, e.g. after a restart
+    ///
+    /// Each row's private key must open its one-time public key
+    /// (`x·G == P`); rows that fail, or hold malformed points or scalars,
+    /// are logged and skipped so a corrupted database cannot plant a bad
+    /// key. Returns the number of outputs loaded and skipped.
+    pub fn load(&mut self, outputs: &[StealthOutput]) -> (usize, usize) {
+        let mut loaded = 0;
+        let mut skipped = 0;
+        
+        for output in outputs {
+            match Self::verified_entry(output) {
+                Some((cache_key, (one_time_public, private_key))) => {
+                    self.cache_output(cache_key, one_time_public, private_key);
+                    loaded += 1;
+                }
+                None => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(output_id = output.id, "Skipping stored stealth output whose key does not verify");
+                    skipped += 1;
+                }
+            }
+        }
+        
+        (loaded, skipped)
+    }
+    
+    /// Cache entry for a stored output whose private key matches its public key
+    fn verified_entry(output: &StealthOutput) -> Option<([u8; 32], (CompressedPoint, Scalar))> {
+        let ephemeral_public = decompress_point_slice(&output.ephemeral_public).ok()?;
+        let one_time_public = decompress_point_slice(&output.one_time_public).ok()?;
+        let bytes: [u8; 32] = output.one_time_private.as_slice().try_into().ok()?;
+        let private_key = Option::<Scalar>::from(Scalar::from_canonical_bytes(bytes))?;
+        
+        if private_key * G != one_time_public {
+            return None;
+        }
+        
+        let tx = StealthTransaction {
+            ephemeral_public,
+            one_time_public,
+            ephemeral_private: None,
+        };
+        Some((tx.cache_key(), (compress_point(&one_time_public), private_key)))
+    }
+    
     /// Get private key for previously scanned output
     pub fn get_private_key(&self, tx_data: &[u8]) -> Option<Scalar> {
-        self.scanned_outputs.get(tx_data).copied()
+        let tx = StealthTransaction::from_transaction_data(tx_data).ok()?;
+        self.scanned_outputs.get(&tx.cache_key()).map(|cached| cached.private_key)
+    }
+    
+    /// Build a compact filter over the one-time keys of every scanned output
+    ///
+    /// A server can use it to return only transactions that touch outputs
+    /// this wallet already owns, such as confirmations or spends.
+    pub fn build_filter(&self) -> OutputFilter {
+        let keys: Vec<CompressedPoint> = self.scanned_outputs.values()
+            .map(|cached| cached.one_time_public)
+            .collect();
+        OutputFilter::new(&keys)
     }
 }
 
@@ -341,6 +1332,215 @@ mod tests {
         assert_eq!(address.view_public, decoded.view_public);
     }
     
+    #[test]
+    fn test_validate_address() {
+        let address = StealthMasterKey::generate().get_stealth_address();
+        let encoded = address.to_base58();
+        assert!(StealthAddress::validate(&encoded).is_ok());
+        
+        let mut bytes = bs58::decode(&encoded).into_vec().unwrap();
+        bytes[CHECKED_ADDRESS_LEN - 1] ^= 0x01;
+        let bad_checksum = bs58::encode(&bytes).into_string();
+        assert!(matches!(
+            StealthAddress::validate(&bad_checksum),
+            Err(CoreError::InvalidAddress(AddressError::Checksum))
+        ));
+        assert!(StealthAddress::from_base58(&bad_checksum).is_err());
+        
+        // A well-checksummed address whose spend key is the identity
+        let mut payload = address.to_bytes();
+        payload[2..34].copy_from_slice(&compress_point(&RistrettoPoint::identity()));
+        let checksum = token_checksum(&payload);
+        payload.extend_from_slice(&checksum);
+        let identity = bs58::encode(&payload).into_string();
+        assert!(matches!(
+            StealthAddress::validate(&identity),
+            Err(CoreError::InvalidAddress(AddressError::IdentityPoint))
+        ));
+    }
+    
+    #[test]
+    fn test_keccak_stealth_scanning() {
+        let recipient = StealthMasterKey::generate().with_hash_algo(StealthHashAlgo::Keccak512);
+        
+        // The algorithm travels with the serialized address
+        let address = StealthAddress::from_bytes(&recipient.get_stealth_address().to_bytes()).unwrap();
+        assert_eq!(address.hash_algo, StealthHashAlgo::Keccak512);
+        
+        let tx = address.generate_one_time_address();
+        assert!(recipient.scan_transaction(&tx.ephemeral_public, &tx.one_time_public).is_some());
+        
+        // A recipient expecting a different algorithm does not recognize the output
+        let mismatched = StealthMasterKey::from_keys(recipient.spend_private, recipient.view_private)
+            .with_hash_algo(StealthHashAlgo::Sha512);
+        assert!(mismatched.scan_transaction(&tx.ephemeral_public, &tx.one_time_public).is_none());
+    }
+    
+    #[test]
+    fn test_load_skips_outputs_whose_key_does_not_verify() {
+        let recipient = StealthMasterKey::generate();
+        let transactions: Vec<StealthTransaction> = (0..3)
+            .map(|_| recipient.get_stealth_address().generate_one_time_address())
+            .collect();
+        
+        let mut outputs: Vec<StealthOutput> = transactions.iter().enumerate().map(|(i, tx)| {
+            let private_key = recipient.scan_transaction(&tx.ephemeral_public, &tx.one_time_public).unwrap();
+            StealthOutput {
+                id: i as i64,
+                tx_hash: format!("0x{:02x}", i),
+                account_id: 1,
+                ephemeral_public: compress_point(&tx.ephemeral_public).to_vec(),
+                one_time_public: compress_point(&tx.one_time_public).to_vec(),
+                one_time_private: private_key.to_bytes().to_vec(),
+                amount: "100".to_string(),
+                spent: false,
+                is_change: false,
+                coinbase_height: None,
+                output_index: None,
+            }
+        }).collect();
+        outputs[1].one_time_private[0] ^= 1;
+        
+        let mut scanner = StealthScanner::new(StealthMasterKey::from_keys(recipient.spend_private, recipient.view_private));
+        assert_eq!(scanner.load(&outputs), (2, 1));
+        
+        assert!(scanner.get_private_key(&transactions[0].to_transaction_data()).is_some());
+        assert!(scanner.get_private_key(&transactions[1].to_transaction_data()).is_none());
+        assert!(scanner.get_private_key(&transactions[2].to_transaction_data()).is_some());
+    }
+    
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_custom_thread_pool_matches_global_pool() {
+        let recipient = StealthMasterKey::generate();
+        let other = StealthMasterKey::generate().get_stealth_address();
+        let transactions: Vec<StealthTransaction> = (0..32)
+            .map(|i| if i % 3 == 0 {
+                recipient.get_stealth_address().generate_one_time_address()
+            } else {
+                other.generate_one_time_address()
+            })
+            .collect();
+        
+        let mut global = StealthScanner::new(StealthMasterKey::from_keys(recipient.spend_private, recipient.view_private));
+        let mut bounded = StealthScanner::new(StealthMasterKey::from_keys(recipient.spend_private, recipient.view_private));
+        bounded.set_thread_pool(rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap());
+        
+        let expected = global.scan_transactions(&transactions);
+        assert_eq!(expected.len(), 11);
+        assert_eq!(bounded.scan_transactions(&transactions), expected);
+    }
+    
+    #[test]
+    fn test_change_and_receive_subaddresses() {
+        let key = StealthMasterKey::generate();
+        let receive = key.get_subaddress(0);
+        let change = StealthAddress::from_bytes(&key.get_change_address().to_bytes()).unwrap();
+        
+        assert!(change.is_subaddress);
+        assert_ne!(receive.spend_public, change.spend_public);
+        assert_ne!(change.spend_public, key.spend_public);
+        
+        let payment = receive.generate_one_time_address();
+        let private = key.scan_subaddress(&payment.ephemeral_public, &payment.one_time_public, 0).unwrap();
+        assert_eq!(private * G, payment.one_time_public);
+        assert!(key.scan_change(&payment.ephemeral_public, &payment.one_time_public).is_none());
+        
+        let change_tx = change.generate_one_time_address();
+        let private = key.scan_change(&change_tx.ephemeral_public, &change_tx.one_time_public).unwrap();
+        assert_eq!(private * G, change_tx.one_time_public);
+        assert!(key.scan_subaddress(&change_tx.ephemeral_public, &change_tx.one_time_public, 0).is_none());
+    }
+    
+    #[test]
+    fn test_detection_only_key_cannot_read_amounts() {
+        let wallet = StealthMasterKey::generate();
+        let address = StealthAddress::from_base58(&wallet.get_stealth_address().to_base58()).unwrap();
+        assert!(address.amount_public.is_some());
+        
+        let tx = address.generate_one_time_address();
+        let encrypted = address.encrypt_amount(&tx, 1_500).unwrap();
+        assert_eq!(encrypted[0], AmountScheme::ChaCha20AmountKey.id());
+        let received = StealthTransaction::from_transaction_data(&tx.to_transaction_data()).unwrap();
+        
+        // The view key, also after a token round trip, reads the amount
+        let view = ViewKey::from_token(&wallet.export_auditor_token()).unwrap();
+        assert!(view.can_read_amounts());
+        assert_eq!(view.decrypt_amount(&received, &encrypted).unwrap(), 1_500);
+        assert_eq!(wallet.decrypt_amount(&received, &encrypted).unwrap(), 1_500);
+        
+        // The detection key flags the output but cannot read the amount
+        let detection = ViewKey::from_token(&wallet.export_detection_only_key().to_token()).unwrap();
+        assert!(!detection.can_read_amounts());
+        assert_eq!(detection.scan_transactions(std::slice::from_ref(&received)), vec![0]);
+        assert!(matches!(detection.decrypt_amount(&received, &encrypted), Err(CoreError::Crypto(_))));
+        
+        // Nor can the view private key paired with another spend key
+        let guess = StealthMasterKey::from_keys(crate::commitments::random_scalar(), wallet.view_private);
+        assert!(guess.decrypt_amount(&received, &encrypted).is_err());
+        
+        // Amounts sent to the spend key stay out of the view key's reach
+        let spend_only = address.encrypt_amount_with(&tx, 7, AmountScheme::ChaCha20Sha256).unwrap();
+        assert_eq!(wallet.decrypt_amount(&received, &spend_only).unwrap(), 7);
+        assert!(view.decrypt_amount(&received, &spend_only).is_err());
+        
+        assert!(wallet.get_subaddress(1).encrypt_amount(&tx, 1_500).is_err());
+        assert!(address.encrypt_amount(&received, 1_500).is_err());
+    }
+    
+    #[test]
+    fn test_encrypted_amount_carries_scheme() {
+        let wallet = StealthMasterKey::generate();
+        let address = wallet.get_stealth_address();
+        let tx = address.generate_one_time_address();
+        
+        let mut encrypted = address.encrypt_amount_with(&tx, 42, AmountScheme::ChaCha20Sha256).unwrap();
+        assert_eq!(encrypted[0], AmountScheme::ChaCha20Sha256.id());
+        assert_eq!(wallet.decrypt_amount(&tx, &encrypted).unwrap(), 42);
+        
+        encrypted[0] = 0xff;
+        assert!(matches!(wallet.decrypt_amount(&tx, &encrypted), Err(CoreError::Serialization(_))));
+        assert!(wallet.decrypt_amount(&tx, &[]).is_err());
+    }
+    
+    #[test]
+    fn test_auditor_token_rejects_corruption() {
+        let token = StealthMasterKey::generate().export_auditor_token();
+        
+        let mut bytes = bs58::decode(&token).into_vec().unwrap();
+        bytes[10] ^= 0x01;
+        let corrupted = bs58::encode(&bytes).into_string();
+        
+        assert!(ViewKey::from_token(&token).is_ok());
+        assert!(matches!(ViewKey::from_token(&corrupted), Err(CoreError::Serialization(_))));
+    }
+    
+    #[test]
+    fn test_legacy_address_bytes_accepted() {
+        let key = StealthMasterKey::generate();
+        let bytes = key.get_stealth_address().to_bytes();
+        
+        let legacy = StealthAddress::from_bytes(&bytes[2..66]).unwrap();
+        assert_eq!(legacy.spend_public, key.spend_public);
+        assert_eq!(legacy.hash_algo, StealthHashAlgo::Blake2b);
+    }
+    
+    #[test]
+    fn test_stealth_address_hex_and_base64() {
+        let key = StealthMasterKey::generate();
+        let address = key.get_stealth_address();
+        
+        let from_hex = StealthAddress::from_hex(&address.to_hex()).unwrap();
+        assert_eq!(address.spend_public, from_hex.spend_public);
+        assert_eq!(address.view_public, from_hex.view_public);
+        
+        let from_base64 = StealthAddress::from_base64(&address.to_base64()).unwrap();
+        assert_eq!(address.spend_public, from_base64.spend_public);
+        assert_eq!(address.view_public, from_base64.view_public);
+        
+        assert!(StealthAddress::from_hex("0xnothex").is_err());
+    }
+    
     #[test]
     fn test_one_time_address_generation() {
         let recipient = StealthMasterKey::generate();
@@ -425,6 +1625,51 @@ mod tests {
         assert_eq!(owned.len(), 5);
     }
     
+    #[test]
+    fn test_cache_capacity_evicts_least_recent_unpinned() {
+        let recipient = StealthMasterKey::generate();
+        let address = recipient.get_stealth_address();
+        let transactions: Vec<StealthTransaction> = (0..200)
+            .map(|_| address.generate_one_time_address())
+            .collect();
+        
+        let mut scanner = StealthScanner::new(StealthMasterKey::from_keys(recipient.spend_private, recipient.view_private))
+            .with_cache_capacity(100);
+        scanner.scan_transactions(&transactions[..1]);
+        assert!(scanner.pin(&transactions[0]));
+        
+        assert_eq!(scanner.scan_transactions(&transactions[1..]).len(), 199);
+        assert_eq!(scanner.cache_len(), 100);
+        
+        let cached = |tx: &StealthTransaction| scanner.get_private_key(&tx.to_transaction_data()).is_some();
+        assert!(cached(&transactions[0]));
+        assert!(!cached(&transactions[1]));
+        assert!(!cached(&transactions[100]));
+        assert!(cached(&transactions[101]));
+        assert!(cached(&transactions[199]));
+        
+        scanner.unpin(&transactions[0]);
+        assert_eq!(scanner.cache_len(), 100);
+    }
+    
+    #[test]
+    fn test_cache_key_depends_only_on_points() {
+        let recipient = StealthMasterKey::generate();
+        let address = recipient.get_stealth_address();
+        
+        // The sender's copy still holds r; the received copy does not
+        let sent = address.generate_one_time_address();
+        let data = sent.to_transaction_data();
+        let received = StealthTransaction::from_transaction_data(&data).unwrap();
+        assert_eq!(sent.cache_key(), received.cache_key());
+        assert_ne!(sent.cache_key(), address.generate_one_time_address().cache_key());
+        
+        let mut scanner = StealthScanner::new(recipient);
+        scanner.scan_transactions(&[sent, received]);
+        assert_eq!(scanner.scanned_outputs.len(), 1);
+        assert!(scanner.get_private_key(&data).is_some());
+    }
+    
     #[test]
     fn test_mixed_transaction_scanning() {
         let recipient1 = StealthMasterKey::generate();
@@ -434,10 +1679,11 @@ mod tests {
         let address2 = recipient2.get_stealth_address();
         
         // Create mixed batch of transactions
-        let mut transactions = Vec::new();
-        transactions.push(address1.generate_one_time_address()); // belongs to recipient1
-        transactions.push(address2.generate_one_time_address()); // belongs to recipient2
-        transactions.push(address1.generate_one_time_address()); // belongs to recipient1
+        let transactions = vec![
+            address1.generate_one_time_address(), // belongs to recipient1
+            address2.generate_one_time_address(), // belongs to recipient2
+            address1.generate_one_time_address(), // belongs to recipient1
+        ];
         
         // Recipient1 scans
         let mut scanner1 = StealthScanner::new(recipient1);
@@ -449,6 +1695,31 @@ mod tests {
         assert_eq!(owned1[1].0, 2);
     }
     
+    #[test]
+    fn test_output_counter_must_match() {
+        let counter = OutputCounter { width: CounterWidth::Eight, big_endian: true };
+        let recipient = StealthMasterKey::generate().with_output_counter(counter);
+        let address = StealthAddress::from_bytes(&recipient.get_stealth_address().to_bytes()).unwrap();
+        assert_eq!(address.output_counter, counter);
+        
+        let r = Scalar::from_bytes_mod_order([7u8; 32]);
+        for index in 0..3 {
+            let tx = address.one_time_address_for_output(r, index).unwrap();
+            let key = recipient.scan_output(&tx.ephemeral_public, &tx.one_time_public, index).unwrap();
+            assert_eq!(key * G, tx.one_time_public);
+        }
+        
+        // Same keys expecting the default 4-byte counter miss the output
+        let tx = address.one_time_address_for_output(r, 1).unwrap();
+        let mismatched = StealthMasterKey::from_keys(recipient.spend_private, recipient.view_private);
+        assert!(mismatched.scan_output(&tx.ephemeral_public, &tx.one_time_public, 1).is_none());
+        
+        // An index too large for a 1-byte counter is rejected by the sender
+        let mut narrow = address.clone();
+        narrow.output_counter = OutputCounter { width: CounterWidth::One, big_endian: false };
+        assert!(narrow.one_time_address_for_output(r, 300).is_err());
+    }
+    
     #[test]
     fn test_master_key_restoration() {
         let original = StealthMasterKey::generate();
@@ -469,4 +1740,64 @@ mod tests {
         assert_eq!(original.spend_public, restored.spend_public);
         assert_eq!(original.view_public, restored.view_public);
     }
+    
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_scan_span_carries_only_counts() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        
+        type Spans = Arc<Mutex<Vec<(&'static str, Vec<(String, String)>)>>>;
+        
+        struct Fields<'a>(&'a mut Vec<(String, String)>);
+        impl Visit for Fields<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0.push((field.name().to_string(), format!("{:?}", value)));
+            }
+        }
+        
+        struct Recorder(Spans);
+        impl tracing::Subscriber for Recorder {
+            fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+                let mut spans = self.0.lock().unwrap();
+                let mut fields = Vec::new();
+                attrs.record(&mut Fields(&mut fields));
+                spans.push((attrs.metadata().name(), fields));
+                Id::from_u64(spans.len() as u64)
+            }
+            fn record(&self, id: &Id, values: &Record<'_>) {
+                let mut spans = self.0.lock().unwrap();
+                values.record(&mut Fields(&mut spans[id.into_u64() as usize - 1].1));
+            }
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, _: &tracing::Event<'_>) {}
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+        
+        let recipient = StealthMasterKey::generate();
+        let secrets = [hex::encode(recipient.export_spend_private()), hex::encode(recipient.export_view_private())];
+        let address = recipient.get_stealth_address();
+        let transactions: Vec<_> = (0..3).map(|_| address.generate_one_time_address()).collect();
+        let mut scanner = StealthScanner::new(recipient);
+        
+        let spans = Spans::default();
+        tracing::subscriber::with_default(Recorder(spans.clone()), || {
+            scanner.scan_transactions(&transactions);
+        });
+        
+        let spans = spans.lock().unwrap();
+        let (_, fields) = spans.iter().find(|(name, _)| *name == "scan_batch").expect("scan span");
+        assert!(fields.contains(&("transactions".to_string(), "3".to_string())));
+        assert!(fields.contains(&("owned".to_string(), "3".to_string())));
+        
+        for (_, value) in spans.iter().flat_map(|(_, fields)| fields) {
+            assert!(value.parse::<u64>().is_ok(), "non-count span field {}", value);
+            assert!(secrets.iter().all(|secret| !value.contains(secret.as_str())));
+        }
+    }
 }
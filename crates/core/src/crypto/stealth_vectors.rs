@@ -0,0 +1,116 @@
+//! Stealth Address Test Vectors
+//!
+//! Fixed inputs and the exact keys they must produce. A failure here means
+//! hash-to-scalar, subaddress or one-time key derivation changed, which
+//! breaks every address and output already on chain.
+
+use curve25519_dalek::scalar::Scalar;
+use super::point::compress_point;
+use super::stealth::{StealthHashAlgo, StealthMasterKey, CHANGE_SUBADDRESS_INDEX};
+
+/// Spend private key shared by all vectors
+const SPEND_PRIVATE: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+
+/// View private key shared by all vectors
+const VIEW_PRIVATE: &str = "0202020202020202020202020202020202020202020202020202020202020202";
+
+struct Vector {
+    hash_algo: StealthHashAlgo,
+    /// Subaddress index, or `None` for the main address
+    index: Option<u32>,
+    ephemeral_private: &'static str,
+    ephemeral_public: &'static str,
+    one_time_public: &'static str,
+    one_time_private: &'static str,
+}
+
+const VECTORS: &[Vector] = &[
+    Vector {
+        hash_algo: StealthHashAlgo::Blake2b,
+        index: None,
+        ephemeral_private: "0303030303030303030303030303030303030303030303030303030303030303",
+        ephemeral_public: "3417ece792a4d8416b6ee445ef9420d1b6e4db370d38ab04872fcbb0fed96f6f",
+        one_time_public: "96347e9229ad748c47066d54c44bd3453f2183074df1ac705e30dcf9cfb8046d",
+        one_time_private: "13720312f063a6f18a4187a65991641a9ebd0ded6be9bd6eebe69f9359035b01",
+    },
+    Vector {
+        hash_algo: StealthHashAlgo::Blake2b,
+        index: Some(0),
+        ephemeral_private: "0404040404040404040404040404040404040404040404040404040404040404",
+        ephemeral_public: "4639fb5e575762d9cf92482c47a7577076d638efba06494c6a5e9c1e4d3a3448",
+        one_time_public: "9cff1065bb187bc72c9e0bbf7ec9d511b9fc2d60983276709d6e823d46efb455",
+        one_time_private: "ed4370eb712b79b15a174e8906aecf4073cffe1a903077fab83fcfeef1c1b60e",
+    },
+    Vector {
+        hash_algo: StealthHashAlgo::Blake2b,
+        index: Some(1),
+        ephemeral_private: "0505050505050505050505050505050505050505050505050505050505050505",
+        ephemeral_public: "b2a2d50aebaecdc5a8ce3db90e847885467b23d01c2775eeec41ec2aee9f4479",
+        one_time_public: "38f87822543414b4545509c99d5528ccc0b23fc184f25b80aba5625108d61045",
+        one_time_private: "7daf63eab6d4808a595797c04666c383749db8973a7c138cf6ff5157df74960f",
+    },
+    Vector {
+        hash_algo: StealthHashAlgo::Blake2b,
+        index: Some(42),
+        ephemeral_private: "0606060606060606060606060606060606060606060606060606060606060606",
+        ephemeral_public: "3825c22c9f68048e5cf7c444f9dfd6f26dd3bd48c15d001d1d7a844f5a102644",
+        one_time_public: "06824fd9e66ba37bbb4aaad518fe10b8b5ce96953c7173597bdb8e66b1292a36",
+        one_time_private: "a5933f9c62be1095843702250bb09357abcb43c45a962b8fde9be885f0976c0f",
+    },
+    Vector {
+        hash_algo: StealthHashAlgo::Blake2b,
+        index: Some(CHANGE_SUBADDRESS_INDEX),
+        ephemeral_private: "0707070707070707070707070707070707070707070707070707070707070707",
+        ephemeral_public: "5a421740c7c2679b3e487e3ed2176fd036785ad94c99ce096400631f7129b33f",
+        one_time_public: "ccf5dcae2a6e153ae04c873688d1cf15d6858357e1d8605c0f16b48be57f7c45",
+        one_time_private: "3a025418570664f06588e4c00f6ce61b1bb1ad33a64eaf5d8c964ee0bb88ba0d",
+    },
+    Vector {
+        hash_algo: StealthHashAlgo::Sha512,
+        index: Some(1),
+        ephemeral_private: "0808080808080808080808080808080808080808080808080808080808080808",
+        ephemeral_public: "9854c0c8cfba1b088adbc5681299ba392271be57982b02034683194477629f1d",
+        one_time_public: "fa0f01ede112aa0cc104c0f3c7de9d2ac2d5889379551667750c672beeb2c82a",
+        one_time_private: "5c9a461aebd8c4de2039e3f61459a0525edf8fb1eec39d6143d23ef2d6bcd502",
+    },
+    Vector {
+        hash_algo: StealthHashAlgo::Keccak512,
+        index: None,
+        ephemeral_private: "0909090909090909090909090909090909090909090909090909090909090909",
+        ephemeral_public: "b8f7a9200f12593e41717e64156d8a81b78972345a3258c928c6ae4029d1307f",
+        one_time_public: "da8b8babd5702f2aabc79bed08c6efa07445ddcb6329edb0d04c8c02280e331a",
+        one_time_private: "8b4217b70edf35a6d6cbe6d36365c743147efe1c2bd84b93a85783c996e7dc07",
+    },
+];
+
+fn scalar(hex_str: &str) -> Scalar {
+    let bytes: [u8; 32] = hex::decode(hex_str).unwrap().try_into().unwrap();
+    Scalar::from_canonical_bytes(bytes).unwrap()
+}
+
+#[test]
+fn test_stealth_vectors() {
+    for (i, v) in VECTORS.iter().enumerate() {
+        let master = StealthMasterKey::from_keys(scalar(SPEND_PRIVATE), scalar(VIEW_PRIVATE))
+            .with_hash_algo(v.hash_algo);
+        let address = match v.index {
+            Some(index) => master.get_subaddress(index),
+            None => master.get_stealth_address(),
+        };
+        
+        let tx = address.one_time_address_from_ephemeral(scalar(v.ephemeral_private));
+        assert_eq!(hex::encode(compress_point(&tx.ephemeral_public)), v.ephemeral_public, "vector {} ephemeral key", i);
+        assert_eq!(hex::encode(compress_point(&tx.one_time_public)), v.one_time_public, "vector {} one-time key", i);
+        
+        let one_time_private = match v.index {
+            Some(index) => master.scan_subaddress(&tx.ephemeral_public, &tx.one_time_public, index),
+            None => master.scan_transaction(&tx.ephemeral_public, &tx.one_time_public),
+        };
+        assert_eq!(
+            one_time_private.map(|p| hex::encode(p.to_bytes())).as_deref(),
+            Some(v.one_time_private),
+            "vector {} one-time private key",
+            i
+        );
+    }
+}
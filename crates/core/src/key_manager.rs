@@ -1,14 +1,38 @@
 
 
 use crate::{CoreError, Result};
+use crate::crypto::stealth::{StealthMasterKey, ViewKey};
+use crate::secret_registry::WipeSecrets;
 use bip39::{Language, Mnemonic};
 use bitcoin::bip32::DerivationPath;
 use bitcoin::secp256k1::{Secp256k1, SecretKey, PublicKey};
 use bitcoin::{Network, PrivateKey};
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 use std::str::FromStr;
 use hdwallet::{ExtendedPrivKey};
 use rand::Rng;
+use zeroize::Zeroize;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::scalar::Scalar;
+
+/// BIP44 change-level branch for receive addresses
+pub const EXTERNAL_BRANCH: u32 = 0;
+
+/// BIP44 change-level branch for change addresses
+pub const INTERNAL_BRANCH: u32 = 1;
+
+/// BIP44 change-level branch reserved for output blinding factors
+/// (0 is external, 1 is internal change)
+const BLINDING_BRANCH: u32 = 2;
+
+/// BIP44 change-level branch for per-account stealth keys
+/// (index 0 is the spend key, 1 the view key)
+const STEALTH_BRANCH: u32 = 3;
+
+/// BIP44 change-level branch of the backup manifest signing key
+/// (account 0, index 0)
+const MANIFEST_BRANCH: u32 = 4;
 
 /// BIP44 coin types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -20,6 +44,40 @@ pub enum CoinType {
     Zcash = 133,
 }
 
+impl CoinType {
+    /// Coin for a `chain` name as stored with transactions
+    pub fn from_chain(chain: &str) -> Option<CoinType> {
+        match chain {
+            "bitcoin" => Some(CoinType::Bitcoin),
+            "ethereum" => Some(CoinType::Ethereum),
+            "solana" => Some(CoinType::Solana),
+            "polygon" => Some(CoinType::Polygon),
+            "zcash" => Some(CoinType::Zcash),
+            _ => None,
+        }
+    }
+    
+    /// Decimal places of the chain's native coin (satoshi, wei, lamport...)
+    pub fn decimals(self) -> u32 {
+        match self {
+            CoinType::Bitcoin | CoinType::Zcash => 8,
+            CoinType::Ethereum | CoinType::Polygon => 18,
+            CoinType::Solana => 9,
+        }
+    }
+    
+    /// Confirmations after which a transaction is treated as final
+    pub fn finality_confirmations(self) -> u32 {
+        match self {
+            CoinType::Bitcoin => 6,
+            CoinType::Ethereum => 12,
+            CoinType::Solana => 32,
+            CoinType::Polygon => 128,
+            CoinType::Zcash => 24,
+        }
+    }
+}
+
 /// Account derivation information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountDerivation {
@@ -66,12 +124,62 @@ pub struct Account {
     pub zcash_address: String,
 }
 
+impl Account {
+    /// Account fingerprint derived from its index and public addresses
+    pub fn fingerprint(&self) -> [u8; 32] {
+        use sha2::{Digest, Sha256};
+        
+        let mut hasher = Sha256::new();
+        hasher.update(b"Zetaris-Account-Fingerprint-v1");
+        hasher.update(self.index.to_be_bytes());
+        for address in [
+            &self.ethereum_address,
+            &self.solana_address,
+            &self.bitcoin_address,
+            &self.polygon_address,
+            &self.zcash_address,
+        ] {
+            hasher.update((address.len() as u32).to_be_bytes());
+            hasher.update(address.as_bytes());
+        }
+        hasher.finalize().into()
+    }
+}
+
 /// Key Manager - Main interface for HD wallet operations
+///
+/// The mnemonic, seed and master key are wiped on drop.
 pub struct KeyManager {
     secp: Secp256k1<bitcoin::secp256k1::All>,
     mnemonic: Mnemonic,
     seed: Vec<u8>,
     master_key: ExtendedPrivKey,
+    fingerprint: [u8; 32],
+    /// Set by `wipe_secrets`; every secret operation fails afterwards
+    wiped: bool,
+    /// Set by `set_locked`; secret operations fail until cleared
+    locked: bool,
+}
+
+impl Drop for KeyManager {
+    fn drop(&mut self) {
+        self.mnemonic.zeroize();
+        self.seed.zeroize();
+        self.wipe_master_key();
+        
+        #[cfg(debug_assertions)]
+        {
+            let entropy = self.mnemonic.to_entropy();
+            // SAFETY: the seed and chain code were just wiped across their full capacity
+            let (seed, chain_code) = unsafe {
+                (
+                    crate::zeroize_verify::allocation_bytes(self.seed.as_ptr(), self.seed.capacity()),
+                    crate::zeroize_verify::allocation_bytes(self.master_key.chain_code.as_ptr(), self.master_key.chain_code.capacity()),
+                )
+            };
+            crate::zeroize_verify::record::<Self>(&[&entropy, seed, chain_code]);
+        }
+    }
 }
 
 impl KeyManager {
@@ -85,15 +193,59 @@ impl KeyManager {
         
         let master_key = ExtendedPrivKey::with_seed(&seed)
             .map_err(|e| CoreError::KeyDerivation(e.to_string()))?;
+        let fingerprint = Self::master_fingerprint(&secp, &master_key)?;
         
         Ok(Self {
             secp,
             mnemonic,
             seed: seed.to_vec(),
             master_key,
+            fingerprint,
+            wiped: false,
+            locked: false,
         })
     }
     
+    /// Overwrite the master private key and chain code in place
+    ///
+    /// hdwallet's secp256k1 0.26 key has no `non_secure_erase`, so the key
+    /// is replaced by a volatile write of a fixed, public scalar.
+    fn wipe_master_key(&mut self) {
+        self.master_key.chain_code.zeroize();
+        let blank = hdwallet::secp256k1::SecretKey::from_slice(&[1u8; 32])
+            .expect("1 is a valid secret key");
+        // SAFETY: `private_key` is a valid, aligned field owned by `self`
+        unsafe { std::ptr::write_volatile(&mut self.master_key.private_key, blank) };
+        std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+    }
+    
+    /// Hash of the BIP32 master public key and chain code
+    fn master_fingerprint(
+        secp: &Secp256k1<bitcoin::secp256k1::All>,
+        master_key: &ExtendedPrivKey,
+    ) -> Result<[u8; 32]> {
+        use sha2::{Digest, Sha256};
+        
+        let secret = SecretKey::from_slice(&master_key.private_key.secret_bytes())
+            .map_err(|e| CoreError::KeyDerivation(e.to_string()))?;
+        let public = PublicKey::from_secret_key(secp, &secret);
+        
+        Ok(Sha256::new()
+            .chain_update(b"Zetaris-Wallet-Fingerprint-v1")
+            .chain_update(public.serialize())
+            .chain_update(&master_key.chain_code)
+            .finalize()
+            .into())
+    }
+    
+    /// Wallet fingerprint derived from the master public key
+    ///
+    /// Identical for every device restored from the same mnemonic, and
+    /// reveals nothing about the seed.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        self.fingerprint
+    }
+    
     /// Generate new random mnemonic (24 words)
     pub fn generate_mnemonic() -> Result<String> {
         // Generate 32 bytes of entropy for 24 words
@@ -147,15 +299,20 @@ impl KeyManager {
         change: u32,
         index: u32,
     ) -> Result<SecretKey> {
-        let derivation = AccountDerivation {
-            coin_type,
-            account_index: account,
-            change,
-            address_index: index,
-        };
+        let change_node = self.derive_change_node(coin_type, account, change)?;
+        Self::derive_child_key(&change_node, index)
+    }
+    
+    /// Derive the m/44'/coin_type'/account'/change node
+    fn derive_change_node(
+        &self,
+        coin_type: CoinType,
+        account: u32,
+        change: u32,
+    ) -> Result<ExtendedPrivKey> {
+        self.ensure_secrets()?;
         
         // Use hdwallet to derive keys
-        // m/44'/coin_type'/account'/change/index
         let mut key = self.master_key.clone();
         
         // Derive each level
@@ -165,9 +322,13 @@ impl KeyManager {
             .map_err(|e| CoreError::KeyDerivation(e.to_string()))?;
         key = key.derive_private_key(hdwallet::KeyIndex::hardened_from_normalize_index(account).unwrap())
             .map_err(|e| CoreError::KeyDerivation(e.to_string()))?;
-        key = key.derive_private_key(hdwallet::KeyIndex::Normal(change))
-            .map_err(|e| CoreError::KeyDerivation(e.to_string()))?;
-        key = key.derive_private_key(hdwallet::KeyIndex::Normal(index))
+        key.derive_private_key(hdwallet::KeyIndex::Normal(change))
+            .map_err(|e| CoreError::KeyDerivation(e.to_string()))
+    }
+    
+    /// Derive the non-hardened child key at `index` below a change node
+    fn derive_child_key(change_node: &ExtendedPrivKey, index: u32) -> Result<SecretKey> {
+        let key = change_node.derive_private_key(hdwallet::KeyIndex::Normal(index))
             .map_err(|e| CoreError::KeyDerivation(e.to_string()))?;
         
         // hdwallet's ExtendedPrivKey wraps a SecretKey
@@ -178,6 +339,80 @@ impl KeyManager {
             .map_err(|e| CoreError::KeyDerivation(e.to_string()))
     }
     
+    /// Derive addresses at m/44'/coin'/account'/change/index for each index in `range`
+    ///
+    /// Used to register an account's receive (change = 0) and change
+    /// (change = 1) addresses with light-client servers.
+    pub fn derive_addresses(
+        &self,
+        coin_type: CoinType,
+        account: u32,
+        change: u32,
+        range: Range<u32>,
+    ) -> Result<Vec<String>> {
+        // Derive the shared parent once rather than per index
+        let change_node = self.derive_change_node(coin_type, account, change)?;
+        
+        range
+            .map(|index| {
+                let key = Self::derive_child_key(&change_node, index)?;
+                Ok(self.address_for(coin_type, &key))
+            })
+            .collect()
+    }
+    
+    /// Format the address for a key on the given chain
+    fn address_for(&self, coin_type: CoinType, key: &SecretKey) -> String {
+        match coin_type {
+            CoinType::Ethereum | CoinType::Polygon => self.generate_ethereum_address(key),
+            CoinType::Solana => self.generate_solana_address(key),
+            CoinType::Bitcoin => self.generate_bitcoin_address(key),
+            CoinType::Zcash => self.generate_zcash_address(key),
+        }
+    }
+    
+    /// Derive a deterministic commitment blinding factor
+    ///
+    /// Uses m/44'/0'/account'/2/output_index so every output blinding can be
+    /// recovered from the mnemonic alone.
+    pub fn derive_blinding_factor(&self, account_index: u32, output_index: u32) -> Result<Scalar> {
+        self.derive_scalar(account_index, BLINDING_BRANCH, output_index, b"Zetaris-Output-Blinding-v1")
+    }
+    
+    /// Derive the stealth keys of an account
+    ///
+    /// Spend and view keys come from m/44'/0'/account'/3/0 and /1, so each
+    /// account has its own stealth address, recoverable from the mnemonic.
+    pub fn derive_account_stealth_key(&self, account_index: u32) -> Result<StealthMasterKey> {
+        let spend_private = self.derive_scalar(account_index, STEALTH_BRANCH, 0, b"Zetaris-Stealth-Spend-v1")?;
+        let view_private = self.derive_scalar(account_index, STEALTH_BRANCH, 1, b"Zetaris-Stealth-View-v1")?;
+        Ok(StealthMasterKey::from_keys(spend_private, view_private))
+    }
+    
+    /// Derive the view key of a single account
+    ///
+    /// Detects that account's stealth payments only; other accounts and
+    /// the rest of the wallet stay hidden from its holder.
+    pub fn derive_account_view_key(&self, account_index: u32) -> Result<ViewKey> {
+        Ok(self.derive_account_stealth_key(account_index)?.view_key())
+    }
+    
+    /// Hash the key at m/44'/0'/account'/branch/index to a scalar under `domain`
+    fn derive_scalar(&self, account_index: u32, branch: u32, index: u32, domain: &[u8]) -> Result<Scalar> {
+        use sha2::{Digest, Sha512};
+        
+        let key = self.derive_key(CoinType::Bitcoin, account_index, branch, index)?;
+        
+        let mut hasher = Sha512::new();
+        hasher.update(domain);
+        hasher.update(key.secret_bytes());
+        let hash = hasher.finalize();
+        
+        let mut wide = [0u8; 64];
+        wide.copy_from_slice(&hash);
+        Ok(Scalar::from_bytes_mod_order_wide(&wide))
+    }
+    
     /// Generate Ethereum-compatible address from private key
     fn generate_ethereum_address(&self, key: &SecretKey) -> String {
         use sha2::Digest;
@@ -230,25 +465,123 @@ impl KeyManager {
         account: &Account,
         coin_type: CoinType,
     ) -> Result<Vec<u8>> {
-        let key = match coin_type {
+        let key = self.account_key(account, coin_type)?;
+        
+        use sha2::Digest;
+        let hash = sha2::Sha256::digest(message);
+        let msg = bitcoin::secp256k1::Message::from_digest_slice(&hash)
+            .map_err(|e| CoreError::Crypto(e.to_string()))?;
+        
+        let signature = self.secp.sign_ecdsa(&msg, &key);
+        Ok(signature.serialize_compact().to_vec())
+    }
+    
+    /// Public key that `sign_message` signs with for `coin_type`
+    pub fn public_key(&self, account: &Account, coin_type: CoinType) -> Result<PublicKey> {
+        let key = self.account_key(account, coin_type)?;
+        Ok(PublicKey::from_secret_key(&self.secp, &key))
+    }
+    
+    /// Sign a backup manifest with the key at m/44'/0'/0'/4/0
+    ///
+    /// The key is dedicated to manifests, so a manifest signature can never
+    /// double as a transaction or message signature.
+    pub fn sign_manifest(&self, manifest: &[u8]) -> Result<Vec<u8>> {
+        let key = self.derive_key(CoinType::Bitcoin, 0, MANIFEST_BRANCH, 0)?;
+        
+        use sha2::Digest;
+        let hash = sha2::Sha256::digest(manifest);
+        let msg = bitcoin::secp256k1::Message::from_digest_slice(&hash)
+            .map_err(|e| CoreError::Crypto(e.to_string()))?;
+        
+        Ok(self.secp.sign_ecdsa(&msg, &key).serialize_compact().to_vec())
+    }
+    
+    /// Public key that `sign_manifest` signs with
+    pub fn manifest_public_key(&self) -> Result<PublicKey> {
+        let key = self.derive_key(CoinType::Bitcoin, 0, MANIFEST_BRANCH, 0)?;
+        Ok(PublicKey::from_secret_key(&self.secp, &key))
+    }
+    
+    /// Secret key an account uses for `coin_type`
+    fn account_key(&self, account: &Account, coin_type: CoinType) -> Result<SecretKey> {
+        self.ensure_secrets()?;
+        match coin_type {
             CoinType::Ethereum | CoinType::Polygon => {
-                account.ethereum_key.ok_or(CoreError::Crypto("No Ethereum key".into()))?
+                account.ethereum_key.ok_or(CoreError::Crypto("No Ethereum key".into()))
             }
             CoinType::Solana => {
-                account.solana_key.ok_or(CoreError::Crypto("No Solana key".into()))?
+                account.solana_key.ok_or(CoreError::Crypto("No Solana key".into()))
             }
             CoinType::Bitcoin | CoinType::Zcash => {
-                account.bitcoin_key.ok_or(CoreError::Crypto("No Bitcoin key".into()))?
+                account.bitcoin_key.ok_or(CoreError::Crypto("No Bitcoin key".into()))
             }
-        };
-        
+        }
+    }
+    
+    /// Verify a compact signature produced by `sign_message`
+    pub fn verify_message(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &PublicKey,
+    ) -> Result<bool> {
         use sha2::Digest;
         let hash = sha2::Sha256::digest(message);
         let msg = bitcoin::secp256k1::Message::from_digest_slice(&hash)
             .map_err(|e| CoreError::Crypto(e.to_string()))?;
+        let signature = bitcoin::secp256k1::ecdsa::Signature::from_compact(signature)
+            .map_err(|e| CoreError::Crypto(e.to_string()))?;
         
-        let signature = self.secp.sign_ecdsa(&msg, &key);
-        Ok(signature.serialize_compact().to_vec())
+        Ok(self.secp.verify_ecdsa(&msg, &signature, public_key).is_ok())
+    }
+    
+    /// Verify many `(message, signature, address)` items, one result each
+    ///
+    /// `address` is a base58 public key, as Solana addresses are. A 32-byte
+    /// key is an ed25519 signer checked with `verify_strict`, which also
+    /// rejects small-order keys and malleable signatures; a 33-byte key is a
+    /// secp256k1 signer checked as in `verify_message`. Items whose address
+    /// or signature cannot be decoded are invalid.
+    ///
+    /// The ed25519 items are checked together with `ed25519_dalek::verify_batch`;
+    /// only if the batch fails is each rechecked to find the bad ones.
+    pub fn verify_signatures_batch(&self, items: &[(&[u8], &[u8], &str)]) -> Vec<bool> {
+        let mut results = vec![false; items.len()];
+        let mut ed25519_items = Vec::new();
+        
+        for (i, (message, signature, address)) in items.iter().enumerate() {
+            let Ok(key) = bs58::decode(address).into_vec() else { continue };
+            match key.len() {
+                32 => {
+                    if let Some((signature, key)) = parse_ed25519_strict(signature, &key) {
+                        ed25519_items.push((i, *message, signature, key));
+                    }
+                }
+                33 => {
+                    results[i] = PublicKey::from_slice(&key)
+                        .ok()
+                        .and_then(|public_key| self.verify_message(message, signature, &public_key).ok())
+                        .unwrap_or(false);
+                }
+                _ => {}
+            }
+        }
+        
+        let messages: Vec<&[u8]> = ed25519_items.iter().map(|(_, message, _, _)| *message).collect();
+        let signatures: Vec<_> = ed25519_items.iter().map(|(_, _, signature, _)| *signature).collect();
+        let keys: Vec<_> = ed25519_items.iter().map(|(_, _, _, key)| *key).collect();
+        if ed25519_dalek::verify_batch(&messages, &signatures, &keys).is_ok() {
+            for (i, _, _, _) in &ed25519_items {
+                results[*i] = true;
+            }
+        } else {
+            for (i, message, signature, key) in &ed25519_items {
+                results[*i] = key.verify_strict(message, signature).is_ok();
+            }
+        }
+        
+        results
     }
     
     /// Export private key for specific chain (USE WITH CAUTION)
@@ -257,20 +590,57 @@ impl KeyManager {
         account: &Account,
         coin_type: CoinType,
     ) -> Result<String> {
-        let key = match coin_type {
-            CoinType::Ethereum | CoinType::Polygon => {
-                account.ethereum_key.ok_or(CoreError::Crypto("No Ethereum key".into()))?
-            }
-            CoinType::Solana => {
-                account.solana_key.ok_or(CoreError::Crypto("No Solana key".into()))?
-            }
-            CoinType::Bitcoin | CoinType::Zcash => {
-                account.bitcoin_key.ok_or(CoreError::Crypto("No Bitcoin key".into()))?
-            }
-        };
-        
+        let key = self.account_key(account, coin_type)?;
         Ok(hex::encode(key.secret_bytes()))
     }
+    
+    /// Refuse (or allow again) every operation that uses a private key
+    pub(crate) fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+    }
+    
+    /// Fail once the secrets have been wiped, or while locked
+    fn ensure_secrets(&self) -> Result<()> {
+        if self.wiped {
+            return Err(CoreError::SecretUnavailable);
+        }
+        if self.locked {
+            return Err(CoreError::KeysLocked);
+        }
+        Ok(())
+    }
+}
+
+impl WipeSecrets for KeyManager {
+    fn wipe_secrets(&mut self) {
+        self.mnemonic.zeroize();
+        self.seed.zeroize();
+        self.wipe_master_key();
+        self.wiped = true;
+    }
+}
+
+impl WipeSecrets for Account {
+    fn wipe_secrets(&mut self) {
+        for key in [&mut self.ethereum_key, &mut self.solana_key, &mut self.bitcoin_key] {
+            if let Some(secret) = key.as_mut() {
+                secret.non_secure_erase();
+            }
+            *key = None;
+        }
+    }
+}
+
+/// Decode an ed25519 signature and key, refusing what `verify_strict` would:
+/// small-order keys or nonces and non-canonical nonce encodings
+fn parse_ed25519_strict(signature: &[u8], key: &[u8]) -> Option<(ed25519_dalek::Signature, ed25519_dalek::VerifyingKey)> {
+    let signature = ed25519_dalek::Signature::from_slice(signature).ok()?;
+    let key = ed25519_dalek::VerifyingKey::from_bytes(&<[u8; 32]>::try_from(key).ok()?).ok()?;
+    let nonce = CompressedEdwardsY(*signature.r_bytes()).decompress()?;
+    if key.is_weak() || nonce.is_small_order() || nonce.compress().as_bytes() != signature.r_bytes() {
+        return None;
+    }
+    Some((signature, key))
 }
 
 #[cfg(test)]
@@ -326,4 +696,77 @@ mod tests {
         
         assert_eq!(signature.len(), 64); // Compact ECDSA signature
     }
+    
+    #[test]
+    fn test_derive_address_range() {
+        let km = KeyManager::new_from_mnemonic(TEST_MNEMONIC).unwrap();
+        let account = km.derive_account(0).unwrap();
+        
+        let addresses = km.derive_addresses(CoinType::Bitcoin, 0, 0, 0..20).unwrap();
+        assert_eq!(addresses.len(), 20);
+        assert_eq!(addresses[0], account.bitcoin_address);
+        
+        let unique: std::collections::HashSet<_> = addresses.iter().collect();
+        assert_eq!(unique.len(), 20);
+    }
+    
+    #[test]
+    fn test_verify_signatures_batch() {
+        use ed25519_dalek::{Signer, SigningKey};
+        
+        let km = KeyManager::new_from_mnemonic(TEST_MNEMONIC).unwrap();
+        let signers: Vec<SigningKey> = (0..50u8).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+        let messages: Vec<Vec<u8>> = (0..50).map(|i| format!("sign in #{}", i).into_bytes()).collect();
+        let addresses: Vec<String> = signers.iter()
+            .map(|key| bs58::encode(key.verifying_key().as_bytes()).into_string())
+            .collect();
+        let mut signatures: Vec<Vec<u8>> = signers.iter().zip(&messages)
+            .map(|(key, message)| key.sign(message).to_bytes().to_vec())
+            .collect();
+        signatures[17] = signers[17].sign(b"something else").to_bytes().to_vec();
+        
+        // A secp256k1 signature from this wallet rides along
+        let account = km.derive_account(0).unwrap();
+        let secp_signature = km.sign_message(b"sign in", &account, CoinType::Solana).unwrap();
+        
+        let mut items: Vec<(&[u8], &[u8], &str)> = (0..50)
+            .map(|i| (messages[i].as_slice(), signatures[i].as_slice(), addresses[i].as_str()))
+            .collect();
+        items.push((b"sign in", &secp_signature, &account.solana_address));
+        
+        let results = km.verify_signatures_batch(&items);
+        assert_eq!(results.len(), 51);
+        for (i, valid) in results.iter().enumerate() {
+            assert_eq!(*valid, i != 17, "item {}", i);
+        }
+        
+        // Without the bad item the whole batch passes at once
+        items.remove(17);
+        assert!(km.verify_signatures_batch(&items).into_iter().all(|valid| valid));
+    }
+    
+    #[test]
+    fn test_wipe_secrets_overwrites_master_key() {
+        let mut km = KeyManager::new_from_mnemonic(TEST_MNEMONIC).unwrap();
+        km.wipe_secrets();
+        
+        assert_eq!(km.master_key.private_key.secret_bytes(), [1u8; 32]);
+        assert!(km.master_key.chain_code.iter().all(|b| *b == 0));
+    }
+    
+    #[test]
+    fn test_verify_signatures_batch_rejects_small_order_key() {
+        let km = KeyManager::new_from_mnemonic(TEST_MNEMONIC).unwrap();
+        
+        // Identity key with R = identity, s = 0 passes the cofactorless
+        // equation for any message; strict verification must refuse it
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&identity);
+        let address = bs58::encode(identity).into_string();
+        
+        let items: Vec<(&[u8], &[u8], &str)> = vec![(b"any message", &signature, &address)];
+        assert_eq!(km.verify_signatures_batch(&items), vec![false]);
+    }
 }
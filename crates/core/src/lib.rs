@@ -1,9 +1,16 @@
+#![allow(non_snake_case)]
+
+pub mod clock;
 pub mod commitments;
 pub mod crypto;
 pub mod key_manager;
+pub mod partial_transaction;
+pub(crate) mod secret_registry;
 pub mod storage;
 pub mod transaction_builder;
 pub mod wallet_state;
+#[cfg(debug_assertions)]
+pub mod zeroize_verify;
 
 use thiserror::Error;
 
@@ -33,16 +40,40 @@ pub enum CoreError {
     
     #[error("Invalid parameter: {0}")]
     InvalidParameter(String),
+    
+    #[error("Malformed transaction: {0}")]
+    InvalidTransaction(String),
+    
+    #[error("Range proof error: {0}")]
+    RangeProof(String),
+    
+    #[error("Invalid stealth address: {0}")]
+    InvalidAddress(crypto::stealth::AddressError),
+    
+    #[error("Wallet locked after failed password attempts; retry in {0}s")]
+    WalletLocked(u64),
+    
+    #[error("Secret key material has been wiped")]
+    SecretUnavailable,
+    
+    #[error("Wallet is locked; unlock it to use its keys")]
+    KeysLocked,
 }
 
 // Re-export main types
-pub use commitments::{PedersenCommitment, Commitment, RangeProof, BalanceCommitment, random_scalar};
+pub use clock::{Clock, SystemClock, MockClock};
+pub use commitments::{PedersenCommitment, Commitment, RangeProof, RangeProofKind, RangeProofScheme, Bulletproofs, BalanceCommitment, ReservesProof, aggregate_supply, verify_supply, recover_value, random_scalar, MAX_RECOVERABLE_VALUE};
 pub use key_manager::{KeyManager, Account, CoinType, AccountDerivation};
-pub use crypto::{AesGcmCipher, ChaCha20Cipher, sha256, blake2b};
-pub use crypto::stealth::{StealthMasterKey, StealthAddress, StealthTransaction, StealthScanner};
-pub use storage::{EncryptedDb, StoredAccount, StoredTransaction, StealthOutput};
-pub use transaction_builder::{TransactionBuilder, PrivateTransaction, UTXO};
-pub use wallet_state::{WalletState, TransactionRecord, ExportedKeys, WalletStatistics};
+pub use crypto::{AesGcmCipher, ChaCha20Cipher, XChaCha20Poly1305Cipher, sha256, blake2b};
+#[cfg(feature = "bulletproofs-plus")]
+pub use commitments::BulletproofsPlus;
+pub use crypto::filter::OutputFilter;
+pub use crypto::merkle::{MerkleTree, MerkleProof};
+pub use crypto::stealth::{StealthMasterKey, StealthAddress, StealthTransaction, AmountScheme, StealthScanner, StealthHashAlgo, ViewKey, AddressError, CHANGE_SUBADDRESS_INDEX};
+pub use storage::{EncryptedDb, DecoyConfig, MetadataKey, OrphanReport, StorageConfig, StoredAccount, StoredTransaction, StoredCommitmentProof, StealthOutput, WalletSummary, WalletSyncState, SyncGap};
+pub use partial_transaction::{PartialTransaction, InputSigningInfo};
+pub use transaction_builder::{TransactionBuilder, PrivateTransaction, InputProof, AnonymityMetrics, UTXO, VerifyOptions};
+pub use wallet_state::{WalletState, TransactionRecord, ExportedKeys, WalletStatistics, NamingPolicy, SignedManifest, SpendAuthorization};
 
 // Version info
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
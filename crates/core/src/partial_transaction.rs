@@ -0,0 +1,275 @@
+//! Partially Signed Transactions
+//!
+//! A container for air-gapped signing: an online device builds the
+//! transaction and records which account key must sign each input, a cold
+//! device holding the mnemonic adds signatures, and the online device
+//! combines the results and broadcasts the finished transaction.
+
+use crate::{
+    CoreError, Result,
+    key_manager::{CoinType, KeyManager},
+    transaction_builder::PrivateTransaction,
+};
+use bincode::Options;
+use bitcoin::secp256k1::PublicKey;
+use serde::{Serialize, Deserialize};
+
+/// Magic prefix identifying a serialized `PartialTransaction`
+const PSBT_MAGIC: &[u8; 5] = b"zpst\xff";
+
+/// Largest partial transaction body `from_psbt_bytes` will decode
+///
+/// Bounds what a malformed length prefix can make the decoder allocate.
+pub const MAX_PSBT_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Which key must sign an input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputSigningInfo {
+    /// BIP44 account index of the owning key
+    pub account_index: u32,
+    /// Chain whose account key signs
+    pub coin_type: CoinType,
+    /// Compressed public key the signature must verify against
+    pub public_key: Vec<u8>,
+}
+
+/// Transaction awaiting input signatures
+///
+/// Signatures are collected in each input's `signature` field, which
+/// `PrivateTransaction::signing_hash` excludes.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PartialTransaction {
+    pub tx: PrivateTransaction,
+    pub signing_info: Vec<InputSigningInfo>,
+}
+
+impl PartialTransaction {
+    /// Wrap an unsigned transaction with one signing entry per input
+    pub fn new(tx: PrivateTransaction, signing_info: Vec<InputSigningInfo>) -> Result<Self> {
+        if signing_info.len() != tx.inputs.len() {
+            return Err(CoreError::InvalidParameter(format!(
+                "Expected signing info for {} inputs, got {}",
+                tx.inputs.len(), signing_info.len()
+            )));
+        }
+        
+        Ok(PartialTransaction { tx, signing_info })
+    }
+    
+    /// Sign every unsigned input whose key `key_manager` holds
+    ///
+    /// Inputs owned by other keys are left alone, so several cold devices
+    /// can each sign their share. Returns the number of inputs signed.
+    /// Callers outside the crate go through
+    /// `WalletState::sign_partial_transaction`, which needs a spend
+    /// authorization.
+    pub(crate) fn sign(&mut self, key_manager: &KeyManager) -> Result<usize> {
+        let hash = self.tx.signing_hash()?;
+        let mut signed = 0;
+        
+        for (input, info) in self.tx.inputs.iter_mut().zip(&self.signing_info) {
+            if !input.signature.is_empty() {
+                continue;
+            }
+            
+            let account = key_manager.derive_account(info.account_index)?;
+            let public_key = key_manager.public_key(&account, info.coin_type)?;
+            if public_key.serialize().as_slice() != info.public_key.as_slice() {
+                continue;
+            }
+            
+            input.signature = key_manager.sign_message(&hash, &account, info.coin_type)?;
+            signed += 1;
+        }
+        
+        Ok(signed)
+    }
+    
+    /// Merge signatures from another copy of the same transaction
+    pub fn combine(&mut self, other: &PartialTransaction) -> Result<()> {
+        if self.tx.signing_hash()? != other.tx.signing_hash()? || self.signing_info != other.signing_info {
+            return Err(CoreError::InvalidParameter("Cannot combine different transactions".into()));
+        }
+        
+        for (input, theirs) in self.tx.inputs.iter_mut().zip(&other.tx.inputs) {
+            if input.signature.is_empty() {
+                input.signature = theirs.signature.clone();
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Whether every input carries a signature
+    pub fn is_fully_signed(&self) -> bool {
+        self.tx.inputs.iter().all(|i| !i.signature.is_empty())
+    }
+    
+    /// Check every present signature against its input's public key
+    ///
+    /// Signing info that no longer matches the inputs one-to-one is
+    /// `CoreError::InvalidTransaction`.
+    pub fn verify_signatures(&self) -> Result<bool> {
+        if self.signing_info.len() != self.tx.inputs.len() {
+            return Err(CoreError::InvalidTransaction(format!(
+                "Expected signing info for {} inputs, got {}",
+                self.tx.inputs.len(), self.signing_info.len()
+            )));
+        }
+        
+        for (index, (input, info)) in self.tx.inputs.iter().zip(&self.signing_info).enumerate() {
+            if input.signature.is_empty() {
+                continue;
+            }
+            
+            let public_key = PublicKey::from_slice(&info.public_key)
+                .map_err(|e| CoreError::Crypto(format!("Invalid signer public key: {}", e)))?;
+            if !self.tx.verify_input_signature(index, &public_key)? {
+                return Ok(false);
+            }
+        }
+        
+        Ok(true)
+    }
+    
+    /// Extract the finished transaction once all signatures verify
+    pub fn finalize(self) -> Result<PrivateTransaction> {
+        if !self.is_fully_signed() {
+            return Err(CoreError::InvalidTransaction("Transaction has unsigned inputs".into()));
+        }
+        if !self.verify_signatures()? {
+            return Err(CoreError::InvalidTransaction("Invalid input signature".into()));
+        }
+        
+        Ok(self.tx)
+    }
+    
+    /// Serialize for transfer to or from a signing device
+    pub fn to_psbt_bytes(&self) -> Result<Vec<u8>> {
+        let body = bincode::serialize(self)
+            .map_err(|e| CoreError::Serialization(format!("Failed to serialize partial transaction: {}", e)))?;
+        
+        let mut bytes = PSBT_MAGIC.to_vec();
+        bytes.extend_from_slice(&body);
+        Ok(bytes)
+    }
+    
+    /// Deserialize bytes produced by `to_psbt_bytes`
+    ///
+    /// Bodies over `MAX_PSBT_BYTES`, or whose signing info does not match
+    /// the inputs one-to-one, are rejected.
+    pub fn from_psbt_bytes(bytes: &[u8]) -> Result<Self> {
+        let body = bytes.strip_prefix(PSBT_MAGIC.as_slice())
+            .ok_or_else(|| CoreError::Serialization("Not a partial transaction".into()))?;
+        
+        // Same layout as `bincode::serialize`, with allocations bounded
+        let partial: PartialTransaction = bincode::options()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(MAX_PSBT_BYTES)
+            .deserialize(body)
+            .map_err(|e| CoreError::Serialization(format!("Failed to deserialize partial transaction: {}", e)))?;
+        if partial.signing_info.len() != partial.tx.inputs.len() {
+            return Err(CoreError::Serialization("Signing info does not match inputs".into()));
+        }
+        
+        Ok(partial)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commitments::random_scalar;
+    use crate::transaction_builder::TransactionBuilder;
+    
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+    
+    fn unsigned_partial(online: &KeyManager) -> PartialTransaction {
+        let input_blinding = random_scalar();
+        let mut builder = TransactionBuilder::new();
+        builder.add_input([1u8; 32], 0, 1000, input_blinding);
+        builder.set_fee(10);
+        let change_blinding = builder.calculate_change_blinding();
+        builder.add_output_with_blinding(b"recipient".to_vec(), 990, change_blinding).unwrap();
+        let tx = builder.build().unwrap();
+        
+        let account = online.derive_account(0).unwrap();
+        let public_key = online.public_key(&account, CoinType::Bitcoin).unwrap();
+        PartialTransaction::new(tx, vec![InputSigningInfo {
+            account_index: 0,
+            coin_type: CoinType::Bitcoin,
+            public_key: public_key.serialize().to_vec(),
+        }]).unwrap()
+    }
+    
+    #[test]
+    fn test_cold_signing_round_trip() {
+        let online = KeyManager::new_from_mnemonic(TEST_MNEMONIC).unwrap();
+        let mut partial = unsigned_partial(&online);
+        let blob = partial.to_psbt_bytes().unwrap();
+        
+        // Cold device signs its copy and returns it
+        let cold = KeyManager::new_from_mnemonic(TEST_MNEMONIC).unwrap();
+        let mut cold_copy = PartialTransaction::from_psbt_bytes(&blob).unwrap();
+        assert_eq!(cold_copy.sign(&cold).unwrap(), 1);
+        let returned = cold_copy.to_psbt_bytes().unwrap();
+        
+        partial.combine(&PartialTransaction::from_psbt_bytes(&returned).unwrap()).unwrap();
+        assert!(partial.is_fully_signed());
+        
+        let tx = partial.finalize().unwrap();
+        assert!(tx.verify().unwrap());
+    }
+    
+    #[test]
+    fn test_foreign_key_does_not_sign() {
+        let online = KeyManager::new_from_mnemonic(TEST_MNEMONIC).unwrap();
+        let mut partial = unsigned_partial(&online);
+        
+        let other = KeyManager::new_from_mnemonic(&KeyManager::generate_mnemonic().unwrap()).unwrap();
+        assert_eq!(partial.sign(&other).unwrap(), 0);
+        assert!(partial.clone().finalize().is_err());
+        
+        // A signature over a different transaction is rejected
+        partial.sign(&online).unwrap();
+        partial.tx.fee += 1;
+        assert!(!partial.verify_signatures().unwrap());
+        assert!(PartialTransaction::from_psbt_bytes(b"garbage").is_err());
+    }
+    
+    #[test]
+    fn test_psbt_decoding_is_bounded() {
+        let online = KeyManager::new_from_mnemonic(TEST_MNEMONIC).unwrap();
+        let partial = unsigned_partial(&online);
+        
+        // An input count claiming u64::MAX entries must not allocate
+        let mut huge = PSBT_MAGIC.to_vec();
+        huge.extend_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(PartialTransaction::from_psbt_bytes(&huge), Err(CoreError::Serialization(_))));
+        
+        // Signing info must cover exactly the inputs
+        let mut extra = partial.clone();
+        extra.signing_info.push(partial.signing_info[0].clone());
+        let bytes = extra.to_psbt_bytes().unwrap();
+        assert!(matches!(PartialTransaction::from_psbt_bytes(&bytes), Err(CoreError::Serialization(_))));
+        
+        let bytes = partial.to_psbt_bytes().unwrap();
+        assert!(PartialTransaction::from_psbt_bytes(&bytes).is_ok());
+    }
+    
+    #[test]
+    fn test_mismatched_signing_info_is_an_error() {
+        let online = KeyManager::new_from_mnemonic(TEST_MNEMONIC).unwrap();
+        let mut partial = unsigned_partial(&online);
+        partial.sign(&online).unwrap();
+        
+        let info = partial.signing_info[0].clone();
+        partial.signing_info.push(info);
+        assert!(matches!(partial.verify_signatures(), Err(CoreError::InvalidTransaction(_))));
+        
+        partial.signing_info.clear();
+        assert!(matches!(partial.verify_signatures(), Err(CoreError::InvalidTransaction(_))));
+        assert!(partial.finalize().is_err());
+    }
+}
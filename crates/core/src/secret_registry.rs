@@ -0,0 +1,31 @@
+//! Secret Registry
+//!
+//! Enumerates every live secret-bearing value a wallet holds, so wiping them
+//! on destroy cannot miss one and does not depend on drop order.
+
+/// A value holding secrets that can be wiped in place
+pub(crate) trait WipeSecrets {
+    /// Zeroize the secrets; later use fails with `CoreError::SecretUnavailable`
+    fn wipe_secrets(&mut self);
+}
+
+/// Borrowed view of a set of secret holders
+#[derive(Default)]
+pub(crate) struct SecretRegistry<'a> {
+    holders: Vec<&'a mut dyn WipeSecrets>,
+}
+
+impl<'a> SecretRegistry<'a> {
+    /// Track `holder` for the next `wipe_all`
+    pub(crate) fn register(&mut self, holder: &'a mut dyn WipeSecrets) -> &mut Self {
+        self.holders.push(holder);
+        self
+    }
+    
+    /// Wipe every registered holder
+    pub(crate) fn wipe_all(self) {
+        for holder in self.holders {
+            holder.wipe_secrets();
+        }
+    }
+}
@@ -1,9 +1,399 @@
 use rusqlite::{Connection, params};
 use serde::{Serialize, Deserialize};
-use zeroize::ZeroizeOnDrop;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::clock::{Clock, SystemClock};
+use crate::key_manager::CoinType;
+use crate::secret_registry::WipeSecrets;
+use crate::storage::field_crypto::{FieldKey, LEGACY_PLAINTEXT_MAX};
+use crate::storage::hidden_volume;
+use crate::storage::sync_state::{SyncGap, SyncSession, WalletSyncState};
 use crate::{CoreError, Result};
 
+/// Well-known wallet metadata keys
+///
+/// Crate internals use these through `set_meta`/`get_meta` so that a typo
+/// is a compile error rather than a silently misplaced row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataKey {
+    WalletVersion,
+    CreatedAt,
+    MnemonicHash,
+    SpendAuthKey,
+    PasswordSalt,
+    PasswordHash,
+    FailedAttempts,
+    LockedUntil,
+    FieldKeySalt,
+    FieldKey,
+    PinSalt,
+    PinWrappedKey,
+    PinFailedAttempts,
+    Encryption,
+    MinReaderVersion,
+    CurrentAccount,
+    MnemonicSalt,
+    MnemonicHashScheme,
+    FieldsEncrypted,
+}
+
+impl MetadataKey {
+    /// Key as stored in `wallet_meta`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetadataKey::WalletVersion => "wallet_version",
+            MetadataKey::CreatedAt => "created_at",
+            MetadataKey::MnemonicHash => "mnemonic_hash",
+            MetadataKey::SpendAuthKey => "spend_auth_key",
+            MetadataKey::PasswordSalt => "password_salt",
+            MetadataKey::PasswordHash => "password_hash",
+            MetadataKey::FailedAttempts => "failed_attempts",
+            MetadataKey::LockedUntil => "locked_until",
+            MetadataKey::FieldKeySalt => "field_key_salt",
+            MetadataKey::FieldKey => "field_key",
+            MetadataKey::PinSalt => "pin_salt",
+            MetadataKey::PinWrappedKey => "pin_wrapped_key",
+            MetadataKey::PinFailedAttempts => "pin_failed_attempts",
+            MetadataKey::Encryption => "encryption",
+            MetadataKey::MinReaderVersion => "min_reader_version",
+            MetadataKey::CurrentAccount => "current_account",
+            MetadataKey::MnemonicSalt => "mnemonic_salt",
+            MetadataKey::MnemonicHashScheme => "mnemonic_hash_scheme",
+            MetadataKey::FieldsEncrypted => "fields_encrypted",
+        }
+    }
+}
+
+/// Kind of mutation recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditOperation {
+    AccountAdded,
+    TransactionRecorded,
+    OutputSpent,
+    PasswordChanged,
+}
+
+impl AuditOperation {
+    /// Operation as stored in `audit_log`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOperation::AccountAdded => "account_added",
+            AuditOperation::TransactionRecorded => "transaction_recorded",
+            AuditOperation::OutputSpent => "output_spent",
+            AuditOperation::PasswordChanged => "password_changed",
+        }
+    }
+}
+
+/// SQLite tuning options
+///
+/// `None` keeps SQLite's default. `page_size` only takes effect when the
+/// database file is first created; it cannot change once the database
+/// exists (and is in WAL mode), so reopening with a different value keeps
+/// the original size. Under `sqlcipher` it is applied as `cipher_page_size`,
+/// which the encrypted file does not record: pass the same value on every
+/// open, or the database fails to open.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorageConfig {
+    /// Page size in bytes: a power of two from 512 to 65536
+    pub page_size: Option<u32>,
+    /// `PRAGMA cache_size`: pages if positive, KiB if negative
+    pub cache_size: Option<i64>,
+    /// Attempts to open a transiently unavailable database (default 5)
+    pub open_attempts: Option<u32>,
+    /// Delay before the first retry, doubling after each (default 50ms)
+    pub open_retry_delay: Option<Duration>,
+    /// How far past the clock's now a stored timestamp may be (default 1 day)
+    pub max_clock_skew: Option<Duration>,
+    /// Read back field-encrypted values after writing and reject any stored
+    /// as plaintext (default: on in debug builds)
+    pub verify_field_encryption: Option<bool>,
+}
+
+/// Default `StorageConfig::open_attempts`
+const DEFAULT_OPEN_ATTEMPTS: u32 = 5;
+
+/// Default `StorageConfig::open_retry_delay`
+const DEFAULT_OPEN_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Default `StorageConfig::max_clock_skew`
+const DEFAULT_MAX_CLOCK_SKEW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Version of this crate, compared against a database's `min_reader_version`
+const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Oldest crate version that can read databases this version writes
+///
+/// Raise it alongside any schema change older readers would corrupt.
+const MIN_READER_VERSION: &str = "1.0.0";
+
+impl StorageConfig {
+    /// Check that the options are values SQLite accepts
+    fn validate(&self) -> Result<()> {
+        if let Some(page_size) = self.page_size {
+            if !(512..=65536).contains(&page_size) || !page_size.is_power_of_two() {
+                return Err(CoreError::InvalidParameter(
+                    format!("Page size must be a power of two from 512 to 65536, got {}", page_size)
+                ));
+            }
+        }
+        
+        if self.open_attempts == Some(0) {
+            return Err(CoreError::InvalidParameter("Open attempts must be at least 1".into()));
+        }
+        
+        Ok(())
+    }
+}
+
+/// With the `tracing` feature, log once per process that secrets are going
+/// into an unencrypted file
+fn warn_if_plaintext() {
+    #[cfg(all(feature = "tracing", not(feature = "sqlcipher")))]
+    {
+        static WARNED: std::sync::Once = std::sync::Once::new();
+        WARNED.call_once(|| {
+            tracing::warn!(
+                "Wallet database is plain SQLite (built without the `sqlcipher` feature); \
+                 only field-encrypted columns are protected at rest"
+            );
+        });
+    }
+}
+
+/// Reject transaction amounts that are not plain decimals in whole coins
+///
+/// Digits with at most one decimal point and no sign, exponent or
+/// separators; known chains also cap the fractional digits at the coin's
+/// precision (8 for bitcoin, 18 for ethereum...).
+pub(crate) fn check_amount(chain: &str, amount: &str) -> Result<()> {
+    let invalid = |reason: &str| Err(CoreError::InvalidParameter(
+        format!("Invalid {} amount {:?}: {}", chain, amount, reason)
+    ));
+    
+    let (whole, fraction) = match amount.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (amount, None),
+    };
+    let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    
+    if !is_digits(whole) || !fraction.is_none_or(is_digits) {
+        return invalid("expected a decimal number");
+    }
+    
+    if let (Some(fraction), Some(coin)) = (fraction, CoinType::from_chain(chain)) {
+        if fraction.len() > coin.decimals() as usize {
+            return invalid(&format!("more than {} decimal places", coin.decimals()));
+        }
+    }
+    
+    Ok(())
+}
+
+/// Fail if `column` of row `id` was stored as the plaintext it should encrypt
+fn check_field_encrypted(conn: &Connection, table: &str, column: &str, id: i64, plaintext: &[u8]) -> Result<()> {
+    let stored: Vec<u8> = conn.query_row(
+        &format!("SELECT {column} FROM {table} WHERE id = ?1"),
+        params![id],
+        |row| row.get(0),
+    ).map_err(|e| CoreError::Storage(format!("Failed to read back {}: {}", column, e)))?;
+    
+    if stored.as_slice() == plaintext {
+        return Err(CoreError::Crypto(format!("{}.{} was stored unencrypted", table, column)));
+    }
+    Ok(())
+}
+
+/// Parse a dotted version such as "1.2.0" for ordered comparison
+///
+/// Pre-release and build suffixes ("1.2.0-rc.1", "1.2.0+abc") are ignored,
+/// so a pre-release compares equal to its release.
+fn parse_version(version: &str) -> Result<Vec<u64>> {
+    let core = version.split(['-', '+']).next().unwrap_or(version);
+    core.split('.')
+        .map(|part| part.parse::<u64>())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| CoreError::Storage(format!("Invalid version '{}': {}", version, e)))
+}
+
+/// Encode a range proof for the `commitment_proofs` table
+///
+/// Returns the stored bytes and whether they are compressed. With the
+/// `compress-proofs` feature proofs are zstd-compressed unless that would
+/// not make them smaller.
+fn encode_range_proof(proof: &[u8]) -> Result<(Vec<u8>, bool)> {
+    #[cfg(feature = "compress-proofs")]
+    {
+        let compressed = zstd::encode_all(proof, 0)
+            .map_err(|e| CoreError::Storage(format!("Failed to compress range proof: {}", e)))?;
+        if compressed.len() < proof.len() {
+            return Ok((compressed, true));
+        }
+    }
+    
+    Ok((proof.to_vec(), false))
+}
+
+/// Decode a range proof stored by `encode_range_proof`
+fn decode_range_proof(stored: Vec<u8>, compressed: bool) -> Result<Vec<u8>> {
+    if !compressed {
+        return Ok(stored);
+    }
+    
+    #[cfg(feature = "compress-proofs")]
+    {
+        zstd::decode_all(stored.as_slice())
+            .map_err(|e| CoreError::Storage(format!("Failed to decompress range proof: {}", e)))
+    }
+    #[cfg(not(feature = "compress-proofs"))]
+    {
+        Err(CoreError::Storage("Range proof is compressed; enable the `compress-proofs` feature to read it".into()))
+    }
+}
+
+/// Column names of `table`, empty if it does not exist
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))
+        .map_err(|e| CoreError::Storage(format!("Failed to inspect table: {}", e)))?;
+    
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| CoreError::Storage(format!("Failed to inspect table: {}", e)))?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| CoreError::Storage(format!("Failed to inspect table: {}", e)));
+    columns
+}
+
+/// Run `op`, retrying with exponential backoff while it fails transiently
+///
+/// Busy, locked and can't-open errors are transient (e.g. the OS holding
+/// the file during a backup). Anything else, such as a wrong SQLCipher key
+/// ("file is not a database"), is returned at once.
+fn retry_transient<T>(
+    attempts: u32,
+    base_delay: Duration,
+    mut op: impl FnMut() -> rusqlite::Result<T>,
+) -> rusqlite::Result<T> {
+    let mut delay = base_delay;
+    let mut attempt = 1;
+    
+    loop {
+        match op() {
+            Err(e) if attempt < attempts && is_transient(&e) => {
+                std::thread::sleep(delay);
+                delay = delay.saturating_mul(2);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether an error may clear up if the operation is retried
+fn is_transient(e: &rusqlite::Error) -> bool {
+    use rusqlite::ErrorCode;
+    matches!(
+        e.sqlite_error_code(),
+        Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked | ErrorCode::CannotOpen)
+    )
+}
+
+/// Most rows `get_transactions` returns in one call
+pub const MAX_TRANSACTION_LIMIT: u32 = 10_000;
+
+/// Field-encrypted columns, by table
+const FIELD_ENCRYPTED_COLUMNS: [(&str, &str); 5] = [
+    ("accounts", "note"),
+    ("stealth_outputs", "one_time_private"),
+    ("commitment_proofs", "blinding"),
+    ("stealth_addresses", "spend_private"),
+    ("stealth_addresses", "view_private"),
+];
+
+/// Tables whose rows belong to an account through `account_id`
+const ACCOUNT_OWNED_TABLES: [&str; 8] = [
+    "transactions",
+    "stealth_addresses",
+    "stealth_outputs",
+    "commitment_proofs",
+    "sync_accounts",
+    "address_counters",
+    "address_pool",
+    "account_addresses",
+];
+
+/// Tables copied by `import_plaintext`, parents before children
+const IMPORT_TABLES: [&str; 12] = [
+    "wallet_meta",
+    "accounts",
+    "account_addresses",
+    "transactions",
+    "stealth_addresses",
+    "stealth_outputs",
+    "commitment_proofs",
+    "sync_state",
+    "sync_gaps",
+    "sync_accounts",
+    "address_counters",
+    "address_pool",
+];
+
+/// Tables covered by `content_hash`: wallet contents, not counters or sync progress
+const CONTENT_TABLES: [&str; 6] = [
+    "accounts",
+    "account_addresses",
+    "transactions",
+    "stealth_addresses",
+    "stealth_outputs",
+    "commitment_proofs",
+];
+
+/// Chain name and `accounts` column of each indexed address
+const ACCOUNT_ADDRESS_COLUMNS: [(&str, &str); 5] = [
+    ("ethereum", "ethereum_address"),
+    ("solana", "solana_address"),
+    ("bitcoin", "bitcoin_address"),
+    ("polygon", "polygon_address"),
+    ("zcash", "zcash_address"),
+];
+
+/// Reverse index from chain address to owning account
+///
+/// Rows go away with their account, so the index never points at a
+/// deleted one.
+const ACCOUNT_ADDRESSES_TABLE: &str = "CREATE TABLE IF NOT EXISTS account_addresses (
+    address TEXT NOT NULL,
+    chain TEXT NOT NULL,
+    account_id INTEGER NOT NULL,
+    PRIMARY KEY(address, chain, account_id),
+    FOREIGN KEY(account_id) REFERENCES accounts(id) ON DELETE CASCADE
+)";
+
+/// Wrong-password counters driving the wallet's password backoff
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Lockout {
+    /// Consecutive wrong passwords
+    pub failed_attempts: u32,
+    /// Unix time before which no password is tried
+    pub locked_until: i64,
+}
+
+/// File beside the database holding its `Lockout` under `sqlcipher`
+fn lockout_path(db_path: &Path) -> std::path::PathBuf {
+    let mut path = db_path.as_os_str().to_owned();
+    path.push("-lockout");
+    path.into()
+}
+
+/// Second, innocuous database sealed into a hidden volume
+pub struct DecoyConfig<'a> {
+    /// Database revealed by the decoy password
+    pub db: &'a EncryptedDb,
+    /// Password that opens the decoy instead of the real wallet
+    pub password: &'a str,
+}
+
 /// Encrypted database manager
 #[derive(ZeroizeOnDrop)]
 pub struct EncryptedDb {
@@ -11,30 +401,176 @@ pub struct EncryptedDb {
     conn: Connection,
     #[zeroize(skip)]
     db_path: String,
+    #[zeroize(skip)]
+    clock: Arc<dyn Clock>,
+    #[zeroize(skip)]
+    max_clock_skew: Duration,
+    #[zeroize(skip)]
+    config: StorageConfig,
+    /// Key for field-level encryption of secret columns
+    field_key: FieldKey,
 }
 
 impl EncryptedDb {
     /// Create or open encrypted database
-    pub fn new<P: AsRef<Path>>(path: P, _password: &str) -> Result<Self> {
+    pub fn new<P: AsRef<Path>>(path: P, password: &str) -> Result<Self> {
+        Self::with_clock(path, password, Arc::new(SystemClock))
+    }
+    
+    /// Create or open encrypted database using the given time source
+    /// for all `created_at` timestamps
+    pub fn with_clock<P: AsRef<Path>>(
+        path: P,
+        password: &str,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self> {
+        Self::with_config(path, password, clock, StorageConfig::default())
+    }
+    
+    /// Create or open encrypted database with SQLite tuning options
+    ///
+    /// `password` unwraps the key protecting secret columns. A wrong
+    /// password still opens the database, but reading or writing those
+    /// columns fails.
+    pub fn with_config<P: AsRef<Path>>(
+        path: P,
+        password: &str,
+        clock: Arc<dyn Clock>,
+        config: StorageConfig,
+    ) -> Result<Self> {
+        config.validate()?;
         let db_path = path.as_ref().to_string_lossy().to_string();
         
-        let conn = Connection::open(&db_path)
-            .map_err(|e| CoreError::Storage(format!("Failed to open database: {}", e)))?;
+        let conn = retry_transient(
+            config.open_attempts.unwrap_or(DEFAULT_OPEN_ATTEMPTS),
+            config.open_retry_delay.unwrap_or(DEFAULT_OPEN_RETRY_DELAY),
+            || Self::open_connection(&db_path, password, &config),
+        ).map_err(|e| match e {
+            // SQLCipher cannot tell a wrong key from a file that is not a database
+            rusqlite::Error::SqliteFailure(err, _)
+                if cfg!(feature = "sqlcipher") && err.code == rusqlite::ErrorCode::NotADatabase =>
+            {
+                CoreError::InvalidParameter("Incorrect password".into())
+            }
+            e => CoreError::Storage(format!("Failed to open database: {}", e)),
+        })?;
+        
+        let mut db = EncryptedDb {
+            conn,
+            db_path,
+            clock,
+            max_clock_skew: config.max_clock_skew.unwrap_or(DEFAULT_MAX_CLOCK_SKEW),
+            config,
+            field_key: FieldKey::default(),
+        };
+        db.initialize_schema()?;
+        db.set_meta(MetadataKey::Encryption, if db.is_encrypted() { "sqlcipher" } else { "none" })?;
+        db.field_key = FieldKey::unlock(&db, password)?;
+        if db.get_meta(MetadataKey::FieldsEncrypted)?.is_none() {
+            db.encrypt_legacy_fields()?;
+        }
+        
+        Ok(db)
+    }
+    
+    /// Open the SQLite connection and apply connection pragmas
+    ///
+    /// These are the first statements to touch the file, so they are where
+    /// a locked or unavailable database shows up.
+    #[cfg_attr(not(feature = "sqlcipher"), allow(unused_variables))]
+    fn open_connection(db_path: &str, password: &str, config: &StorageConfig) -> rusqlite::Result<Connection> {
+        let conn = Connection::open(db_path)?;
+        
+        // Without the `sqlcipher` feature the file itself is plain SQLite;
+        // only the field-encrypted columns are protected (see `is_encrypted`)
+        #[cfg(feature = "sqlcipher")]
+        conn.pragma_update(None, "key", password)?;
         
-        // NOTE: For production, use SQLCipher build of rusqlite
-        // For now, we'll use plain SQLite for development
-        // To enable encryption, compile with: cargo build --features "rusqlite/sqlcipher"
+        // Page size must be set before WAL mode and before any table exists;
+        // SQLCipher sets it through `cipher_page_size` once keyed
+        if let Some(page_size) = config.page_size {
+            let pragma = if cfg!(feature = "sqlcipher") { "cipher_page_size" } else { "page_size" };
+            conn.execute_batch(&format!("PRAGMA {} = {};", pragma, page_size))?;
+        }
         
         // Performance optimizations using execute_batch (doesn't expect return values)
         conn.execute_batch(
             "PRAGMA journal_mode = WAL;
              PRAGMA synchronous = NORMAL;"
-        ).map_err(|e| CoreError::Storage(format!("Failed to set pragmas: {}", e)))?;
+        )?;
         
-        let mut db = EncryptedDb { conn, db_path };
-        db.initialize_schema()?;
+        if let Some(cache_size) = config.cache_size {
+            conn.execute_batch(&format!("PRAGMA cache_size = {};", cache_size))?;
+        }
         
-        Ok(db)
+        Ok(conn)
+    }
+    
+    /// Open `path` as a bare connection, keyed as `open_connection` keys it
+    #[cfg(test)]
+    #[cfg_attr(not(feature = "sqlcipher"), allow(unused_variables))]
+    pub(crate) fn raw_connection<P: AsRef<Path>>(path: P, password: &str) -> Connection {
+        let conn = Connection::open(path).unwrap();
+        #[cfg(feature = "sqlcipher")]
+        conn.pragma_update(None, "key", password).unwrap();
+        conn
+    }
+    
+    /// Read a non-secret summary of a wallet without unlocking it
+    ///
+    /// Opens the database read-only and reads only `wallet_version`,
+    /// `created_at`, the account count and the latest transaction time.
+    /// This works on plaintext development databases; a fully encrypted
+    /// database cannot be read without its key and returns a storage error.
+    pub fn read_public_summary<P: AsRef<Path>>(path: P) -> Result<WalletSummary> {
+        let conn = Connection::open_with_flags(
+            path.as_ref(),
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        ).map_err(|e| CoreError::Storage(format!("Failed to open database: {}", e)))?;
+        
+        let locked = |e: rusqlite::Error| {
+            CoreError::Storage(format!("Wallet summary unavailable (database locked or encrypted): {}", e))
+        };
+        
+        let meta = |key: &str| -> Result<Option<String>> {
+            match conn.query_row("SELECT value FROM wallet_meta WHERE key = ?1", params![key], |row| row.get(0)) {
+                Ok(value) => Ok(Some(value)),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(locked(e)),
+            }
+        };
+        
+        let wallet_version = meta(MetadataKey::WalletVersion.as_str())?;
+        let created_at = meta(MetadataKey::CreatedAt.as_str())?;
+        
+        let account_count: u32 = conn.query_row("SELECT COUNT(*) FROM accounts", [], |row| row.get(0))
+            .map_err(locked)?;
+        let last_activity: Option<i64> = conn.query_row("SELECT MAX(timestamp) FROM transactions", [], |row| row.get(0))
+            .map_err(locked)?;
+        
+        Ok(WalletSummary {
+            wallet_version,
+            created_at,
+            account_count,
+            last_activity,
+        })
+    }
+    
+    /// Time source used for stored timestamps
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+    
+    /// Current time as unix seconds, according to the injected clock
+    ///
+    /// A clock set before the unix epoch yields a storage error rather
+    /// than a panic.
+    pub(crate) fn unix_now(&self) -> Result<i64> {
+        self.clock
+            .now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .map_err(|e| CoreError::Storage(format!("System clock is before unix epoch: {}", e)))
     }
     
     /// Initialize database schema
@@ -48,6 +584,9 @@ impl EncryptedDb {
             [],
         ).map_err(|e| CoreError::Storage(format!("Schema creation failed: {}", e)))?;
         
+        // Refuse newer schemas before any migration touches them
+        self.check_reader_version()?;
+        
         // Accounts table
         self.conn.execute(
             "CREATE TABLE IF NOT EXISTS accounts (
@@ -133,6 +672,80 @@ impl EncryptedDb {
             [],
         ).map_err(|e| CoreError::Storage(format!("Schema creation failed: {}", e)))?;
         
+        // Chain sync progress (single row)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS sync_state (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                last_scanned_height INTEGER,
+                session_start INTEGER,
+                session_target INTEGER,
+                session_next INTEGER
+            )",
+            [],
+        ).map_err(|e| CoreError::Storage(format!("Schema creation failed: {}", e)))?;
+        
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS sync_gaps (
+                start_height INTEGER PRIMARY KEY,
+                end_height INTEGER NOT NULL
+            )",
+            [],
+        ).map_err(|e| CoreError::Storage(format!("Schema creation failed: {}", e)))?;
+        
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS sync_accounts (
+                account_id INTEGER PRIMARY KEY,
+                last_scanned_height INTEGER,
+                in_session BOOLEAN NOT NULL DEFAULT 0,
+                FOREIGN KEY(account_id) REFERENCES accounts(id)
+            )",
+            [],
+        ).map_err(|e| CoreError::Storage(format!("Schema creation failed: {}", e)))?;
+        
+        // Next unused HD address index per account, coin and branch
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS address_counters (
+                account_id INTEGER NOT NULL,
+                coin_type INTEGER NOT NULL,
+                branch INTEGER NOT NULL,
+                next_index INTEGER NOT NULL,
+                PRIMARY KEY(account_id, coin_type, branch),
+                FOREIGN KEY(account_id) REFERENCES accounts(id)
+            )",
+            [],
+        ).map_err(|e| CoreError::Storage(format!("Schema creation failed: {}", e)))?;
+        
+        // Addresses derived ahead of time for handing out while offline
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS address_pool (
+                account_id INTEGER NOT NULL,
+                coin_type INTEGER NOT NULL,
+                branch INTEGER NOT NULL,
+                address_index INTEGER NOT NULL,
+                address TEXT NOT NULL,
+                used BOOLEAN NOT NULL DEFAULT 0,
+                PRIMARY KEY(account_id, coin_type, branch, address_index),
+                FOREIGN KEY(account_id) REFERENCES accounts(id)
+            )",
+            [],
+        ).map_err(|e| CoreError::Storage(format!("Schema creation failed: {}", e)))?;
+        
+        // Reverse index from chain address to owning account
+        self.conn.execute(ACCOUNT_ADDRESSES_TABLE, [])
+            .map_err(|e| CoreError::Storage(format!("Schema creation failed: {}", e)))?;
+        
+        // Append-only, hash-chained record of mutations
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                operation TEXT NOT NULL,
+                detail TEXT NOT NULL,
+                hash BLOB NOT NULL
+            )",
+            [],
+        ).map_err(|e| CoreError::Storage(format!("Schema creation failed: {}", e)))?;
+        
         // Create indexes
         self.conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_tx_account ON transactions(account_id)",
@@ -149,10 +762,122 @@ impl EncryptedDb {
             [],
         ).map_err(|e| CoreError::Storage(format!("Index creation failed: {}", e)))?;
         
+        self.run_migrations()?;
+        
+        Ok(())
+    }
+    
+    /// Fail if the database needs a newer crate to read it, then record
+    /// this version's own requirement
+    fn check_reader_version(&self) -> Result<()> {
+        let required = match self.get_meta(MetadataKey::MinReaderVersion)? {
+            Some(stored) => {
+                let stored_version = parse_version(&stored)?;
+                if stored_version > parse_version(CRATE_VERSION)? {
+                    return Err(CoreError::Storage("database requires newer version".into()));
+                }
+                stored_version
+            }
+            None => Vec::new(),
+        };
+        
+        if required < parse_version(MIN_READER_VERSION)? {
+            self.set_meta(MetadataKey::MinReaderVersion, MIN_READER_VERSION)?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Bring tables created by older versions up to the current schema
+    fn run_migrations(&self) -> Result<()> {
+        // UTXO reservations
+        self.add_column_if_missing("stealth_outputs", "reserved", "BOOLEAN NOT NULL DEFAULT 0")?;
+        self.add_column_if_missing("stealth_outputs", "reserved_until", "INTEGER")?;
+        
+        // Link commitment openings to the output they commit to
+        self.add_column_if_missing("commitment_proofs", "output_id", "INTEGER REFERENCES stealth_outputs(id)")?;
+        
+        // Confirmation depth for display
+        self.add_column_if_missing("transactions", "confirmations", "INTEGER")?;
+        
+        // Effective fee paid, separate from the gas units in `gas_used`
+        self.add_column_if_missing("transactions", "fee", "TEXT")?;
+        
+        // Outputs received on our own change subaddress
+        self.add_column_if_missing("stealth_outputs", "is_change", "BOOLEAN NOT NULL DEFAULT 0")?;
+        
+        // Tiny outputs flagged as likely dust attacks
+        self.add_column_if_missing("stealth_outputs", "dust", "BOOLEAN NOT NULL DEFAULT 0")?;
+        
+        // Coinbase provenance for maturity checks
+        self.add_column_if_missing("stealth_outputs", "coinbase_height", "INTEGER")?;
+        
+        // Index of an output within its transaction, for spending it
+        self.add_column_if_missing("stealth_outputs", "output_index", "INTEGER")?;
+        
+        // Transaction that spent an output, so pruning can wait for it to confirm
+        self.add_column_if_missing("stealth_outputs", "spent_by", "TEXT")?;
+        
+        // Whether range_proof holds zstd-compressed bytes
+        self.add_column_if_missing("commitment_proofs", "range_proof_compressed", "BOOLEAN NOT NULL DEFAULT 0")?;
+        
+        // Private, field-encrypted account notes
+        self.add_column_if_missing("accounts", "note", "BLOB")?;
+        
+        // Rebuild a reverse index created without ON DELETE CASCADE; the
+        // backfill below repopulates it from accounts
+        let cascades: bool = self.conn.query_row(
+            "SELECT EXISTS(
+                SELECT 1 FROM pragma_foreign_key_list('account_addresses') WHERE on_delete = 'CASCADE'
+            )",
+            [],
+            |row| row.get(0),
+        ).map_err(|e| CoreError::Storage(format!("Failed to inspect account address index: {}", e)))?;
+        if !cascades {
+            self.conn.execute_batch(&format!("DROP TABLE account_addresses; {};", ACCOUNT_ADDRESSES_TABLE))
+                .map_err(|e| CoreError::Storage(format!("Migration failed: {}", e)))?;
+        }
+        
+        // Index addresses of accounts stored before the reverse index existed
+        for (chain, column) in ACCOUNT_ADDRESS_COLUMNS {
+            self.conn.execute(
+                &format!(
+                    "INSERT OR IGNORE INTO account_addresses (address, chain, account_id)
+                     SELECT {column}, ?1, id FROM accounts"
+                ),
+                params![chain],
+            ).map_err(|e| CoreError::Storage(format!("Failed to index account addresses: {}", e)))?;
+        }
+        
+        // One row per on-chain output; clear duplicates left by re-scans first
+        self.dedupe_stealth_outputs()?;
+        self.conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_stealth_unique_output
+             ON stealth_outputs(tx_hash, one_time_public)",
+            [],
+        ).map_err(|e| CoreError::Storage(format!("Index creation failed: {}", e)))?;
+        
+        Ok(())
+    }
+    
+    /// Add a column to an existing table unless it is already present
+    fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+        let exists = table_columns(&self.conn, table)?.iter().any(|name| name == column);
+        
+        if !exists {
+            self.conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+                [],
+            ).map_err(|e| CoreError::Storage(format!("Migration failed: {}", e)))?;
+        }
+        
         Ok(())
     }
     
-    /// Store wallet metadata
+    /// Store wallet metadata under an arbitrary key
+    ///
+    /// Prefer `set_meta` for the keys in `MetadataKey`; this raw form is for
+    /// extensions.
     pub fn set_metadata(&self, key: &str, value: &str) -> Result<()> {
         self.conn.execute(
             "INSERT OR REPLACE INTO wallet_meta (key, value) VALUES (?1, ?2)",
@@ -177,14 +902,40 @@ impl EncryptedDb {
         }
     }
     
+    /// Store a well-known metadata value
+    pub fn set_meta(&self, key: MetadataKey, value: &str) -> Result<()> {
+        self.set_metadata(key.as_str(), value)
+    }
+    
+    /// Get a well-known metadata value
+    pub fn get_meta(&self, key: MetadataKey) -> Result<Option<String>> {
+        self.get_metadata(key.as_str())
+    }
+    
+    /// Set several well-known metadata values atomically
+    pub fn set_metas(&self, entries: &[(MetadataKey, &str)]) -> Result<()> {
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        for (key, value) in entries {
+            sql_tx.execute(
+                "INSERT OR REPLACE INTO wallet_meta (key, value) VALUES (?1, ?2)",
+                params![key.as_str(), value],
+            ).map_err(|e| CoreError::Storage(format!("Failed to set metadata: {}", e)))?;
+        }
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit metadata: {}", e)))
+    }
+    
     /// Store account
     pub fn store_account(&self, account: &StoredAccount) -> Result<i64> {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        let timestamp = self.unix_now()?;
         
-        self.conn.execute(
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        sql_tx.execute(
             "INSERT INTO accounts (
                 account_index, name, ethereum_address, solana_address,
                 bitcoin_address, polygon_address, zcash_address, created_at
@@ -200,8 +951,62 @@ impl EncryptedDb {
                 timestamp,
             ],
         ).map_err(|e| CoreError::Storage(format!("Failed to store account: {}", e)))?;
+        let account_id = sql_tx.last_insert_rowid();
         
-        Ok(self.conn.last_insert_rowid())
+        let addresses = [
+            &account.ethereum_address,
+            &account.solana_address,
+            &account.bitcoin_address,
+            &account.polygon_address,
+            &account.zcash_address,
+        ];
+        for ((chain, _), address) in ACCOUNT_ADDRESS_COLUMNS.iter().zip(addresses) {
+            sql_tx.execute(
+                "INSERT OR IGNORE INTO account_addresses (address, chain, account_id) VALUES (?1, ?2, ?3)",
+                params![address, chain, account_id],
+            ).map_err(|e| CoreError::Storage(format!("Failed to index account address: {}", e)))?;
+        }
+        append_audit(&sql_tx, &self.field_key, timestamp, AuditOperation::AccountAdded, &account.index.to_string())?;
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit account: {}", e)))?;
+        
+        Ok(account_id)
+    }
+    
+    /// Find the account owning `address` on any chain
+    ///
+    /// Uses the `account_addresses` reverse index rather than scanning every
+    /// address column.
+    pub fn account_for_address(&self, address: &str) -> Result<Option<StoredAccount>> {
+        let result = self.conn.query_row(
+            "SELECT a.id, a.account_index, a.name, a.ethereum_address, a.solana_address,
+                    a.bitcoin_address, a.polygon_address, a.zcash_address
+             FROM account_addresses aa
+             JOIN accounts a ON a.id = aa.account_id
+             WHERE aa.address = ?1
+             ORDER BY a.account_index
+             LIMIT 1",
+            params![address],
+            |row| {
+                Ok(StoredAccount {
+                    id: row.get(0)?,
+                    index: row.get(1)?,
+                    name: row.get(2)?,
+                    ethereum_address: row.get(3)?,
+                    solana_address: row.get(4)?,
+                    bitcoin_address: row.get(5)?,
+                    polygon_address: row.get(6)?,
+                    zcash_address: row.get(7)?,
+                })
+            },
+        );
+        
+        match result {
+            Ok(account) => Ok(Some(account)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(CoreError::Storage(format!("Failed to look up address: {}", e))),
+        }
     }
     
     /// Get account by index
@@ -258,12 +1063,76 @@ impl EncryptedDb {
     }
     
     /// Store transaction
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "db_store_transaction", skip_all))]
     pub fn store_transaction(&self, tx: &StoredTransaction) -> Result<i64> {
-        self.conn.execute(
+        self.check_timestamp(tx.timestamp)?;
+        check_amount(&tx.chain, &tx.amount)?;
+        let timestamp = self.unix_now()?;
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        let id = Self::insert_transaction(&sql_tx, tx)?;
+        append_audit(&sql_tx, &self.field_key, timestamp, AuditOperation::TransactionRecorded, &tx.tx_hash)?;
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit transaction: {}", e)))?;
+        
+        Ok(id)
+    }
+    
+    /// Record a replacement (RBF) transaction
+    ///
+    /// Inserts `new_tx` and marks the pending transaction `old_hash` as
+    /// `replaced` atomically.
+    pub fn replace_transaction(&self, old_hash: &str, new_tx: &StoredTransaction) -> Result<i64> {
+        self.check_timestamp(new_tx.timestamp)?;
+        check_amount(&new_tx.chain, &new_tx.amount)?;
+        let timestamp = self.unix_now()?;
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        let updated = sql_tx.execute(
+            "UPDATE transactions SET status = 'replaced' WHERE tx_hash = ?1 AND status = 'pending'",
+            params![old_hash],
+        ).map_err(|e| CoreError::Storage(format!("Failed to mark transaction replaced: {}", e)))?;
+        
+        if updated == 0 {
+            return Err(CoreError::InvalidParameter("No pending transaction to replace".into()));
+        }
+        
+        let id = Self::insert_transaction(&sql_tx, new_tx)?;
+        append_audit(&sql_tx, &self.field_key, timestamp, AuditOperation::TransactionRecorded, &new_tx.tx_hash)?;
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit replacement: {}", e)))?;
+        
+        Ok(id)
+    }
+    
+    /// Reject negative timestamps and ones too far past the clock's now
+    fn check_timestamp(&self, timestamp: i64) -> Result<()> {
+        if timestamp < 0 {
+            return Err(CoreError::InvalidParameter(format!("Negative timestamp {}", timestamp)));
+        }
+        
+        let latest = self.unix_now()?.saturating_add(self.max_clock_skew.as_secs() as i64);
+        if timestamp > latest {
+            return Err(CoreError::InvalidParameter(format!(
+                "Timestamp {} is more than {}s in the future", timestamp, self.max_clock_skew.as_secs()
+            )));
+        }
+        
+        Ok(())
+    }
+    
+    /// Insert a transaction row using the given connection (or open transaction)
+    fn insert_transaction(conn: &Connection, tx: &StoredTransaction) -> Result<i64> {
+        conn.execute(
             "INSERT INTO transactions (
                 tx_hash, account_id, chain, type, amount,
-                from_address, to_address, status, timestamp
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                from_address, to_address, status, timestamp,
+                block_number, gas_used, confirmations, fee
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
             params![
                 tx.tx_hash,
                 tx.account_id,
@@ -274,114 +1143,1456 @@ impl EncryptedDb {
                 tx.to_address,
                 tx.status,
                 tx.timestamp,
+                tx.block_number,
+                tx.gas_used,
+                tx.confirmations,
+                tx.fee,
             ],
         ).map_err(|e| CoreError::Storage(format!("Failed to store transaction: {}", e)))?;
         
-        Ok(self.conn.last_insert_rowid())
+        Ok(conn.last_insert_rowid())
     }
     
-    /// Get transactions for account
+    /// Get the most recent transactions for account
+    ///
+    /// `limit` is clamped to `MAX_TRANSACTION_LIMIT`; use
+    /// `for_each_transaction` to walk a longer history.
     pub fn get_transactions(&self, account_id: i64, limit: u32) -> Result<Vec<StoredTransaction>> {
+        let limit = limit.min(MAX_TRANSACTION_LIMIT);
+        
         let mut stmt = self.conn.prepare(
             "SELECT id, tx_hash, account_id, chain, type, amount,
-                    from_address, to_address, status, timestamp, block_number, gas_used
+                    from_address, to_address, status, timestamp, block_number, gas_used,
+                    confirmations, fee
              FROM transactions
              WHERE account_id = ?1
              ORDER BY timestamp DESC
              LIMIT ?2"
         ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
         
-        let txs = stmt.query_map(params![account_id, limit], |row| {
-            Ok(StoredTransaction {
-                id: row.get(0)?,
-                tx_hash: row.get(1)?,
-                account_id: row.get(2)?,
-                chain: row.get(3)?,
-                tx_type: row.get(4)?,
-                amount: row.get(5)?,
-                from_address: row.get(6)?,
-                to_address: row.get(7)?,
-                status: row.get(8)?,
-                timestamp: row.get(9)?,
-                block_number: row.get(10)?,
-                gas_used: row.get(11)?,
-            })
-        }).map_err(|e| CoreError::Storage(format!("Failed to query transactions: {}", e)))?;
+        let txs = stmt.query_map(params![account_id, limit], Self::transaction_from_row)
+            .map_err(|e| CoreError::Storage(format!("Failed to query transactions: {}", e)))?;
         
         txs.collect::<std::result::Result<Vec<_>, _>>()
             .map_err(|e| CoreError::Storage(format!("Failed to collect transactions: {}", e)))
     }
     
+    /// Visit every transaction for account, newest first, one row at a time
+    ///
+    /// Rows are handed to `f` as they are read, so memory use does not grow
+    /// with the history. Returns the number of rows visited.
+    pub fn for_each_transaction<F>(&self, account_id: i64, mut f: F) -> Result<usize>
+    where
+        F: FnMut(StoredTransaction),
+    {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, tx_hash, account_id, chain, type, amount,
+                    from_address, to_address, status, timestamp, block_number, gas_used,
+                    confirmations, fee
+             FROM transactions
+             WHERE account_id = ?1
+             ORDER BY timestamp DESC"
+        ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+        
+        let txs = stmt.query_map(params![account_id], Self::transaction_from_row)
+            .map_err(|e| CoreError::Storage(format!("Failed to query transactions: {}", e)))?;
+        
+        let mut visited = 0;
+        for tx in txs {
+            f(tx.map_err(|e| CoreError::Storage(format!("Failed to read transaction: {}", e)))?);
+            visited += 1;
+        }
+        
+        Ok(visited)
+    }
+    
+    /// Get transactions for account with `from_ts <= timestamp < to_ts`
+    pub fn get_transactions_between(
+        &self,
+        account_id: i64,
+        from_ts: i64,
+        to_ts: i64,
+    ) -> Result<Vec<StoredTransaction>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, tx_hash, account_id, chain, type, amount,
+                    from_address, to_address, status, timestamp, block_number, gas_used,
+                    confirmations, fee
+             FROM transactions
+             WHERE account_id = ?1 AND timestamp >= ?2 AND timestamp < ?3
+             ORDER BY timestamp DESC"
+        ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+        
+        let txs = stmt.query_map(params![account_id, from_ts, to_ts], Self::transaction_from_row)
+            .map_err(|e| CoreError::Storage(format!("Failed to query transactions: {}", e)))?;
+        
+        txs.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| CoreError::Storage(format!("Failed to collect transactions: {}", e)))
+    }
+    
+    /// Distinct chains an account has transactions on, alphabetically
+    pub fn get_active_chains(&self, account_id: i64) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT chain FROM transactions WHERE account_id = ?1 ORDER BY chain"
+        ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+        
+        let chains = stmt.query_map(params![account_id], |row| row.get(0))
+            .map_err(|e| CoreError::Storage(format!("Failed to query chains: {}", e)))?;
+        
+        chains.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| CoreError::Storage(format!("Failed to collect chains: {}", e)))
+    }
+    
+    /// Transactions still pending, oldest first, across all accounts
+    ///
+    /// Pass a `chain` to poll one chain only. Lets a syncer query the chain
+    /// for just the transactions whose status can still change.
+    pub fn pending_transactions(&self, chain: Option<&str>) -> Result<Vec<StoredTransaction>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, tx_hash, account_id, chain, type, amount,
+                    from_address, to_address, status, timestamp, block_number, gas_used,
+                    confirmations, fee
+             FROM transactions
+             WHERE status = 'pending' AND (?1 IS NULL OR chain = ?1)
+             ORDER BY timestamp, id"
+        ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+        
+        let txs = stmt.query_map(params![chain], Self::transaction_from_row)
+            .map_err(|e| CoreError::Storage(format!("Failed to query transactions: {}", e)))?;
+        
+        txs.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| CoreError::Storage(format!("Failed to collect transactions: {}", e)))
+    }
+    
+    /// Claim the next address index of an account's HD branch
+    ///
+    /// Counters start at `first` and are persisted, so an index is never
+    /// handed out twice even across restarts.
+    pub fn take_address_index(&self, account_id: i64, coin_type: u32, branch: u32, first: u32) -> Result<u32> {
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        let index = Self::advance_address_counter(&sql_tx, account_id, coin_type, branch, first, 1)?;
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit address counter: {}", e)))?;
+        
+        Ok(index)
+    }
+    
+    /// Claim `count` consecutive indices and store `derive`'s addresses for
+    /// them as unused pool entries, all in one transaction
+    ///
+    /// `derive` receives the claimed index range. Returns the stored
+    /// addresses in index order.
+    pub fn pregenerate_addresses<F>(
+        &self,
+        account_id: i64,
+        coin_type: u32,
+        branch: u32,
+        first: u32,
+        count: u32,
+        derive: F,
+    ) -> Result<Vec<String>>
+    where
+        F: FnOnce(std::ops::Range<u32>) -> Result<Vec<String>>,
+    {
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        let start = Self::advance_address_counter(&sql_tx, account_id, coin_type, branch, first, count)?;
+        let addresses = derive(start..start + count)?;
+        
+        for (index, address) in (start..).zip(&addresses) {
+            sql_tx.execute(
+                "INSERT INTO address_pool (account_id, coin_type, branch, address_index, address)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![account_id, coin_type, branch, index, address],
+            ).map_err(|e| CoreError::Storage(format!("Failed to store pooled address: {}", e)))?;
+        }
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit address pool: {}", e)))?;
+        
+        Ok(addresses)
+    }
+    
+    /// Mark the lowest unused pooled address of an HD branch used and
+    /// return it with its index, or `None` if the pool is empty
+    pub fn take_pooled_address(&self, account_id: i64, coin_type: u32, branch: u32) -> Result<Option<(String, u32)>> {
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        let pooled = sql_tx.query_row(
+            "SELECT address, address_index FROM address_pool
+             WHERE account_id = ?1 AND coin_type = ?2 AND branch = ?3 AND used = 0
+             ORDER BY address_index LIMIT 1",
+            params![account_id, coin_type, branch],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?)),
+        );
+        let (address, index) = match pooled {
+            Ok(entry) => entry,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(CoreError::Storage(format!("Failed to read address pool: {}", e))),
+        };
+        
+        sql_tx.execute(
+            "UPDATE address_pool SET used = 1
+             WHERE account_id = ?1 AND coin_type = ?2 AND branch = ?3 AND address_index = ?4",
+            params![account_id, coin_type, branch, index],
+        ).map_err(|e| CoreError::Storage(format!("Failed to mark pooled address used: {}", e)))?;
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit address pool: {}", e)))?;
+        
+        Ok(Some((address, index)))
+    }
+    
+    /// Advance an address counter by `count` within `conn`'s transaction,
+    /// returning the first claimed index
+    fn advance_address_counter(
+        conn: &Connection,
+        account_id: i64,
+        coin_type: u32,
+        branch: u32,
+        first: u32,
+        count: u32,
+    ) -> Result<u32> {
+        conn.execute(
+            "INSERT OR IGNORE INTO address_counters (account_id, coin_type, branch, next_index)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![account_id, coin_type, branch, first],
+        ).map_err(|e| CoreError::Storage(format!("Failed to create address counter: {}", e)))?;
+        
+        let index: u32 = conn.query_row(
+            "SELECT next_index FROM address_counters WHERE account_id = ?1 AND coin_type = ?2 AND branch = ?3",
+            params![account_id, coin_type, branch],
+            |row| row.get(0),
+        ).map_err(|e| CoreError::Storage(format!("Failed to read address counter: {}", e)))?;
+        
+        let next = index.checked_add(count)
+            .ok_or_else(|| CoreError::KeyDerivation("Address index space exhausted".into()))?;
+        conn.execute(
+            "UPDATE address_counters SET next_index = ?4 WHERE account_id = ?1 AND coin_type = ?2 AND branch = ?3",
+            params![account_id, coin_type, branch, next],
+        ).map_err(|e| CoreError::Storage(format!("Failed to advance address counter: {}", e)))?;
+        
+        Ok(index)
+    }
+    
+    /// Map a `transactions` row to a `StoredTransaction`
+    fn transaction_from_row(row: &rusqlite::Row) -> rusqlite::Result<StoredTransaction> {
+        Ok(StoredTransaction {
+            id: row.get(0)?,
+            tx_hash: row.get(1)?,
+            account_id: row.get(2)?,
+            chain: row.get(3)?,
+            tx_type: row.get(4)?,
+            amount: row.get(5)?,
+            from_address: row.get(6)?,
+            to_address: row.get(7)?,
+            status: row.get(8)?,
+            timestamp: row.get(9)?,
+            block_number: row.get(10)?,
+            gas_used: row.get(11)?,
+            confirmations: row.get(12)?,
+            fee: row.get(13)?,
+        })
+    }
+    
+    /// Set the confirmation depth of a transaction
+    pub fn update_confirmations(&self, tx_hash: &str, confirmations: u32) -> Result<()> {
+        let updated = self.conn.execute(
+            "UPDATE transactions SET confirmations = ?2 WHERE tx_hash = ?1",
+            params![tx_hash, confirmations],
+        ).map_err(|e| CoreError::Storage(format!("Failed to update confirmations: {}", e)))?;
+        
+        if updated == 0 {
+            return Err(CoreError::InvalidParameter("Transaction not found".into()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Recompute confirmations of all confirmed transactions for a chain tip
+    ///
+    /// Confirmations are `current_height - block_number`, so a transaction
+    /// in the tip block has none. Returns the number of transactions updated.
+    pub fn update_confirmations_for_height(&self, current_height: u64) -> Result<usize> {
+        self.conn.execute(
+            "UPDATE transactions
+             SET confirmations = MAX(0, ?1 - block_number)
+             WHERE status = 'confirmed' AND block_number IS NOT NULL",
+            params![current_height as i64],
+        ).map_err(|e| CoreError::Storage(format!("Failed to update confirmations: {}", e)))
+    }
+    
+    /// Store an account's stealth key pair
+    ///
+    /// The private keys are field-encrypted; the public keys stay readable
+    /// so addresses can be listed while the field key is locked.
+    pub fn store_stealth_keys(
+        &self,
+        account_id: i64,
+        spend_public: &[u8],
+        view_public: &[u8],
+        spend_private: &[u8],
+        view_private: &[u8],
+    ) -> Result<i64> {
+        let timestamp = self.unix_now()?;
+        let spend_private = self.field_key.encrypt("spend_private", spend_private)?;
+        let view_private = self.field_key.encrypt("view_private", view_private)?;
+        
+        self.conn.execute(
+            "INSERT INTO stealth_addresses (
+                account_id, spend_public, view_public, spend_private, view_private, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![account_id, spend_public, view_public, spend_private, view_private, timestamp],
+        ).map_err(|e| CoreError::Storage(format!("Failed to store stealth keys: {}", e)))?;
+        
+        Ok(self.conn.last_insert_rowid())
+    }
+    
+    /// Spend and view public keys of an account's stealth addresses,
+    /// oldest first
+    pub fn list_stealth_public_keys(&self, account_id: i64) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT spend_public, view_public FROM stealth_addresses WHERE account_id = ?1 ORDER BY id"
+        ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+        
+        let keys = stmt.query_map(params![account_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| CoreError::Storage(format!("Failed to query stealth addresses: {}", e)))?;
+        
+        keys.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| CoreError::Storage(format!("Failed to collect stealth addresses: {}", e)))
+    }
+    
     /// Store stealth output
+    ///
+    /// Storing an output that already exists (same `tx_hash` and
+    /// `one_time_public`) is a no-op that returns the existing id.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "db_store_stealth_output", skip_all))]
     pub fn store_stealth_output(&self, output: &StealthOutput) -> Result<i64> {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        let timestamp = self.unix_now()?;
+        self.insert_stealth_output(&self.conn, output, timestamp)
+    }
+    
+    /// Insert a stealth output using the given connection (or open transaction)
+    fn insert_stealth_output(&self, conn: &Connection, output: &StealthOutput, timestamp: i64) -> Result<i64> {
+        warn_if_plaintext();
+        let one_time_private = self.field_key.encrypt("one_time_private", &output.one_time_private)?;
+        let coinbase_height = output.coinbase_height
+            .map(i64::try_from)
+            .transpose()
+            .map_err(|_| CoreError::InvalidParameter("Coinbase height out of range".into()))?;
         
-        self.conn.execute(
-            "INSERT INTO stealth_outputs (
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO stealth_outputs (
                 tx_hash, account_id, ephemeral_public, one_time_public,
-                one_time_private, amount, spent, created_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                one_time_private, amount, spent, created_at, is_change, coinbase_height, output_index
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
             params![
                 output.tx_hash,
                 output.account_id,
                 output.ephemeral_public,
                 output.one_time_public,
-                output.one_time_private,
+                one_time_private,
                 output.amount,
                 output.spent,
                 timestamp,
+                output.is_change,
+                coinbase_height,
+                output.output_index,
             ],
         ).map_err(|e| CoreError::Storage(format!("Failed to store stealth output: {}", e)))?;
         
-        Ok(self.conn.last_insert_rowid())
+        if inserted == 0 {
+            return conn.query_row(
+                "SELECT id FROM stealth_outputs WHERE tx_hash = ?1 AND one_time_public = ?2",
+                params![output.tx_hash, output.one_time_public],
+                |row| row.get(0),
+            ).map_err(|e| CoreError::Storage(format!("Failed to find existing stealth output: {}", e)));
+        }
+        
+        Ok(conn.last_insert_rowid())
+    }
+    
+    /// Remove duplicate stealth outputs, keeping the oldest row of each
+    ///
+    /// The kept row is marked spent if any duplicate was, and commitment
+    /// proofs pointing at removed rows are re-linked to it. Returns the
+    /// number of rows removed.
+    pub fn dedupe_stealth_outputs(&self) -> Result<usize> {
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        sql_tx.execute_batch(
+            "CREATE TEMP TABLE stealth_keepers AS
+                SELECT MIN(id) AS keep_id, tx_hash, one_time_public, MAX(spent) AS any_spent
+                FROM stealth_outputs
+                GROUP BY tx_hash, one_time_public
+                HAVING COUNT(*) > 1;
+             
+             UPDATE stealth_outputs
+             SET spent = (SELECT any_spent FROM stealth_keepers k WHERE k.keep_id = stealth_outputs.id)
+             WHERE id IN (SELECT keep_id FROM stealth_keepers);
+             
+             UPDATE commitment_proofs
+             SET output_id = (
+                 SELECT k.keep_id FROM stealth_outputs so
+                 JOIN stealth_keepers k ON k.tx_hash = so.tx_hash AND k.one_time_public = so.one_time_public
+                 WHERE so.id = commitment_proofs.output_id
+             )
+             WHERE output_id IN (
+                 SELECT so.id FROM stealth_outputs so
+                 JOIN stealth_keepers k ON k.tx_hash = so.tx_hash AND k.one_time_public = so.one_time_public
+                 WHERE so.id != k.keep_id
+             );"
+        ).map_err(|e| CoreError::Storage(format!("Failed to merge duplicate outputs: {}", e)))?;
+        
+        let removed = sql_tx.execute(
+            "DELETE FROM stealth_outputs
+             WHERE id IN (
+                 SELECT so.id FROM stealth_outputs so
+                 JOIN stealth_keepers k ON k.tx_hash = so.tx_hash AND k.one_time_public = so.one_time_public
+                 WHERE so.id != k.keep_id
+             )",
+            [],
+        ).map_err(|e| CoreError::Storage(format!("Failed to delete duplicate outputs: {}", e)))?;
+        
+        sql_tx.execute_batch("DROP TABLE stealth_keepers;")
+            .map_err(|e| CoreError::Storage(format!("Failed to merge duplicate outputs: {}", e)))?;
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit dedupe: {}", e)))?;
+        
+        Ok(removed)
     }
     
     /// Get unspent stealth outputs for account
     pub fn get_unspent_stealth_outputs(&self, account_id: i64) -> Result<Vec<StealthOutput>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, tx_hash, account_id, ephemeral_public, one_time_public,
-                    one_time_private, amount, spent
+                    one_time_private, amount, spent, is_change, coinbase_height, output_index
              FROM stealth_outputs
              WHERE account_id = ?1 AND spent = 0
              ORDER BY created_at DESC"
         ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
         
-        let outputs = stmt.query_map(params![account_id], |row| {
-            Ok(StealthOutput {
-                id: row.get(0)?,
-                tx_hash: row.get(1)?,
-                account_id: row.get(2)?,
-                ephemeral_public: row.get(3)?,
-                one_time_public: row.get(4)?,
-                one_time_private: row.get(5)?,
-                amount: row.get(6)?,
-                spent: row.get(7)?,
-            })
-        }).map_err(|e| CoreError::Storage(format!("Failed to query stealth outputs: {}", e)))?;
+        let outputs = stmt.query_map(params![account_id], |row| self.stealth_output_from_row(row))
+            .map_err(|e| CoreError::Storage(format!("Failed to query stealth outputs: {}", e)))?;
         
         outputs.collect::<std::result::Result<Vec<_>, _>>()
             .map_err(|e| CoreError::Storage(format!("Failed to collect stealth outputs: {}", e)))
     }
     
-    /// Mark stealth output as spent
-    pub fn mark_stealth_output_spent(&self, output_id: i64) -> Result<()> {
+    /// Get unspent stealth outputs that are not currently reserved
+    ///
+    /// Reservations whose `reserved_until` has passed are treated as released.
+    /// Outputs flagged as dust are skipped unless `include_dust` is set.
+    pub fn list_spendable_utxos(&self, account_id: i64, include_dust: bool) -> Result<Vec<StealthOutput>> {
+        let now = self.unix_now()?;
+        
+        let mut stmt = self.conn.prepare(
+            "SELECT id, tx_hash, account_id, ephemeral_public, one_time_public,
+                    one_time_private, amount, spent, is_change, coinbase_height, output_index
+             FROM stealth_outputs
+             WHERE account_id = ?1 AND spent = 0
+               AND (reserved = 0 OR reserved_until IS NULL OR reserved_until <= ?2)
+               AND (dust = 0 OR ?3)
+             ORDER BY created_at DESC"
+        ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+        
+        let outputs = stmt.query_map(params![account_id, now, include_dust], |row| self.stealth_output_from_row(row))
+            .map_err(|e| CoreError::Storage(format!("Failed to query stealth outputs: {}", e)))?;
+        
+        outputs.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| CoreError::Storage(format!("Failed to collect stealth outputs: {}", e)))
+    }
+    
+    /// Flag unspent outputs worth less than `threshold` as dust
+    ///
+    /// Amounts are compared as integers in Rust, not by SQLite's lenient
+    /// cast; any candidate whose amount is not a plain integer fails the
+    /// whole call and nothing is flagged. Returns the number of outputs
+    /// newly flagged.
+    pub fn flag_dust_outputs(&self, threshold: u64) -> Result<usize> {
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        let candidates: Vec<(i64, String)> = {
+            let mut stmt = sql_tx.prepare("SELECT id, amount FROM stealth_outputs WHERE spent = 0 AND dust = 0")
+                .map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| CoreError::Storage(format!("Failed to query stealth outputs: {}", e)))?;
+            rows.collect::<std::result::Result<_, _>>()
+                .map_err(|e| CoreError::Storage(format!("Failed to collect stealth outputs: {}", e)))?
+        };
+        
+        let mut flagged = 0;
+        for (id, amount) in candidates {
+            if amount.is_empty() || !amount.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(CoreError::Storage(format!("Output {} has a non-integer amount {:?}", id, amount)));
+            }
+            // Too many digits for u128 is far above any u64 threshold
+            if amount.parse::<u128>().is_ok_and(|value| value < u128::from(threshold)) {
+                flagged += sql_tx.execute("UPDATE stealth_outputs SET dust = 1 WHERE id = ?1", params![id])
+                    .map_err(|e| CoreError::Storage(format!("Failed to flag dust outputs: {}", e)))?;
+            }
+        }
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit dust flags: {}", e)))?;
+        
+        Ok(flagged)
+    }
+    
+    /// Get unspent outputs flagged as dust
+    pub fn get_dust_outputs(&self, account_id: i64) -> Result<Vec<StealthOutput>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, tx_hash, account_id, ephemeral_public, one_time_public,
+                    one_time_private, amount, spent, is_change, coinbase_height, output_index
+             FROM stealth_outputs
+             WHERE account_id = ?1 AND spent = 0 AND dust = 1
+             ORDER BY created_at DESC"
+        ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+        
+        let outputs = stmt.query_map(params![account_id], |row| self.stealth_output_from_row(row))
+            .map_err(|e| CoreError::Storage(format!("Failed to query dust outputs: {}", e)))?;
+        
+        outputs.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| CoreError::Storage(format!("Failed to collect dust outputs: {}", e)))
+    }
+    
+    /// Reserve an unspent stealth output for `ttl_secs` seconds
+    ///
+    /// Fails if the output is spent or already holds an unexpired reservation.
+    pub fn reserve_stealth_output(&self, output_id: i64, ttl_secs: u64) -> Result<()> {
+        let now = self.unix_now()?;
+        let until = now.saturating_add(ttl_secs as i64);
+        
+        let updated = self.conn.execute(
+            "UPDATE stealth_outputs SET reserved = 1, reserved_until = ?2
+             WHERE id = ?1 AND spent = 0
+               AND (reserved = 0 OR reserved_until IS NULL OR reserved_until <= ?3)",
+            params![output_id, until, now],
+        ).map_err(|e| CoreError::Storage(format!("Failed to reserve output: {}", e)))?;
+        
+        if updated == 0 {
+            return Err(CoreError::InvalidParameter("Output is not available for reservation".into()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Release a stealth output reservation
+    pub fn release_stealth_output(&self, output_id: i64) -> Result<()> {
         self.conn.execute(
-            "UPDATE stealth_outputs SET spent = 1 WHERE id = ?1",
+            "UPDATE stealth_outputs SET reserved = 0, reserved_until = NULL WHERE id = ?1",
             params![output_id],
+        ).map_err(|e| CoreError::Storage(format!("Failed to release output: {}", e)))?;
+        
+        Ok(())
+    }
+    
+    /// Store a commitment opening (and optional range proof)
+    pub fn store_commitment_proof(&self, proof: &StoredCommitmentProof) -> Result<i64> {
+        let timestamp = self.unix_now()?;
+        warn_if_plaintext();
+        let blinding = self.field_key.encrypt("blinding", &proof.blinding)?;
+        let (range_proof, compressed) = match &proof.range_proof {
+            Some(range_proof) => {
+                let (encoded, compressed) = encode_range_proof(range_proof)?;
+                (Some(encoded), compressed)
+            }
+            None => (None, false),
+        };
+        
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        sql_tx.execute(
+            "INSERT INTO commitment_proofs (
+                account_id, output_id, commitment, value, blinding, range_proof,
+                range_proof_compressed, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                proof.account_id,
+                proof.output_id,
+                proof.commitment,
+                proof.value,
+                blinding,
+                range_proof,
+                compressed,
+                timestamp,
+            ],
+        ).map_err(|e| CoreError::Storage(format!("Failed to store commitment proof: {}", e)))?;
+        let proof_id = sql_tx.last_insert_rowid();
+        
+        if self.config.verify_field_encryption.unwrap_or(cfg!(debug_assertions)) {
+            check_field_encrypted(&sql_tx, "commitment_proofs", "blinding", proof_id, &proof.blinding)?;
+        }
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit commitment proof: {}", e)))?;
+        
+        Ok(proof_id)
+    }
+    
+    /// Get the commitments of all unspent stealth outputs for an account
+    pub fn get_unspent_output_commitments(&self, account_id: i64) -> Result<Vec<Vec<u8>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT cp.commitment
+             FROM commitment_proofs cp
+             JOIN stealth_outputs so ON so.id = cp.output_id
+             WHERE cp.account_id = ?1 AND so.spent = 0"
+        ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+        
+        let commitments = stmt.query_map(params![account_id], |row| row.get(0))
+            .map_err(|e| CoreError::Storage(format!("Failed to query commitments: {}", e)))?;
+        
+        commitments.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| CoreError::Storage(format!("Failed to collect commitments: {}", e)))
+    }
+    
+    /// Get the commitment openings of an account's unspent outputs
+    pub fn get_unspent_output_openings(&self, account_id: i64) -> Result<Vec<StoredCommitmentProof>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT cp.id, cp.account_id, cp.output_id, cp.commitment, cp.value, cp.blinding, cp.range_proof,
+                    cp.range_proof_compressed
+             FROM commitment_proofs cp
+             JOIN stealth_outputs so ON so.id = cp.output_id
+             WHERE cp.account_id = ?1 AND so.spent = 0"
+        ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+        
+        let openings = stmt.query_map(params![account_id], |row| self.commitment_proof_from_row(row))
+            .map_err(|e| CoreError::Storage(format!("Failed to query commitment openings: {}", e)))?;
+        
+        openings.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| CoreError::Storage(format!("Failed to collect commitment openings: {}", e)))
+    }
+    
+    /// Get every commitment opening of an account, spent outputs included
+    pub fn get_commitment_proofs(&self, account_id: i64) -> Result<Vec<StoredCommitmentProof>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, account_id, output_id, commitment, value, blinding, range_proof, range_proof_compressed
+             FROM commitment_proofs
+             WHERE account_id = ?1
+             ORDER BY id"
+        ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+        
+        let proofs = stmt.query_map(params![account_id], |row| self.commitment_proof_from_row(row))
+            .map_err(|e| CoreError::Storage(format!("Failed to query commitment proofs: {}", e)))?;
+        
+        proofs.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| CoreError::Storage(format!("Failed to collect commitment proofs: {}", e)))
+    }
+    
+    /// Map a `commitment_proofs` row to a `StoredCommitmentProof`
+    fn commitment_proof_from_row(&self, row: &rusqlite::Row) -> rusqlite::Result<StoredCommitmentProof> {
+        let blinding: Vec<u8> = row.get(5)?;
+        let range_proof: Option<Vec<u8>> = row.get(6)?;
+        let compressed: bool = row.get(7)?;
+        
+        Ok(StoredCommitmentProof {
+            id: row.get(0)?,
+            account_id: row.get(1)?,
+            output_id: row.get(2)?,
+            commitment: row.get(3)?,
+            value: row.get(4)?,
+            blinding: self.decrypt_field("blinding", 5, &blinding)?,
+            range_proof: range_proof
+                .map(|stored| decode_range_proof(stored, compressed))
+                .transpose()
+                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(6, rusqlite::types::Type::Blob, Box::new(e)))?,
+        })
+    }
+    
+    /// Overwrite the stored balance commitment for an account
+    pub fn set_balance_commitment(&self, account_index: u32, commitment: &[u8]) -> Result<()> {
+        let updated = self.conn.execute(
+            "UPDATE accounts SET balance_commitment = ?2 WHERE account_index = ?1",
+            params![account_index, commitment],
+        ).map_err(|e| CoreError::Storage(format!("Failed to set balance commitment: {}", e)))?;
+        
+        if updated == 0 {
+            return Err(CoreError::InvalidParameter("Account not found".into()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Get the stored balance commitment for an account
+    pub fn get_balance_commitment(&self, account_index: u32) -> Result<Option<Vec<u8>>> {
+        let result = self.conn.query_row(
+            "SELECT balance_commitment FROM accounts WHERE account_index = ?1",
+            params![account_index],
+            |row| row.get(0),
+        );
+        
+        match result {
+            Ok(commitment) => Ok(commitment),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(CoreError::Storage(format!("Failed to get balance commitment: {}", e))),
+        }
+    }
+    
+    /// Set or, with `None`, clear an account's private note
+    pub fn set_account_note(&self, account_index: u32, note: Option<&str>) -> Result<()> {
+        let encrypted = note
+            .map(|note| self.field_key.encrypt("note", note.as_bytes()))
+            .transpose()?;
+        
+        let updated = self.conn.execute(
+            "UPDATE accounts SET note = ?2 WHERE account_index = ?1",
+            params![account_index, encrypted],
+        ).map_err(|e| CoreError::Storage(format!("Failed to set account note: {}", e)))?;
+        
+        if updated == 0 {
+            return Err(CoreError::InvalidParameter("Account not found".into()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Get an account's private note, if it has one
+    pub fn get_account_note(&self, account_index: u32) -> Result<Option<String>> {
+        let result = self.conn.query_row(
+            "SELECT note FROM accounts WHERE account_index = ?1",
+            params![account_index],
+            |row| {
+                let stored: Option<Vec<u8>> = row.get(0)?;
+                stored.map(|stored| self.decrypt_field("note", 0, &stored)).transpose()
+            },
+        );
+        
+        let plaintext = match result {
+            Ok(plaintext) => plaintext,
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(CoreError::Storage(format!("Failed to get account note: {}", e))),
+        };
+        
+        plaintext
+            .map(|bytes| String::from_utf8(bytes)
+                .map_err(|e| CoreError::Serialization(format!("Invalid account note: {}", e))))
+            .transpose()
+    }
+    
+    /// Map a `stealth_outputs` row to a `StealthOutput`
+    fn stealth_output_from_row(&self, row: &rusqlite::Row) -> rusqlite::Result<StealthOutput> {
+        let one_time_private: Vec<u8> = row.get(5)?;
+        
+        Ok(StealthOutput {
+            id: row.get(0)?,
+            tx_hash: row.get(1)?,
+            account_id: row.get(2)?,
+            ephemeral_public: row.get(3)?,
+            one_time_public: row.get(4)?,
+            one_time_private: self.decrypt_field("one_time_private", 5, &one_time_private)?,
+            amount: row.get(6)?,
+            spent: row.get(7)?,
+            is_change: row.get(8)?,
+            coinbase_height: row.get(9)?,
+            output_index: row.get(10)?,
+        })
+    }
+    
+    /// Decrypt a secret column inside a row mapper
+    fn decrypt_field(&self, column: &str, index: usize, stored: &[u8]) -> rusqlite::Result<Vec<u8>> {
+        warn_if_plaintext();
+        self.field_key.decrypt(column, stored).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Blob, Box::new(e))
+        })
+    }
+    
+    /// Encrypt secret columns written before field-level encryption
+    ///
+    /// Skipped while the field key is locked. Sets `fields_encrypted` once
+    /// done, after which unencrypted values are rejected on read.
+    fn encrypt_legacy_fields(&self) -> Result<()> {
+        if !self.field_key.is_unlocked() {
+            return Ok(());
+        }
+        
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        for (table, column) in FIELD_ENCRYPTED_COLUMNS {
+            let legacy: Vec<(i64, Vec<u8>)> = {
+                let mut stmt = sql_tx.prepare(&format!(
+                    "SELECT id, {column} FROM {table} WHERE length({column}) <= ?1"
+                )).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+                
+                let rows = stmt.query_map(params![LEGACY_PLAINTEXT_MAX as i64], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .map_err(|e| CoreError::Storage(format!("Failed to query legacy fields: {}", e)))?;
+                rows.collect::<std::result::Result<_, _>>()
+                    .map_err(|e| CoreError::Storage(format!("Failed to collect legacy fields: {}", e)))?
+            };
+            
+            for (id, mut plaintext) in legacy {
+                let encrypted = self.field_key.encrypt(column, &plaintext);
+                plaintext.zeroize();
+                sql_tx.execute(
+                    &format!("UPDATE {table} SET {column} = ?2 WHERE id = ?1"),
+                    params![id, encrypted?],
+                ).map_err(|e| CoreError::Storage(format!("Failed to encrypt legacy field: {}", e)))?;
+            }
+        }
+        
+        sql_tx.execute(
+            "INSERT OR REPLACE INTO wallet_meta (key, value) VALUES (?1, '1')",
+            params![MetadataKey::FieldsEncrypted.as_str()],
+        ).map_err(|e| CoreError::Storage(format!("Failed to set metadata: {}", e)))?;
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit field encryption: {}", e)))
+    }
+    
+    /// Copy every row of a plaintext development database into this one
+    ///
+    /// Meant for a freshly created database. Metadata is copied as-is,
+    /// including a wrapped field key, so `password` must be the one the
+    /// source wallet used; secret columns stored unencrypted are encrypted
+    /// once copied. Columns missing from the source keep their defaults.
+    /// Fails unless every data table ends with the source's row count.
+    pub fn import_plaintext<P: AsRef<Path>>(&mut self, plaintext_path: P, password: &str) -> Result<()> {
+        let source = Connection::open_with_flags(
+            plaintext_path.as_ref(),
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        ).map_err(|e| CoreError::Storage(format!("Failed to open plaintext database: {}", e)))?;
+        
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        let mut source_counts = Vec::new();
+        for table in IMPORT_TABLES {
+            let source_columns = table_columns(&source, table)?;
+            if source_columns.is_empty() {
+                continue;
+            }
+            let columns: Vec<String> = table_columns(&sql_tx, table)?
+                .into_iter()
+                .filter(|c| source_columns.contains(c))
+                .collect();
+            
+            let list = columns.join(", ");
+            let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+            let mut select = source.prepare(&format!("SELECT {list} FROM {table}"))
+                .map_err(|e| CoreError::Storage(format!("Failed to read {}: {}", table, e)))?;
+            let mut insert = sql_tx.prepare(&format!(
+                "INSERT OR REPLACE INTO {table} ({list}) VALUES ({})", placeholders.join(", ")
+            )).map_err(|e| CoreError::Storage(format!("Failed to prepare insert: {}", e)))?;
+            
+            let mut rows = select.query([])
+                .map_err(|e| CoreError::Storage(format!("Failed to read {}: {}", table, e)))?;
+            let mut copied = 0i64;
+            while let Some(row) = rows.next()
+                .map_err(|e| CoreError::Storage(format!("Failed to read {}: {}", table, e)))?
+            {
+                let values = (0..columns.len())
+                    .map(|i| row.get::<_, rusqlite::types::Value>(i))
+                    .collect::<rusqlite::Result<Vec<_>>>()
+                    .map_err(|e| CoreError::Storage(format!("Failed to read {}: {}", table, e)))?;
+                insert.execute(rusqlite::params_from_iter(values))
+                    .map_err(|e| CoreError::Storage(format!("Failed to copy {} row: {}", table, e)))?;
+                copied += 1;
+            }
+            source_counts.push((table, copied));
+        }
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit import: {}", e)))?;
+        
+        // The destination keeps its own metadata, so only data tables must match exactly
+        for (table, expected) in source_counts.into_iter().filter(|(t, _)| *t != "wallet_meta") {
+            let actual: i64 = self.conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| row.get(0))
+                .map_err(|e| CoreError::Storage(format!("Failed to count {}: {}", table, e)))?;
+            if actual != expected {
+                return Err(CoreError::Storage(format!(
+                    "Import of {} copied {} of {} rows", table, actual, expected
+                )));
+            }
+        }
+        
+        if !self.unlock_field_key(password)? {
+            return Err(CoreError::Crypto("Password does not unlock the plaintext wallet".into()));
+        }
+        self.encrypt_legacy_fields()
+    }
+    
+    /// Whether the database file itself is encrypted
+    ///
+    /// False unless built with the `sqlcipher` feature. Recorded as the
+    /// `encryption` metadata value each time the database is opened.
+    pub fn is_encrypted(&self) -> bool {
+        cfg!(feature = "sqlcipher")
+    }
+    
+    /// Re-wrap the field key under a new wallet password
+    ///
+    /// The new wrapping, the new password's `verifier` entries and a
+    /// `password_changed` audit entry are written in one transaction.
+    pub(crate) fn rewrap_field_key(&self, new_password: &str, verifier: &[(MetadataKey, &str)]) -> Result<()> {
+        let (salt, wrapped) = self.field_key.seal(new_password)?;
+        let timestamp = self.unix_now()?;
+        
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        let wrapping = [(MetadataKey::FieldKeySalt, salt.as_str()), (MetadataKey::FieldKey, wrapped.as_str())];
+        for (key, value) in wrapping.iter().chain(verifier) {
+            sql_tx.execute(
+                "INSERT OR REPLACE INTO wallet_meta (key, value) VALUES (?1, ?2)",
+                params![key.as_str(), value],
+            ).map_err(|e| CoreError::Storage(format!("Failed to set metadata: {}", e)))?;
+        }
+        append_audit(&sql_tx, &self.field_key, timestamp, AuditOperation::PasswordChanged, "")?;
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit password change: {}", e)))
+    }
+    
+    /// Re-encrypt the database file under a new wallet password
+    ///
+    /// A no-op without the `sqlcipher` feature, where the file is plain.
+    #[cfg_attr(not(feature = "sqlcipher"), allow(unused_variables))]
+    pub(crate) fn rekey(&self, new_password: &str) -> Result<()> {
+        #[cfg(feature = "sqlcipher")]
+        self.conn.pragma_update(None, "rekey", new_password)
+            .map_err(|e| CoreError::Storage(format!("Failed to rekey database: {}", e)))?;
+        
+        Ok(())
+    }
+    
+    /// Current wrong-password counters
+    ///
+    /// Kept in metadata, or under `sqlcipher` in a file beside the
+    /// database, since a wrong key cannot open the database to update them.
+    pub(crate) fn lockout(&self) -> Result<Lockout> {
+        if self.is_encrypted() {
+            return Self::read_lockout(Path::new(&self.db_path));
+        }
+        
+        let number = |key: MetadataKey| -> Result<i64> {
+            match self.get_meta(key)? {
+                Some(value) => value.parse()
+                    .map_err(|_| CoreError::Storage(format!("Corrupt {} value", key.as_str()))),
+                None => Ok(0),
+            }
+        };
+        Ok(Lockout {
+            failed_attempts: u32::try_from(number(MetadataKey::FailedAttempts)?)
+                .map_err(|_| CoreError::Storage("Corrupt failed_attempts value".into()))?,
+            locked_until: number(MetadataKey::LockedUntil)?,
+        })
+    }
+    
+    /// Store the wrong-password counters where `lockout` reads them
+    pub(crate) fn set_lockout(&self, lockout: &Lockout) -> Result<()> {
+        if self.is_encrypted() {
+            return Self::write_lockout(Path::new(&self.db_path), lockout);
+        }
+        
+        self.set_metas(&[
+            (MetadataKey::FailedAttempts, &lockout.failed_attempts.to_string()),
+            (MetadataKey::LockedUntil, &lockout.locked_until.to_string()),
+        ])
+    }
+    
+    /// Read the lockout file of the database at `db_path`; zero if absent
+    pub(crate) fn read_lockout(db_path: &Path) -> Result<Lockout> {
+        let contents = match std::fs::read_to_string(lockout_path(db_path)) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Lockout::default()),
+            Err(e) => return Err(CoreError::Storage(format!("Failed to read lockout file: {}", e))),
+        };
+        
+        let corrupt = || CoreError::Storage("Corrupt lockout file".into());
+        let (failed_attempts, locked_until) = contents.trim().split_once(' ').ok_or_else(corrupt)?;
+        Ok(Lockout {
+            failed_attempts: failed_attempts.parse().map_err(|_| corrupt())?,
+            locked_until: locked_until.parse().map_err(|_| corrupt())?,
+        })
+    }
+    
+    /// Write the lockout file of the database at `db_path`
+    pub(crate) fn write_lockout(db_path: &Path, lockout: &Lockout) -> Result<()> {
+        std::fs::write(
+            lockout_path(db_path),
+            format!("{} {}\n", lockout.failed_attempts, lockout.locked_until),
+        ).map_err(|e| CoreError::Storage(format!("Failed to write lockout file: {}", e)))
+    }
+    
+    /// Whether secret columns can currently be read
+    pub fn is_unlocked(&self) -> bool {
+        self.field_key.is_unlocked()
+    }
+    
+    /// Forget the field key until the next unlock
+    pub(crate) fn lock_field_key(&mut self) {
+        self.field_key = FieldKey::default();
+    }
+    
+    /// Unwrap the field key with the wallet password; false if it is wrong
+    pub(crate) fn unlock_field_key(&mut self, password: &str) -> Result<bool> {
+        self.field_key = FieldKey::unlock(self, password)?;
+        Ok(self.field_key.is_unlocked())
+    }
+    
+    /// Store a second wrapping of the field key under `pin`
+    pub(crate) fn wrap_field_key_with_pin(&self, pin: &str) -> Result<()> {
+        let (salt, wrapped) = self.field_key.seal(pin)?;
+        self.set_meta(MetadataKey::PinSalt, &salt)?;
+        self.set_meta(MetadataKey::PinWrappedKey, &wrapped)
+    }
+    
+    /// Unwrap the field key with `pin`
+    ///
+    /// Returns false if the PIN is wrong or no PIN is set.
+    pub(crate) fn unlock_field_key_with_pin(&mut self, pin: &str) -> Result<bool> {
+        let (Some(salt), Some(wrapped)) = (
+            self.get_meta(MetadataKey::PinSalt)?,
+            self.get_meta(MetadataKey::PinWrappedKey)?,
+        ) else {
+            return Ok(false);
+        };
+        
+        let key = FieldKey::open(&salt, &wrapped, pin)?;
+        if !key.is_unlocked() {
+            return Ok(false);
+        }
+        
+        self.field_key = key;
+        Ok(true)
+    }
+    
+    /// Remove the PIN wrapping, leaving only the password
+    pub(crate) fn clear_pin(&self) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM wallet_meta WHERE key IN (?1, ?2, ?3)",
+            params![
+                MetadataKey::PinSalt.as_str(),
+                MetadataKey::PinWrappedKey.as_str(),
+                MetadataKey::PinFailedAttempts.as_str(),
+            ],
+        ).map_err(|e| CoreError::Storage(format!("Failed to clear PIN: {}", e)))?;
+        
+        Ok(())
+    }
+    
+    /// Whether a PIN wrapping of the field key exists
+    pub(crate) fn has_pin(&self) -> Result<bool> {
+        Ok(self.get_meta(MetadataKey::PinWrappedKey)?.is_some())
+    }
+    
+    /// Mark stealth output as spent
+    pub fn mark_stealth_output_spent(&self, output_id: i64) -> Result<()> {
+        self.mark_output_spent(output_id, None)
+    }
+    
+    /// Mark stealth output as spent by the transaction `spending_tx_hash`
+    pub fn mark_stealth_output_spent_by(&self, output_id: i64, spending_tx_hash: &str) -> Result<()> {
+        self.mark_output_spent(output_id, Some(spending_tx_hash))
+    }
+    
+    /// Set `spent` (and `spent_by`, when known) and log the spend
+    fn mark_output_spent(&self, output_id: i64, spending_tx_hash: Option<&str>) -> Result<()> {
+        let timestamp = self.unix_now()?;
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        sql_tx.execute(
+            "UPDATE stealth_outputs SET spent = 1, spent_by = COALESCE(?2, spent_by) WHERE id = ?1",
+            params![output_id, spending_tx_hash],
         ).map_err(|e| CoreError::Storage(format!("Failed to mark output spent: {}", e)))?;
+        append_audit(&sql_tx, &self.field_key, timestamp, AuditOperation::OutputSpent, &output_id.to_string())?;
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit spend: {}", e)))
+    }
+    
+    /// Every audit log entry, oldest first
+    pub fn get_audit_log(&self) -> Result<Vec<AuditEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, timestamp, operation, detail, hash FROM audit_log ORDER BY id"
+        ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+        
+        let entries = stmt.query_map([], |row| {
+            Ok(AuditEntry {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                operation: row.get(2)?,
+                detail: row.get(3)?,
+                hash: row.get(4)?,
+            })
+        }).map_err(|e| CoreError::Storage(format!("Failed to query audit log: {}", e)))?;
+        
+        entries.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| CoreError::Storage(format!("Failed to collect audit log: {}", e)))
+    }
+    
+    /// Recompute the audit MAC chain; false if any entry was altered,
+    /// removed or reordered. Requires an unlocked field key.
+    pub fn verify_audit_chain(&self) -> Result<bool> {
+        let mut key = self.field_key.audit_key()?;
+        let mut previous = [0u8; 32];
+        let mut intact = true;
+        for entry in self.get_audit_log()? {
+            let hash = audit_hash(&key, &previous, entry.timestamp, &entry.operation, &entry.detail);
+            if entry.hash != hash {
+                intact = false;
+                break;
+            }
+            previous = hash;
+        }
+        key.zeroize();
+        Ok(intact)
+    }
+    
+    /// List rows whose `account_id` has no matching account
+    ///
+    /// Such rows are left by an account deleted with foreign key checks
+    /// off, e.g. by an external tool, and drop out of every per-account
+    /// query.
+    pub fn find_orphans(&self) -> Result<OrphanReport> {
+        let mut rows = Vec::new();
+        
+        for table in ACCOUNT_OWNED_TABLES {
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT rowid FROM {table} WHERE account_id NOT IN (SELECT id FROM accounts) ORDER BY rowid"
+            )).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+            
+            let ids = stmt.query_map([], |row| row.get::<_, i64>(0))
+                .map_err(|e| CoreError::Storage(format!("Failed to query orphans: {}", e)))?;
+            for id in ids {
+                let id = id.map_err(|e| CoreError::Storage(format!("Failed to read orphan: {}", e)))?;
+                rows.push((table.to_string(), id));
+            }
+        }
+        
+        Ok(OrphanReport { rows })
+    }
+    
+    /// Delete every row `find_orphans` reports, in one transaction
+    ///
+    /// Returns the number of rows deleted.
+    pub fn delete_orphans(&self) -> Result<usize> {
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        let mut deleted = 0;
+        for table in ACCOUNT_OWNED_TABLES {
+            deleted += sql_tx.execute(
+                &format!("DELETE FROM {table} WHERE account_id NOT IN (SELECT id FROM accounts)"),
+                [],
+            ).map_err(|e| CoreError::Storage(format!("Failed to delete orphans: {}", e)))?;
+        }
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit orphan cleanup: {}", e)))?;
+        
+        Ok(deleted)
+    }
+    
+    /// Delete spent outputs created before `before_timestamp`
+    ///
+    /// Unspent outputs are kept regardless of age, as are outputs whose
+    /// spending transaction is still pending. Commitment openings of the
+    /// deleted outputs go with them. Returns the number of outputs deleted.
+    pub fn prune_spent_outputs(&self, before_timestamp: i64) -> Result<usize> {
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        sql_tx.execute(
+            "CREATE TEMP TABLE prunable_outputs AS
+                SELECT so.id FROM stealth_outputs so
+                WHERE so.spent = 1 AND so.created_at < ?1
+                  AND NOT EXISTS (
+                      SELECT 1 FROM transactions t
+                      WHERE t.tx_hash = so.spent_by AND t.status = 'pending'
+                  )",
+            params![before_timestamp],
+        ).map_err(|e| CoreError::Storage(format!("Failed to select prunable outputs: {}", e)))?;
+        
+        sql_tx.execute(
+            "DELETE FROM commitment_proofs WHERE output_id IN (SELECT id FROM prunable_outputs)",
+            [],
+        ).map_err(|e| CoreError::Storage(format!("Failed to delete pruned openings: {}", e)))?;
+        
+        let removed = sql_tx.execute(
+            "DELETE FROM stealth_outputs WHERE id IN (SELECT id FROM prunable_outputs)",
+            [],
+        ).map_err(|e| CoreError::Storage(format!("Failed to prune spent outputs: {}", e)))?;
+        
+        sql_tx.execute_batch("DROP TABLE prunable_outputs;")
+            .map_err(|e| CoreError::Storage(format!("Failed to prune spent outputs: {}", e)))?;
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit prune: {}", e)))?;
+        
+        Ok(removed)
+    }
+    
+    /// Load chain sync progress
+    pub fn load_sync_state(&self) -> Result<WalletSyncState> {
+        // last_scanned_height, session_start, session_target, session_next
+        type SyncStateRow = (Option<i64>, Option<i64>, Option<i64>, Option<i64>);
+        
+        let row: Option<SyncStateRow> = match self.conn.query_row(
+            "SELECT last_scanned_height, session_start, session_target, session_next
+             FROM sync_state WHERE id = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        ) {
+            Ok(row) => Some(row),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(CoreError::Storage(format!("Failed to load sync state: {}", e))),
+        };
+        
+        let mut stmt = self.conn.prepare(
+            "SELECT start_height, end_height FROM sync_gaps ORDER BY start_height"
+        ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+        let gaps = stmt.query_map([], |row| {
+            Ok(SyncGap {
+                start: row.get::<_, i64>(0)? as u64,
+                end: row.get::<_, i64>(1)? as u64,
+            })
+        })
+            .map_err(|e| CoreError::Storage(format!("Failed to query sync gaps: {}", e)))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| CoreError::Storage(format!("Failed to collect sync gaps: {}", e)))?;
+        
+        let mut stmt = self.conn.prepare(
+            "SELECT account_id, last_scanned_height, in_session FROM sync_accounts ORDER BY account_id"
+        ).map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+        let accounts = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Option<i64>>(1)?, row.get::<_, bool>(2)?))
+        })
+            .map_err(|e| CoreError::Storage(format!("Failed to query sync accounts: {}", e)))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| CoreError::Storage(format!("Failed to collect sync accounts: {}", e)))?;
+        
+        let (last_scanned, start, target, next) = row.unwrap_or_default();
+        let session = match (start, target, next) {
+            (Some(start), Some(target), Some(next)) => Some(SyncSession {
+                start_height: start as u64,
+                target_height: target as u64,
+                next_height: next as u64,
+                accounts: accounts.iter().filter(|a| a.2).map(|a| a.0).collect(),
+            }),
+            _ => None,
+        };
+        
+        Ok(WalletSyncState {
+            last_scanned_height: last_scanned.map(|h| h as u64),
+            gaps,
+            account_heights: accounts.iter()
+                .filter_map(|(id, height, _)| height.map(|h| (*id, h as u64)))
+                .collect(),
+            session,
+        })
+    }
+    
+    /// Replace the stored chain sync progress
+    pub fn save_sync_state(&self, state: &WalletSyncState) -> Result<()> {
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        Self::write_sync_state(&sql_tx, state)?;
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit sync state: {}", e)))
+    }
+    
+    /// Store outputs found during sync together with the new sync progress
+    ///
+    /// Either both are written or neither is. Returns the output ids.
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        name = "db_ingest_sync_batch",
+        skip_all,
+        fields(outputs = outputs.len()),
+    ))]
+    pub fn ingest_sync_batch(&self, state: &WalletSyncState, outputs: &[StealthOutput]) -> Result<Vec<i64>> {
+        let timestamp = self.unix_now()?;
+        
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        let ids = outputs.iter()
+            .map(|output| self.insert_stealth_output(&sql_tx, output, timestamp))
+            .collect::<Result<Vec<_>>>()?;
+        Self::write_sync_state(&sql_tx, state)?;
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit sync batch: {}", e)))?;
+        
+        Ok(ids)
+    }
+    
+    /// Overwrite the sync tables using the given connection (or open transaction)
+    fn write_sync_state(conn: &Connection, state: &WalletSyncState) -> Result<()> {
+        let session = state.session.as_ref();
+        
+        conn.execute(
+            "INSERT OR REPLACE INTO sync_state (
+                id, last_scanned_height, session_start, session_target, session_next
+            ) VALUES (1, ?1, ?2, ?3, ?4)",
+            params![
+                state.last_scanned_height.map(|h| h as i64),
+                session.map(|s| s.start_height as i64),
+                session.map(|s| s.target_height as i64),
+                session.map(|s| s.next_height as i64),
+            ],
+        ).map_err(|e| CoreError::Storage(format!("Failed to save sync state: {}", e)))?;
+        
+        conn.execute_batch("DELETE FROM sync_gaps; DELETE FROM sync_accounts;")
+            .map_err(|e| CoreError::Storage(format!("Failed to save sync state: {}", e)))?;
+        
+        for gap in &state.gaps {
+            conn.execute(
+                "INSERT INTO sync_gaps (start_height, end_height) VALUES (?1, ?2)",
+                params![gap.start as i64, gap.end as i64],
+            ).map_err(|e| CoreError::Storage(format!("Failed to save sync gap: {}", e)))?;
+        }
+        
+        let mut account_ids: Vec<i64> = state.account_heights.keys().copied().collect();
+        account_ids.extend(session.iter().flat_map(|s| s.accounts.iter().copied()));
+        account_ids.sort_unstable();
+        account_ids.dedup();
+        
+        for account_id in account_ids {
+            let in_session = session.is_some_and(|s| s.accounts.contains(&account_id));
+            conn.execute(
+                "INSERT INTO sync_accounts (account_id, last_scanned_height, in_session)
+                 VALUES (?1, ?2, ?3)",
+                params![
+                    account_id,
+                    state.account_heights.get(&account_id).map(|h| *h as i64),
+                    in_session,
+                ],
+            ).map_err(|e| CoreError::Storage(format!("Failed to save sync account: {}", e)))?;
+        }
         
         Ok(())
     }
     
+    /// Seal this database, and optionally a decoy, into a hidden volume
+    ///
+    /// `password` opens this database and `decoy.password` opens the decoy;
+    /// nothing in the file shows which slot is in use. Use each wallet's own
+    /// password, since the same password unwraps its field key. See the
+    /// `hidden_volume` module for the limitations of this scheme.
+    pub fn export_hidden_volume<P: AsRef<Path>>(
+        &self,
+        volume_path: P,
+        password: &str,
+        decoy: Option<&DecoyConfig>,
+    ) -> Result<()> {
+        let mut real = self.snapshot()?;
+        let mut payloads = vec![(password, real.as_slice())];
+        
+        let mut decoy_bytes = match decoy {
+            Some(decoy) => decoy.db.snapshot()?,
+            None => Vec::new(),
+        };
+        if let Some(decoy) = decoy {
+            payloads.push((decoy.password, decoy_bytes.as_slice()));
+        }
+        
+        let volume = hidden_volume::seal(&payloads);
+        real.zeroize();
+        decoy_bytes.zeroize();
+        
+        std::fs::write(volume_path, volume?)
+            .map_err(|e| CoreError::Storage(format!("Failed to write hidden volume: {}", e)))
+    }
+    
+    /// Open whichever database in a hidden volume `password` unlocks
+    ///
+    /// The plaintext database is written to `working_path`; delete it when
+    /// done, since a leftover copy defeats deniability.
+    pub fn open_hidden_volume<P: AsRef<Path>, W: AsRef<Path>>(
+        volume_path: P,
+        password: &str,
+        working_path: W,
+    ) -> Result<Self> {
+        let volume = std::fs::read(volume_path)
+            .map_err(|e| CoreError::Storage(format!("Failed to read hidden volume: {}", e)))?;
+        let mut db_bytes = hidden_volume::open(&volume, password)?;
+        
+        let written = std::fs::write(working_path.as_ref(), &db_bytes);
+        db_bytes.zeroize();
+        written.map_err(|e| CoreError::Storage(format!("Failed to write working database: {}", e)))?;
+        
+        Self::new(working_path, password)
+    }
+    
+    /// Flush the WAL into the main database file
+    fn checkpoint(&self) -> Result<()> {
+        self.conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")
+            .map_err(|e| CoreError::Storage(format!("Checkpoint failed: {}", e)))
+    }
+    
+    /// Read the complete database file after a checkpoint
+    fn snapshot(&self) -> Result<Vec<u8>> {
+        self.checkpoint()?;
+        std::fs::read(&self.db_path)
+            .map_err(|e| CoreError::Storage(format!("Failed to read database: {}", e)))
+    }
+    
+    /// SHA-256 over every row of the wallet's content tables
+    ///
+    /// Rows are hashed in rowid order with each value tagged by type, so
+    /// two databases holding the same rows hash equally whatever their page
+    /// layout. Metadata and sync progress are left out, as they change on
+    /// every open.
+    pub fn content_hash(&self) -> Result<[u8; 32]> {
+        use rusqlite::types::ValueRef;
+        use sha2::{Digest, Sha256};
+        
+        let mut hasher = Sha256::new();
+        hasher.update(b"Zetaris-Content-v1");
+        
+        for table in CONTENT_TABLES {
+            hasher.update((table.len() as u32).to_be_bytes());
+            hasher.update(table.as_bytes());
+            
+            let mut stmt = self.conn.prepare(&format!("SELECT * FROM {table} ORDER BY rowid"))
+                .map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+            let column_count = stmt.column_count();
+            let mut rows = stmt.query([])
+                .map_err(|e| CoreError::Storage(format!("Failed to read {}: {}", table, e)))?;
+            
+            while let Some(row) = rows.next().map_err(|e| CoreError::Storage(format!("Failed to read {}: {}", table, e)))? {
+                hasher.update([b'R']);
+                for i in 0..column_count {
+                    let value = row.get_ref(i)
+                        .map_err(|e| CoreError::Storage(format!("Failed to read {}: {}", table, e)))?;
+                    let (tag, bytes) = match value {
+                        ValueRef::Null => (0u8, Vec::new()),
+                        ValueRef::Integer(n) => (1, n.to_be_bytes().to_vec()),
+                        ValueRef::Real(x) => (2, x.to_be_bytes().to_vec()),
+                        ValueRef::Text(text) => (3, text.to_vec()),
+                        ValueRef::Blob(blob) => (4, blob.to_vec()),
+                    };
+                    hasher.update([tag]);
+                    hasher.update((bytes.len() as u32).to_be_bytes());
+                    hasher.update(&bytes);
+                }
+            }
+        }
+        
+        Ok(hasher.finalize().into())
+    }
+    
     /// Backup database to file
+    ///
+    /// Checkpoints the WAL into the main file first, so the copy holds
+    /// every committed write without its `-wal` sidecar.
     pub fn backup<P: AsRef<Path>>(&self, backup_path: P) -> Result<()> {
         use std::fs;
         
-        // Close connection first
-        drop(&self.conn);
+        // Flush the WAL into the main database file first
+        self.checkpoint()?;
         
         // Copy database file
         fs::copy(&self.db_path, backup_path)
@@ -390,6 +2601,163 @@ impl EncryptedDb {
         Ok(())
     }
     
+    /// Replace the database with a compacted copy under a new field key
+    ///
+    /// The copy is written next to the database, given a fresh field key
+    /// wrapped under `new_password` (with SQLCipher, also a new file key),
+    /// passed to `prepare` for further changes and reopened with
+    /// `new_password` as a check. Only then is it swapped in: the original
+    /// is moved aside, and moved back and reopened with `old_password` if
+    /// the swap fails, so a failure leaves the original in use. The PIN is
+    /// cleared, as it wraps the old key. Requires an unlocked field key.
+    pub(crate) fn compact_rekeyed<F>(&mut self, old_password: &str, new_password: &str, prepare: F) -> Result<()>
+    where
+        F: FnOnce(&EncryptedDb) -> Result<()>,
+    {
+        if !self.field_key.is_unlocked() {
+            return Err(CoreError::Crypto("Field key is locked: unlock the wallet before re-keying".into()));
+        }
+        
+        let compact_path = format!("{}.compact", self.db_path);
+        let _ = std::fs::remove_file(&compact_path);
+        
+        let prepared = (|| {
+            self.export_compacted(&compact_path, new_password)?;
+            
+            let mut compacted = EncryptedDb::with_config(&compact_path, new_password, self.clock.clone(), self.config)?;
+            compacted.field_key = self.field_key.clone();
+            compacted.rotate_field_key(new_password)?;
+            prepare(&compacted)?;
+            drop(compacted);
+            
+            let reopened = EncryptedDb::with_config(&compact_path, new_password, self.clock.clone(), self.config)?;
+            if !reopened.is_unlocked() {
+                return Err(CoreError::Storage("Compacted database does not open with the new password".into()));
+            }
+            Ok(reopened.field_key.clone())
+        })();
+        let field_key = match prepared {
+            Ok(field_key) => field_key,
+            Err(e) => {
+                let _ = std::fs::remove_file(&compact_path);
+                return Err(e);
+            }
+        };
+        
+        // Close the original so its WAL is folded in and removed before the swap
+        let placeholder = Connection::open_in_memory()
+            .map_err(|e| CoreError::Storage(format!("Failed to open database: {}", e)))?;
+        if let Err((conn, e)) = std::mem::replace(&mut self.conn, placeholder).close() {
+            self.conn = conn;
+            let _ = std::fs::remove_file(&compact_path);
+            return Err(CoreError::Storage(format!("Failed to close database: {}", e)));
+        }
+        
+        let original_path = format!("{}.original", self.db_path);
+        let swapped = (|| {
+            std::fs::rename(&self.db_path, &original_path)
+                .map_err(|e| CoreError::Storage(format!("Failed to move database aside: {}", e)))?;
+            if let Err(e) = std::fs::rename(&compact_path, &self.db_path) {
+                let _ = std::fs::rename(&original_path, &self.db_path);
+                return Err(CoreError::Storage(format!("Failed to replace database: {}", e)));
+            }
+            match Self::open_connection(&self.db_path, new_password, &self.config) {
+                Ok(conn) => Ok(conn),
+                Err(e) => {
+                    let _ = std::fs::rename(&original_path, &self.db_path);
+                    Err(CoreError::Storage(format!("Failed to open database: {}", e)))
+                }
+            }
+        })();
+        
+        match swapped {
+            Ok(conn) => {
+                self.conn = conn;
+                self.field_key = field_key;
+                let _ = std::fs::remove_file(&original_path);
+                Ok(())
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(&compact_path);
+                self.conn = Self::open_connection(&self.db_path, old_password, &self.config)
+                    .map_err(|reopen| CoreError::Storage(format!("{}; reopening the original failed: {}", e, reopen)))?;
+                Err(e)
+            }
+        }
+    }
+    
+    /// Write a compacted copy of the database to `path`
+    #[cfg(not(feature = "sqlcipher"))]
+    fn export_compacted(&self, path: &str, _new_password: &str) -> Result<()> {
+        self.conn.execute("VACUUM INTO ?1", params![path])
+            .map_err(|e| CoreError::Storage(format!("Compaction failed: {}", e)))?;
+        Ok(())
+    }
+    
+    /// Write a compacted copy of the database to `path`, keyed by `new_password`
+    #[cfg(feature = "sqlcipher")]
+    fn export_compacted(&self, path: &str, new_password: &str) -> Result<()> {
+        self.conn.execute("ATTACH DATABASE ?1 AS compacted KEY ?2", params![path, new_password])
+            .map_err(|e| CoreError::Storage(format!("Compaction failed: {}", e)))?;
+        let exported = self.conn.query_row("SELECT sqlcipher_export('compacted')", [], |_| Ok(()));
+        self.conn.execute("DETACH DATABASE compacted", [])
+            .map_err(|e| CoreError::Storage(format!("Compaction failed: {}", e)))?;
+        exported.map_err(|e| CoreError::Storage(format!("Compaction failed: {}", e)))
+    }
+    
+    /// Re-encrypt every secret column under a new field key wrapped by
+    /// `password`, and drop the PIN wrapping of the old key
+    fn rotate_field_key(&mut self, password: &str) -> Result<()> {
+        let new_key = FieldKey::generate();
+        
+        let sql_tx = self.conn.unchecked_transaction()
+            .map_err(|e| CoreError::Storage(format!("Failed to begin transaction: {}", e)))?;
+        
+        for (table, column) in FIELD_ENCRYPTED_COLUMNS {
+            let rows: Vec<(i64, Vec<u8>)> = {
+                let mut stmt = sql_tx.prepare(&format!("SELECT id, {column} FROM {table} WHERE {column} IS NOT NULL"))
+                    .map_err(|e| CoreError::Storage(format!("Failed to prepare query: {}", e)))?;
+                let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .map_err(|e| CoreError::Storage(format!("Failed to query secret fields: {}", e)))?;
+                rows.collect::<std::result::Result<_, _>>()
+                    .map_err(|e| CoreError::Storage(format!("Failed to collect secret fields: {}", e)))?
+            };
+            
+            for (id, stored) in rows {
+                let mut plaintext = self.field_key.decrypt(column, &stored)?;
+                let encrypted = new_key.encrypt(column, &plaintext);
+                plaintext.zeroize();
+                sql_tx.execute(
+                    &format!("UPDATE {table} SET {column} = ?2 WHERE id = ?1"),
+                    params![id, encrypted?],
+                ).map_err(|e| CoreError::Storage(format!("Failed to re-encrypt field: {}", e)))?;
+            }
+        }
+        
+        // Re-MAC the audit chain under the new key, but never launder a broken one
+        if !self.verify_audit_chain()? {
+            return Err(CoreError::Storage("Audit log failed verification".into()));
+        }
+        let mut audit_key = new_key.audit_key()?;
+        let mut previous = [0u8; 32];
+        for entry in self.get_audit_log()? {
+            let hash = audit_hash(&audit_key, &previous, entry.timestamp, &entry.operation, &entry.detail);
+            sql_tx.execute(
+                "UPDATE audit_log SET hash = ?2 WHERE id = ?1",
+                params![entry.id, hash.to_vec()],
+            ).map_err(|e| CoreError::Storage(format!("Failed to update audit entry: {}", e)))?;
+            previous = hash;
+        }
+        audit_key.zeroize();
+        
+        sql_tx.commit()
+            .map_err(|e| CoreError::Storage(format!("Failed to commit re-encryption: {}", e)))?;
+        
+        self.field_key = new_key;
+        self.field_key.wrap(self, password)?;
+        self.clear_pin()
+    }
+    
     /// Vacuum database (reclaim space, optimize)
     pub fn vacuum(&self) -> Result<()> {
         self.conn.execute("VACUUM", [])
@@ -399,34 +2767,166 @@ impl EncryptedDb {
     }
 }
 
-/// Stored account data
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StoredAccount {
+/// Stored account data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredAccount {
+    pub id: i64,
+    pub index: u32,
+    pub name: String,
+    pub ethereum_address: String,
+    pub solana_address: String,
+    pub bitcoin_address: String,
+    pub polygon_address: String,
+    pub zcash_address: String,
+}
+
+/// Stored transaction data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredTransaction {
+    pub id: i64,
+    pub tx_hash: String,
+    pub account_id: i64,
+    pub chain: String,
+    pub tx_type: String,
+    /// Non-negative decimal in whole coins, e.g. "1.5" (see `check_amount`)
+    pub amount: String,
+    pub from_address: Option<String>,
+    pub to_address: Option<String>,
+    pub status: String,
+    pub timestamp: i64,
+    pub block_number: Option<i64>,
+    pub gas_used: Option<String>,
+    pub confirmations: Option<u32>,
+    /// Fee paid as an integer in the chain's base unit (wei, satoshi,
+    /// lamport); for EVM chains, gas used times the effective gas price
+    pub fee: Option<String>,
+}
+
+impl StoredTransaction {
+    /// Confirmations at chain height `current_height`, i.e. blocks mined
+    /// after the including one; `None` until the transaction is in a block
+    pub fn confirmations_at(&self, current_height: u64) -> Option<u32> {
+        let block = u64::try_from(self.block_number?).ok()?;
+        let depth = current_height.checked_sub(block)?;
+        Some(u32::try_from(depth).unwrap_or(u32::MAX))
+    }
+    
+    /// Whether the transaction is deep enough to be final on its chain
+    ///
+    /// Uses `CoinType::finality_confirmations`, which needs a block number.
+    /// Chains without a known threshold are final once confirmed.
+    pub fn is_final(&self, current_height: u64) -> bool {
+        if self.status != "confirmed" {
+            return false;
+        }
+        
+        match (CoinType::from_chain(&self.chain), self.confirmations_at(current_height)) {
+            (Some(coin), Some(confirmations)) => confirmations >= coin.finality_confirmations(),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+}
+
+/// Non-secret wallet overview readable without the password
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletSummary {
+    pub wallet_version: Option<String>,
+    pub created_at: Option<String>,
+    pub account_count: u32,
+    /// Timestamp of the most recent transaction, if any
+    pub last_activity: Option<i64>,
+}
+
+/// Rows left behind by a missing account, from `find_orphans`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrphanReport {
+    /// Table name and rowid of each orphaned row
+    pub rows: Vec<(String, i64)>,
+}
+
+impl OrphanReport {
+    /// Whether no orphans were found
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+    
+    /// Number of orphaned rows in `table`
+    pub fn count(&self, table: &str) -> usize {
+        self.rows.iter().filter(|(t, _)| t == table).count()
+    }
+}
+
+impl WipeSecrets for EncryptedDb {
+    fn wipe_secrets(&mut self) {
+        self.lock_field_key();
+    }
+}
+
+/// MAC of an audit entry under the audit key, chained to the previous entry's MAC
+///
+/// Keyed so that rewriting an entry and recomputing the chain needs the
+/// field key, not just write access to the database.
+pub(crate) fn audit_hash(key: &[u8; 32], previous: &[u8; 32], timestamp: i64, operation: &str, detail: &str) -> [u8; 32] {
+    use hmac::{Hmac, Mac};
+    let mut mac = <Hmac<sha2::Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(previous);
+    mac.update(&timestamp.to_be_bytes());
+    mac.update(&(operation.len() as u32).to_be_bytes());
+    mac.update(operation.as_bytes());
+    mac.update(&(detail.len() as u32).to_be_bytes());
+    mac.update(detail.as_bytes());
+    mac.finalize().into_bytes().into()
+}
+
+/// Append an entry to the audit log using the given connection (or open transaction)
+fn append_audit(conn: &Connection, field_key: &FieldKey, timestamp: i64, operation: AuditOperation, detail: &str) -> Result<()> {
+    let result: rusqlite::Result<Vec<u8>> = conn.query_row(
+        "SELECT hash FROM audit_log ORDER BY id DESC LIMIT 1",
+        [],
+        |row| row.get(0),
+    );
+    let previous: [u8; 32] = match result {
+        Ok(hash) => hash.as_slice().try_into()
+            .map_err(|_| CoreError::Storage("Invalid audit hash length".into()))?,
+        Err(rusqlite::Error::QueryReturnedNoRows) => [0u8; 32],
+        Err(e) => return Err(CoreError::Storage(format!("Failed to read audit log: {}", e))),
+    };
+    
+    let mut key = field_key.audit_key()?;
+    let hash = audit_hash(&key, &previous, timestamp, operation.as_str(), detail);
+    key.zeroize();
+    conn.execute(
+        "INSERT INTO audit_log (timestamp, operation, detail, hash) VALUES (?1, ?2, ?3, ?4)",
+        params![timestamp, operation.as_str(), detail, hash.to_vec()],
+    ).map_err(|e| CoreError::Storage(format!("Failed to append audit entry: {}", e)))?;
+    
+    Ok(())
+}
+
+/// One entry of the tamper-evident audit log
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
     pub id: i64,
-    pub index: u32,
-    pub name: String,
-    pub ethereum_address: String,
-    pub solana_address: String,
-    pub bitcoin_address: String,
-    pub polygon_address: String,
-    pub zcash_address: String,
+    pub timestamp: i64,
+    pub operation: String,
+    /// Account index, transaction hash or output id the operation touched
+    pub detail: String,
+    /// HMAC-SHA256 under a subkey of the field key, over the previous
+    /// entry's hash and this entry
+    pub hash: Vec<u8>,
 }
 
-/// Stored transaction data
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StoredTransaction {
+/// Stored commitment opening
+#[derive(Debug, Clone)]
+pub struct StoredCommitmentProof {
     pub id: i64,
-    pub tx_hash: String,
     pub account_id: i64,
-    pub chain: String,
-    pub tx_type: String,
-    pub amount: String,
-    pub from_address: Option<String>,
-    pub to_address: Option<String>,
-    pub status: String,
-    pub timestamp: i64,
-    pub block_number: Option<i64>,
-    pub gas_used: Option<String>,
+    pub output_id: Option<i64>,
+    pub commitment: Vec<u8>,
+    pub value: String,
+    pub blinding: Vec<u8>,
+    pub range_proof: Option<Vec<u8>>,
 }
 
 /// Stealth output data
@@ -440,11 +2940,18 @@ pub struct StealthOutput {
     pub one_time_private: Vec<u8>,
     pub amount: String,
     pub spent: bool,
+    /// Received on our own change subaddress
+    pub is_change: bool,
+    /// Block height of the coinbase transaction that created this output
+    pub coinbase_height: Option<u64>,
+    /// Index within `tx_hash`; `None` for outputs stored before it was recorded
+    pub output_index: Option<u32>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::test_fixtures::{test_account, test_output};
     use tempfile::tempdir;
     
     #[test]
@@ -452,10 +2959,187 @@ mod tests {
         let dir = tempdir().unwrap();
         let db_path = dir.path().join("test.db");
         
-        let db = EncryptedDb::new(&db_path, "test_password_123").unwrap();
+        let _db = EncryptedDb::new(&db_path, "test_password_123").unwrap();
         assert!(db_path.exists());
     }
     
+    #[test]
+    fn test_find_and_delete_orphans() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = EncryptedDb::new(&db_path, "test_password_123").unwrap();
+        
+        let account = |index: u32| StoredAccount {
+            index,
+            name: format!("Account {}", index + 1),
+            ethereum_address: format!("0xeth{}", index),
+            solana_address: format!("Sol{}", index),
+            bitcoin_address: format!("bc1q{}", index),
+            polygon_address: format!("0xpoly{}", index),
+            zcash_address: format!("t1{}", index),
+            ..test_account()
+        };
+        let kept = db.store_account(&account(0)).unwrap();
+        let lost = db.store_account(&account(1)).unwrap();
+        db.take_address_index(kept, 0, 0, 0).unwrap();
+        db.take_address_index(lost, 0, 0, 0).unwrap();
+        assert!(db.find_orphans().unwrap().is_empty());
+        
+        // Remove the account row directly, leaving its dependents behind
+        db.conn.execute_batch("PRAGMA foreign_keys = OFF").unwrap();
+        db.conn.execute("DELETE FROM accounts WHERE id = ?1", params![lost]).unwrap();
+        db.conn.execute_batch("PRAGMA foreign_keys = ON").unwrap();
+        
+        let report = db.find_orphans().unwrap();
+        assert_eq!(report.count("address_counters"), 1);
+        assert_eq!(report.count("account_addresses"), 5);
+        assert_eq!(db.delete_orphans().unwrap(), report.rows.len());
+        assert!(db.find_orphans().unwrap().is_empty());
+        assert_eq!(db.get_account(0).unwrap().unwrap().id, kept);
+    }
+    
+    #[test]
+    fn test_refuses_database_requiring_newer_version() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        
+        let db = EncryptedDb::new(&db_path, "test_password_123").unwrap();
+        assert_eq!(db.get_meta(MetadataKey::MinReaderVersion).unwrap().as_deref(), Some(MIN_READER_VERSION));
+        db.set_meta(MetadataKey::MinReaderVersion, "99.0.0").unwrap();
+        drop(db);
+        
+        match EncryptedDb::new(&db_path, "test_password_123") {
+            Err(CoreError::Storage(msg)) => assert_eq!(msg, "database requires newer version"),
+            other => panic!("expected version error, got {:?}", other.err()),
+        }
+    }
+    
+    #[test]
+    fn test_parse_version_ignores_pre_release_suffix() {
+        assert_eq!(parse_version("1.0.0-rc.1").unwrap(), vec![1, 0, 0]);
+        assert_eq!(parse_version("1.2.3+build.5").unwrap(), vec![1, 2, 3]);
+        assert!(parse_version("1.0.0-rc.1").unwrap() < parse_version("1.0.1").unwrap());
+        assert!(matches!(parse_version("1.x.0"), Err(CoreError::Storage(_))));
+    }
+    
+    #[test]
+    fn test_custom_page_and_cache_size() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let config = StorageConfig {
+            page_size: Some(8192),
+            cache_size: Some(-4000),
+            ..StorageConfig::default()
+        };
+        
+        let pragma = |db: &EncryptedDb, name: &str| -> i64 {
+            db.conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get(0)).unwrap()
+        };
+        
+        {
+            let db = EncryptedDb::with_config(&db_path, "password", Arc::new(SystemClock), config).unwrap();
+            assert_eq!(pragma(&db, "page_size"), 8192);
+            assert_eq!(pragma(&db, "cache_size"), -4000);
+        }
+        
+        // The page size is fixed once the file exists; SQLCipher cannot
+        // read it from the encrypted file, so it must be given on every open
+        let reopened = EncryptedDb::with_config(&db_path, "password", Arc::new(SystemClock), StorageConfig {
+            page_size: Some(1024),
+            ..StorageConfig::default()
+        });
+        if cfg!(feature = "sqlcipher") {
+            assert!(reopened.is_err());
+            let reopened = EncryptedDb::with_config(&db_path, "password", Arc::new(SystemClock), config).unwrap();
+            assert_eq!(pragma(&reopened, "page_size"), 8192);
+        } else {
+            assert_eq!(pragma(&reopened.unwrap(), "page_size"), 8192);
+        }
+        
+        let invalid = StorageConfig { page_size: Some(3000), ..StorageConfig::default() };
+        assert!(matches!(
+            EncryptedDb::with_config(dir.path().join("other.db"), "password", Arc::new(SystemClock), invalid),
+            Err(CoreError::InvalidParameter(_))
+        ));
+    }
+    
+    #[test]
+    fn test_open_retries_transient_failures_only() {
+        let busy = || rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            None,
+        );
+        
+        // A lock that clears after two attempts is waited out
+        let mut calls = 0;
+        let opened = retry_transient(5, Duration::from_millis(1), || {
+            calls += 1;
+            if calls < 3 { Err(busy()) } else { Ok(calls) }
+        });
+        assert_eq!(opened.unwrap(), 3);
+        
+        // A lock that never clears gives up after the configured attempts
+        let mut calls = 0;
+        assert!(retry_transient(4, Duration::from_millis(1), || -> rusqlite::Result<()> {
+            calls += 1;
+            Err(busy())
+        }).is_err());
+        assert_eq!(calls, 4);
+        
+        // A file that is not a database (as with a wrong key) fails at once
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("garbage.db");
+        std::fs::write(&db_path, vec![0xAB; 4096]).unwrap();
+        let mut calls = 0;
+        let result = retry_transient(5, Duration::from_secs(60), || {
+            calls += 1;
+            EncryptedDb::open_connection(&db_path.to_string_lossy(), "password", &StorageConfig::default())
+        });
+        assert_eq!(result.unwrap_err().sqlite_error_code(), Some(rusqlite::ErrorCode::NotADatabase));
+        assert_eq!(calls, 1);
+    }
+    
+    #[test]
+    fn test_hidden_volume_passwords_open_their_own_wallet() {
+        let dir = tempdir().unwrap();
+        let named_account = |name: &str| StoredAccount {
+            name: name.to_string(),
+            ..test_account()
+        };
+        
+        let real = EncryptedDb::new(dir.path().join("real.db"), "real-password").unwrap();
+        real.store_account(&named_account("Savings")).unwrap();
+        let decoy = EncryptedDb::new(dir.path().join("decoy.db"), "decoy-password").unwrap();
+        decoy.store_account(&named_account("Pocket money")).unwrap();
+        
+        let volume_path = dir.path().join("wallet.vol");
+        real.export_hidden_volume(&volume_path, "real-password", Some(&DecoyConfig {
+            db: &decoy,
+            password: "decoy-password",
+        })).unwrap();
+        
+        // Neither wallet is visible in the sealed file
+        let volume = std::fs::read(&volume_path).unwrap();
+        for needle in [&b"Savings"[..], b"Pocket money", b"SQLite format"] {
+            assert!(!volume.windows(needle.len()).any(|w| w == needle));
+        }
+        
+        let opened = EncryptedDb::open_hidden_volume(&volume_path, "real-password", dir.path().join("a.db")).unwrap();
+        let accounts = opened.get_all_accounts().unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].name, "Savings");
+        
+        let opened = EncryptedDb::open_hidden_volume(&volume_path, "decoy-password", dir.path().join("b.db")).unwrap();
+        let accounts = opened.get_all_accounts().unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].name, "Pocket money");
+        
+        assert!(matches!(
+            EncryptedDb::open_hidden_volume(&volume_path, "guess", dir.path().join("c.db")),
+            Err(CoreError::Crypto(_))
+        ));
+    }
+    
     #[test]
     fn test_metadata_storage() {
         let dir = tempdir().unwrap();
@@ -474,16 +3158,7 @@ mod tests {
         let db_path = dir.path().join("test.db");
         let db = EncryptedDb::new(&db_path, "password").unwrap();
         
-        let account = StoredAccount {
-            id: 0,
-            index: 0,
-            name: "Account 1".to_string(),
-            ethereum_address: "0x1234...".to_string(),
-            solana_address: "Sol1234...".to_string(),
-            bitcoin_address: "bc1q...".to_string(),
-            polygon_address: "0x1234...".to_string(),
-            zcash_address: "t1...".to_string(),
-        };
+        let account = test_account();
         
         let id = db.store_account(&account).unwrap();
         assert!(id > 0);
@@ -493,6 +3168,427 @@ mod tests {
         assert_eq!(retrieved.ethereum_address, "0x1234...");
     }
     
+    #[test]
+    fn test_finality_depends_on_chain() {
+        let tx = |chain: &str| StoredTransaction {
+            id: 0,
+            tx_hash: "0x01".to_string(),
+            account_id: 1,
+            chain: chain.to_string(),
+            tx_type: "receive".to_string(),
+            amount: "100".to_string(),
+            from_address: None,
+            to_address: None,
+            status: "confirmed".to_string(),
+            timestamp: 0,
+            block_number: Some(100),
+            gas_used: None,
+            fee: None,
+            confirmations: None,
+        };
+        
+        // Six confirmations at height 106
+        assert_eq!(tx("bitcoin").confirmations_at(100), Some(0));
+        assert_eq!(tx("bitcoin").confirmations_at(106), Some(6));
+        assert!(!tx("bitcoin").is_final(105));
+        assert!(tx("bitcoin").is_final(106));
+        assert!(!tx("ethereum").is_final(111));
+        assert!(tx("ethereum").is_final(112));
+        
+        assert!(tx("zetaris").is_final(100));
+        assert_eq!(tx("bitcoin").confirmations_at(99), None);
+        let pending = StoredTransaction { status: "pending".to_string(), block_number: None, ..tx("bitcoin") };
+        assert!(!pending.is_final(1_000));
+    }
+    
+    #[test]
+    fn test_range_proof_round_trips_through_storage() {
+        let dir = tempdir().unwrap();
+        let db = EncryptedDb::new(dir.path().join("test.db"), "password").unwrap();
+        let account_id = db.store_account(&test_account()).unwrap();
+        let output_id = db.store_stealth_output(&test_output(account_id, "0x01")).unwrap();
+        
+        let blinding = crate::commitments::random_scalar();
+        let proof = crate::commitments::RangeProof::prove(100, &blinding, 64).unwrap();
+        let proof_bytes = bincode::serialize(&proof).unwrap();
+        db.store_commitment_proof(&StoredCommitmentProof {
+            id: 0,
+            account_id,
+            output_id: Some(output_id),
+            commitment: vec![4; 32],
+            value: "100".to_string(),
+            blinding: blinding.to_bytes().to_vec(),
+            range_proof: Some(proof_bytes.clone()),
+        }).unwrap();
+        
+        let (stored_len, compressed): (usize, bool) = db.conn.query_row(
+            "SELECT length(range_proof), range_proof_compressed FROM commitment_proofs",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).unwrap();
+        assert_eq!(compressed, cfg!(feature = "compress-proofs"));
+        if compressed {
+            // The placeholder proof repeats its bytes heavily, so it shrinks
+            // to well under a quarter of its size
+            assert!(stored_len * 4 < proof_bytes.len(), "{} of {} bytes", stored_len, proof_bytes.len());
+        } else {
+            assert_eq!(stored_len, proof_bytes.len());
+        }
+        
+        let openings = db.get_unspent_output_openings(account_id).unwrap();
+        assert_eq!(openings[0].range_proof.as_deref(), Some(proof_bytes.as_slice()));
+    }
+    
+    #[test]
+    fn test_stored_blinding_is_encrypted() {
+        let dir = tempdir().unwrap();
+        let db = EncryptedDb::with_config(dir.path().join("test.db"), "password", Arc::new(SystemClock), StorageConfig {
+            verify_field_encryption: Some(true),
+            ..StorageConfig::default()
+        }).unwrap();
+        assert!(db.is_unlocked());
+        let account_id = db.store_account(&test_account()).unwrap();
+        
+        let blinding = crate::commitments::random_scalar().to_bytes().to_vec();
+        let proof_id = db.store_commitment_proof(&StoredCommitmentProof {
+            id: 0,
+            account_id,
+            output_id: None,
+            commitment: vec![4; 32],
+            value: "100".to_string(),
+            blinding: blinding.clone(),
+            range_proof: None,
+        }).unwrap();
+        
+        let stored: Vec<u8> = db.conn.query_row(
+            "SELECT blinding FROM commitment_proofs WHERE id = ?1",
+            params![proof_id],
+            |row| row.get(0),
+        ).unwrap();
+        assert_ne!(stored, blinding);
+        assert!(check_field_encrypted(&db.conn, "commitment_proofs", "blinding", proof_id, &blinding).is_ok());
+        
+        // The check itself catches a plaintext write
+        db.conn.execute("UPDATE commitment_proofs SET blinding = ?1", params![blinding]).unwrap();
+        assert!(matches!(
+            check_field_encrypted(&db.conn, "commitment_proofs", "blinding", proof_id, &blinding),
+            Err(CoreError::Crypto(_))
+        ));
+    }
+    
+    #[test]
+    fn test_prune_spent_outputs_keeps_unspent_and_pending() {
+        let dir = tempdir().unwrap();
+        let clock = Arc::new(crate::clock::MockClock::from_unix(1_700_000_000));
+        let db = EncryptedDb::with_clock(dir.path().join("test.db"), "password", clock.clone()).unwrap();
+        let account_id = db.store_account(&test_account()).unwrap();
+        
+        let store = |tx_hash: &str| db.store_stealth_output(&test_output(account_id, tx_hash)).unwrap();
+        
+        let old_spent = store("0x01");
+        let old_unspent = store("0x02");
+        let old_pending = store("0x03");
+        db.store_commitment_proof(&StoredCommitmentProof {
+            id: 0,
+            account_id,
+            output_id: Some(old_spent),
+            commitment: vec![4; 32],
+            value: "100".to_string(),
+            blinding: vec![5; 32],
+            range_proof: None,
+        }).unwrap();
+        
+        db.mark_stealth_output_spent_by(old_spent, "0xconfirmed").unwrap();
+        db.mark_stealth_output_spent_by(old_pending, "0xpending").unwrap();
+        db.store_transaction(&StoredTransaction {
+            id: 0,
+            tx_hash: "0xpending".to_string(),
+            account_id,
+            chain: "zetaris".to_string(),
+            tx_type: "send".to_string(),
+            amount: "100".to_string(),
+            from_address: None,
+            to_address: None,
+            status: "pending".to_string(),
+            timestamp: 1_700_000_000,
+            block_number: None,
+            gas_used: None,
+            fee: None,
+            confirmations: None,
+        }).unwrap();
+        
+        clock.advance(Duration::from_secs(30 * 24 * 60 * 60));
+        let recent_spent = store("0x04");
+        db.mark_stealth_output_spent(recent_spent).unwrap();
+        
+        assert_eq!(db.prune_spent_outputs(1_700_000_000 + 24 * 60 * 60).unwrap(), 1);
+        
+        let remaining: Vec<i64> = db.conn.prepare("SELECT id FROM stealth_outputs ORDER BY id").unwrap()
+            .query_map([], |row| row.get(0)).unwrap()
+            .collect::<std::result::Result<_, _>>().unwrap();
+        assert_eq!(remaining, vec![old_unspent, old_pending, recent_spent]);
+        
+        let openings: i64 = db.conn.query_row("SELECT COUNT(*) FROM commitment_proofs", [], |row| row.get(0)).unwrap();
+        assert_eq!(openings, 0);
+    }
+    
+    #[test]
+    fn test_account_for_address() {
+        let dir = tempdir().unwrap();
+        let db = EncryptedDb::new(dir.path().join("test.db"), "password").unwrap();
+        
+        for index in 0..3u32 {
+            db.store_account(&StoredAccount {
+                index,
+                name: format!("Account {}", index + 1),
+                ethereum_address: format!("0xeth{}", index),
+                solana_address: format!("Sol{}", index),
+                bitcoin_address: format!("bc1q{}", index),
+                polygon_address: format!("0xeth{}", index),
+                zcash_address: format!("t1{}", index),
+                ..test_account()
+            }).unwrap();
+        }
+        
+        let found = db.account_for_address("Sol1").unwrap().unwrap();
+        assert_eq!(found.index, 1);
+        assert_eq!(found.name, "Account 2");
+        assert_eq!(db.account_for_address("0xeth2").unwrap().unwrap().index, 2);
+        assert!(db.account_for_address("Sol9").unwrap().is_none());
+    }
+    
+    #[test]
+    fn test_account_addresses_follow_account_deletion() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = EncryptedDb::new(&db_path, "password").unwrap();
+        let account_id = db.store_account(&test_account()).unwrap();
+        
+        // Recreate the index the way older versions did, without the cascade
+        db.conn.execute_batch(
+            "DROP TABLE account_addresses;
+             CREATE TABLE account_addresses (
+                address TEXT NOT NULL,
+                chain TEXT NOT NULL,
+                account_id INTEGER NOT NULL,
+                PRIMARY KEY(address, chain, account_id),
+                FOREIGN KEY(account_id) REFERENCES accounts(id)
+             );",
+        ).unwrap();
+        drop(db);
+        
+        let db = EncryptedDb::new(&db_path, "password").unwrap();
+        assert_eq!(db.account_for_address("Sol1234...").unwrap().unwrap().id, account_id);
+        
+        db.conn.execute("DELETE FROM accounts WHERE id = ?1", params![account_id]).unwrap();
+        let indexed: i64 = db.conn.query_row("SELECT COUNT(*) FROM account_addresses", [], |row| row.get(0)).unwrap();
+        assert_eq!(indexed, 0);
+        assert!(db.account_for_address("Sol1234...").unwrap().is_none());
+    }
+    
+    #[test]
+    fn test_account_timestamp_uses_clock() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let clock = Arc::new(crate::clock::MockClock::from_unix(1_700_000_000));
+        let db = EncryptedDb::with_clock(&db_path, "password", clock).unwrap();
+        
+        let account = test_account();
+        db.store_account(&account).unwrap();
+        
+        let created_at: i64 = db.conn.query_row(
+            "SELECT created_at FROM accounts WHERE account_index = 0",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(created_at, 1_700_000_000);
+    }
+    
+    #[test]
+    fn test_read_public_summary() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        {
+            let db = EncryptedDb::new(&db_path, "password").unwrap();
+            db.set_metadata("wallet_version", "1.0.0").unwrap();
+            db.store_account(&test_account()).unwrap();
+        }
+        
+        // No password needed, unless the whole file is encrypted
+        let summary = EncryptedDb::read_public_summary(&db_path);
+        if cfg!(feature = "sqlcipher") {
+            assert!(matches!(summary, Err(CoreError::Storage(_))));
+            return;
+        }
+        let summary = summary.unwrap();
+        assert_eq!(summary.wallet_version.as_deref(), Some("1.0.0"));
+        assert_eq!(summary.account_count, 1);
+        assert_eq!(summary.last_activity, None);
+        
+        // An unreadable (e.g. fully encrypted) file errors instead of panicking
+        let encrypted_path = dir.path().join("encrypted.db");
+        std::fs::write(&encrypted_path, [0x5au8; 4096]).unwrap();
+        assert!(matches!(
+            EncryptedDb::read_public_summary(&encrypted_path),
+            Err(CoreError::Storage(_))
+        ));
+    }
+    
+    #[test]
+    fn test_duplicate_stealth_output_stored_once() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = EncryptedDb::new(&db_path, "password").unwrap();
+        let account_id = db.store_account(&test_account()).unwrap();
+        
+        let output = StealthOutput {
+            coinbase_height: Some(42),
+            output_index: Some(2),
+            ..test_output(account_id, "0xabc")
+        };
+        
+        let first = db.store_stealth_output(&output).unwrap();
+        let second = db.store_stealth_output(&output).unwrap();
+        assert_eq!(first, second);
+        let stored = db.get_unspent_stealth_outputs(account_id).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].coinbase_height, Some(42));
+        assert_eq!(stored[0].output_index, Some(2));
+        
+        let too_high = StealthOutput { coinbase_height: Some(u64::MAX), ..test_output(account_id, "0xdef") };
+        assert!(matches!(db.store_stealth_output(&too_high), Err(CoreError::InvalidParameter(_))));
+        
+        // Duplicates written before the unique index existed are cleaned up
+        db.conn.execute("DROP INDEX idx_stealth_unique_output", []).unwrap();
+        db.conn.execute(
+            "INSERT INTO stealth_outputs (tx_hash, account_id, ephemeral_public, one_time_public,
+                one_time_private, amount, spent, created_at)
+             SELECT tx_hash, account_id, ephemeral_public, one_time_public,
+                one_time_private, amount, 1, created_at FROM stealth_outputs",
+            [],
+        ).unwrap();
+        
+        assert_eq!(db.dedupe_stealth_outputs().unwrap(), 1);
+        
+        // The surviving row inherits the spent flag of its duplicate
+        assert!(db.get_unspent_stealth_outputs(account_id).unwrap().is_empty());
+        let rows: i64 = db.conn.query_row("SELECT COUNT(*) FROM stealth_outputs", [], |row| row.get(0)).unwrap();
+        assert_eq!(rows, 1);
+    }
+    
+    #[test]
+    fn test_secret_columns_encrypted_at_rest() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = EncryptedDb::new(&db_path, "password").unwrap();
+        let account_id = db.store_account(&test_account()).unwrap();
+        
+        let secret = vec![7u8; 32];
+        let output_id = db.store_stealth_output(&StealthOutput {
+            one_time_private: secret.clone(),
+            ..test_output(account_id, "0xabc")
+        }).unwrap();
+        
+        let raw: Vec<u8> = db.conn.query_row(
+            "SELECT one_time_private FROM stealth_outputs WHERE id = ?1",
+            params![output_id],
+            |row| row.get(0),
+        ).unwrap();
+        assert_ne!(raw, secret);
+        assert!(!raw.windows(secret.len()).any(|w| w == secret.as_slice()));
+        assert_eq!(db.get_unspent_stealth_outputs(account_id).unwrap()[0].one_time_private, secret);
+        
+        // Once migrated, an unencrypted value is an error rather than plaintext
+        db.conn.execute(
+            "UPDATE stealth_outputs SET one_time_private = ?2 WHERE id = ?1",
+            params![output_id, secret],
+        ).unwrap();
+        drop(db);
+        let db = EncryptedDb::new(&db_path, "password").unwrap();
+        assert!(db.get_unspent_stealth_outputs(account_id).is_err());
+        
+        // A database from before the migration is encrypted on the next open
+        db.conn.execute(
+            "DELETE FROM wallet_meta WHERE key = ?1",
+            params![MetadataKey::FieldsEncrypted.as_str()],
+        ).unwrap();
+        drop(db);
+        let db = EncryptedDb::new(&db_path, "password").unwrap();
+        let raw: Vec<u8> = db.conn.query_row(
+            "SELECT one_time_private FROM stealth_outputs WHERE id = ?1",
+            params![output_id],
+            |row| row.get(0),
+        ).unwrap();
+        assert_ne!(raw, secret);
+        assert_eq!(db.get_unspent_stealth_outputs(account_id).unwrap()[0].one_time_private, secret);
+        drop(db);
+        
+        // The wrong password opens a plain database but not its secrets;
+        // SQLCipher does not open the file at all
+        if cfg!(feature = "sqlcipher") {
+            assert!(matches!(EncryptedDb::new(&db_path, "wrong"), Err(CoreError::InvalidParameter(_))));
+        } else {
+            let db = EncryptedDb::new(&db_path, "wrong").unwrap();
+            assert_eq!(db.get_all_accounts().unwrap().len(), 1);
+            assert!(db.get_unspent_stealth_outputs(account_id).is_err());
+        }
+    }
+    
+    #[test]
+    fn test_pre_epoch_clock_does_not_panic() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let before_epoch = std::time::UNIX_EPOCH - std::time::Duration::from_secs(60);
+        let clock = Arc::new(crate::clock::MockClock::new(before_epoch));
+        let db = EncryptedDb::with_clock(&db_path, "password", clock).unwrap();
+        
+        let output = test_output(1, "0xabc");
+        
+        let result = db.store_stealth_output(&output);
+        assert!(matches!(result, Err(CoreError::Storage(_))));
+    }
+    
+    #[test]
+    fn test_for_each_transaction_streams_large_history() {
+        let dir = tempdir().unwrap();
+        let db = EncryptedDb::new(dir.path().join("test.db"), "password").unwrap();
+        let account_id = db.store_account(&test_account()).unwrap();
+        
+        let sql_tx = db.conn.unchecked_transaction().unwrap();
+        for i in 0..50_000i64 {
+            EncryptedDb::insert_transaction(&sql_tx, &StoredTransaction {
+                id: 0,
+                tx_hash: format!("0x{:x}", i),
+                account_id,
+                chain: "ethereum".to_string(),
+                tx_type: "receive".to_string(),
+                amount: "1".to_string(),
+                from_address: None,
+                to_address: None,
+                status: "confirmed".to_string(),
+                timestamp: i,
+                block_number: None,
+                gas_used: None,
+                fee: None,
+                confirmations: None,
+            }).unwrap();
+        }
+        sql_tx.commit().unwrap();
+        
+        // Only running aggregates are kept while streaming
+        let mut newest = None;
+        let mut previous = i64::MAX;
+        let visited = db.for_each_transaction(account_id, |tx| {
+            newest.get_or_insert(tx.timestamp);
+            assert!(tx.timestamp < previous);
+            previous = tx.timestamp;
+        }).unwrap();
+        
+        assert_eq!(visited, 50_000);
+        assert_eq!(newest, Some(49_999));
+        assert_eq!(db.get_transactions(account_id, u32::MAX).unwrap().len(), MAX_TRANSACTION_LIMIT as usize);
+    }
+    
     #[test]
     fn test_transaction_storage() {
         let dir = tempdir().unwrap();
@@ -500,16 +3596,7 @@ mod tests {
         let db = EncryptedDb::new(&db_path, "password").unwrap();
         
         // First create an account
-        let account = StoredAccount {
-            id: 0,
-            index: 0,
-            name: "Account 1".to_string(),
-            ethereum_address: "0x1234...".to_string(),
-            solana_address: "Sol1234...".to_string(),
-            bitcoin_address: "bc1q...".to_string(),
-            polygon_address: "0x1234...".to_string(),
-            zcash_address: "t1...".to_string(),
-        };
+        let account = test_account();
         let account_id = db.store_account(&account).unwrap();
         
         // Store transaction
@@ -526,6 +3613,8 @@ mod tests {
             timestamp: 1700000000,
             block_number: Some(18500000),
             gas_used: Some("21000".to_string()),
+            fee: None,
+            confirmations: None,
         };
         
         let tx_id = db.store_transaction(&tx).unwrap();
@@ -536,4 +3625,112 @@ mod tests {
         assert_eq!(txs.len(), 1);
         assert_eq!(txs[0].tx_hash, "0xabc123...");
     }
+    
+    #[test]
+    fn test_malformed_amount_rejected() {
+        let dir = tempdir().unwrap();
+        let db = EncryptedDb::new(dir.path().join("test.db"), "password").unwrap();
+        let account_id = db.store_account(&test_account()).unwrap();
+        
+        let store = |amount: &str| db.store_transaction(&StoredTransaction {
+            id: 0,
+            tx_hash: format!("0x{}", amount),
+            account_id,
+            chain: "bitcoin".to_string(),
+            tx_type: "receive".to_string(),
+            amount: amount.to_string(),
+            from_address: None,
+            to_address: None,
+            status: "confirmed".to_string(),
+            timestamp: 1_700_000_000,
+            block_number: None,
+            gas_used: None,
+            fee: None,
+            confirmations: None,
+        });
+        
+        for malformed in ["1.5.0", "", "-1", "1e5", ".5", "1.", "1,000", "0.123456789"] {
+            assert!(matches!(store(malformed), Err(CoreError::InvalidParameter(_))), "{malformed:?}");
+        }
+        assert!(store("0.00000001").is_ok());
+        assert!(store("21000000").is_ok());
+        assert_eq!(db.get_transactions(account_id, 10).unwrap().len(), 2);
+    }
+    
+    #[test]
+    fn test_pending_transactions() {
+        let dir = tempdir().unwrap();
+        let db = EncryptedDb::new(dir.path().join("test.db"), "password").unwrap();
+        let account_id = db.store_account(&test_account()).unwrap();
+        
+        for (i, (chain, status)) in [
+            ("ethereum", "pending"),
+            ("ethereum", "confirmed"),
+            ("solana", "pending"),
+            ("solana", "failed"),
+            ("ethereum", "pending"),
+        ].into_iter().enumerate() {
+            db.store_transaction(&StoredTransaction {
+                id: 0,
+                tx_hash: format!("0x{:02x}", i),
+                account_id,
+                chain: chain.to_string(),
+                tx_type: "send".to_string(),
+                amount: "1".to_string(),
+                from_address: None,
+                to_address: None,
+                status: status.to_string(),
+                timestamp: 1_700_000_000 + i as i64,
+                block_number: None,
+                gas_used: None,
+                fee: None,
+                confirmations: None,
+            }).unwrap();
+        }
+        
+        let hashes = |chain| -> Vec<String> {
+            db.pending_transactions(chain).unwrap().into_iter().map(|tx| tx.tx_hash).collect()
+        };
+        assert_eq!(hashes(None), ["0x00", "0x02", "0x04"]);
+        assert_eq!(hashes(Some("ethereum")), ["0x00", "0x04"]);
+        assert!(hashes(Some("bitcoin")).is_empty());
+    }
+    
+    #[test]
+    fn test_future_timestamps_rejected() {
+        let dir = tempdir().unwrap();
+        let now = 1_700_000_000i64;
+        let clock = Arc::new(crate::clock::MockClock::from_unix(now as u64));
+        let db = EncryptedDb::with_config(dir.path().join("test.db"), "password", clock, StorageConfig {
+            max_clock_skew: Some(Duration::from_secs(3600)),
+            ..StorageConfig::default()
+        }).unwrap();
+        let account_id = db.store_account(&test_account()).unwrap();
+        
+        let tx_at = |timestamp: i64| StoredTransaction {
+            id: 0,
+            tx_hash: format!("0x{:x}", timestamp),
+            account_id,
+            chain: "ethereum".to_string(),
+            tx_type: "receive".to_string(),
+            amount: "1".to_string(),
+            from_address: None,
+            to_address: None,
+            status: "confirmed".to_string(),
+            timestamp,
+            block_number: None,
+            gas_used: None,
+            fee: None,
+            confirmations: None,
+        };
+        
+        let a_year_ahead = now + 365 * 24 * 60 * 60;
+        assert!(matches!(db.store_transaction(&tx_at(a_year_ahead)), Err(CoreError::InvalidParameter(_))));
+        assert!(matches!(db.store_transaction(&tx_at(-1)), Err(CoreError::InvalidParameter(_))));
+        assert!(db.store_transaction(&tx_at(now + 3601)).is_err());
+        
+        db.store_transaction(&tx_at(now + 3600)).unwrap();
+        db.store_transaction(&tx_at(0)).unwrap();
+        assert_eq!(db.get_transactions(account_id, 10).unwrap().len(), 2);
+    }
 }
@@ -0,0 +1,153 @@
+//! Field-Level Encryption
+//!
+//! Defense in depth for the most sensitive columns. A random field key is
+//! kept in `wallet_meta`, wrapped under an Argon2 key derived from the
+//! wallet password, and every value is encrypted under its own subkey
+//! HKDF(field key, row nonce, column). A leaked database handle without
+//! the password yields only ciphertext.
+//!
+//! Stored values are `row nonce (16) || cipher nonce (12) || ciphertext`.
+//! Values of 32 bytes or less predate field encryption. They are encrypted
+//! once, the first time the wallet is opened with its key, and rejected on
+//! read from then on.
+
+use rand::RngCore;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+use crate::crypto::{argon2_derive_key, hkdf_sha256, ChaCha20Cipher};
+use crate::storage::{EncryptedDb, MetadataKey};
+use crate::{CoreError, Result};
+
+/// Per-value random salt for the HKDF subkey
+const ROW_NONCE_LEN: usize = 16;
+
+/// Longest value treated as legacy plaintext (a raw scalar)
+pub(crate) const LEGACY_PLAINTEXT_MAX: usize = 32;
+
+/// Unwrapped field key, or `None` if the password did not unwrap it
+#[derive(Clone, Default, Zeroize, ZeroizeOnDrop)]
+pub(crate) struct FieldKey {
+    key: Option<[u8; 32]>,
+}
+
+impl FieldKey {
+    /// Unwrap the stored field key with `password`, creating one if absent
+    ///
+    /// A wrong password leaves the key locked rather than failing, so the
+    /// wallet can still report the error through its own password check.
+    pub(crate) fn unlock(db: &EncryptedDb, password: &str) -> Result<Self> {
+        let (Some(salt), Some(wrapped)) = (
+            db.get_meta(MetadataKey::FieldKeySalt)?,
+            db.get_meta(MetadataKey::FieldKey)?,
+        ) else {
+            let field_key = Self::generate();
+            field_key.wrap(db, password)?;
+            return Ok(field_key);
+        };
+        
+        Self::open(&salt, &wrapped, password)
+    }
+    
+    /// A new random key
+    pub(crate) fn generate() -> Self {
+        let mut key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        let field_key = FieldKey { key: Some(key) };
+        key.zeroize();
+        field_key
+    }
+    
+    /// Store this key wrapped under `password` with a fresh salt
+    ///
+    /// Salt and wrapped key are written together, so a failure cannot
+    /// leave one paired with the other's predecessor.
+    pub(crate) fn wrap(&self, db: &EncryptedDb, password: &str) -> Result<()> {
+        let (salt, wrapped) = self.seal(password)?;
+        db.set_metas(&[(MetadataKey::FieldKeySalt, &salt), (MetadataKey::FieldKey, &wrapped)])
+    }
+    
+    /// Wrap this key under an Argon2 key from `secret`, as hex `(salt, wrapped)`
+    pub(crate) fn seal(&self, secret: &str) -> Result<(String, String)> {
+        let key = self.key()?;
+        
+        let mut salt = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let mut kek = argon2_derive_key(secret.as_bytes(), &salt)?;
+        let wrapped = ChaCha20Cipher::new(&kek).encrypt(key);
+        kek.zeroize();
+        
+        Ok((hex::encode(salt), hex::encode(wrapped?)))
+    }
+    
+    /// Unwrap a key sealed by `seal`; locked if `secret` is wrong
+    pub(crate) fn open(salt: &str, wrapped: &str, secret: &str) -> Result<Self> {
+        let salt = hex::decode(salt)
+            .map_err(|e| CoreError::Storage(format!("Corrupt field key salt: {}", e)))?;
+        let wrapped = hex::decode(wrapped)
+            .map_err(|e| CoreError::Storage(format!("Corrupt field key: {}", e)))?;
+        
+        let mut kek = argon2_derive_key(secret.as_bytes(), &salt)?;
+        let unwrapped = ChaCha20Cipher::new(&kek).decrypt(&wrapped);
+        kek.zeroize();
+        
+        let key = match unwrapped {
+            Ok(mut bytes) => {
+                let key = <[u8; 32]>::try_from(bytes.as_slice()).ok();
+                bytes.zeroize();
+                key
+            }
+            Err(_) => None,
+        };
+        
+        Ok(FieldKey { key })
+    }
+    
+    /// Whether the key was unwrapped
+    pub(crate) fn is_unlocked(&self) -> bool {
+        self.key.is_some()
+    }
+    
+    /// Encrypt a value of `column`
+    pub(crate) fn encrypt(&self, column: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut row_nonce = [0u8; ROW_NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut row_nonce);
+        
+        let mut subkey = self.subkey(column, &row_nonce)?;
+        let ciphertext = ChaCha20Cipher::new(&subkey).encrypt(plaintext);
+        subkey.zeroize();
+        
+        let mut stored = row_nonce.to_vec();
+        stored.extend_from_slice(&ciphertext?);
+        Ok(stored)
+    }
+    
+    /// Decrypt a stored value of `column`
+    pub(crate) fn decrypt(&self, column: &str, stored: &[u8]) -> Result<Vec<u8>> {
+        if stored.len() <= LEGACY_PLAINTEXT_MAX {
+            return Err(CoreError::Crypto(format!("Unencrypted value in {}", column)));
+        }
+        
+        let (row_nonce, ciphertext) = stored.split_at(ROW_NONCE_LEN);
+        let mut subkey = self.subkey(column, row_nonce)?;
+        let plaintext = ChaCha20Cipher::new(&subkey).decrypt(ciphertext);
+        subkey.zeroize();
+        
+        plaintext
+    }
+    
+    /// Key for the audit log MAC chain: HKDF(field key, "", audit label)
+    pub(crate) fn audit_key(&self) -> Result<[u8; 32]> {
+        Ok(hkdf_sha256(self.key()?, &[], b"Zetaris-Audit-v1"))
+    }
+    
+    /// Subkey for one value: HKDF(field key, row nonce, column)
+    fn subkey(&self, column: &str, row_nonce: &[u8]) -> Result<[u8; 32]> {
+        let mut info = b"Zetaris-Field-v1/".to_vec();
+        info.extend_from_slice(column.as_bytes());
+        Ok(hkdf_sha256(self.key()?, row_nonce, &info))
+    }
+    
+    fn key(&self) -> Result<&[u8; 32]> {
+        self.key.as_ref()
+            .ok_or_else(|| CoreError::Crypto("Field key is locked: wallet opened with the wrong password".into()))
+    }
+}
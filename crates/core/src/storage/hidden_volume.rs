@@ -0,0 +1,127 @@
+//! Hidden Volumes
+//!
+//! Seals up to two databases into one file, each under its own password.
+//! A coerced user can reveal the decoy password; the real wallet stays in
+//! a slot that is indistinguishable from the random padding of an unused
+//! one.
+//!
+//! # Limitations
+//!
+//! - Every volume has exactly two equal-size slots, so the format does not
+//!   reveal whether a second wallet exists, but an adversary who knows this
+//!   software knows a second password *may* exist.
+//! - Opening a volume writes the plaintext database to a working path.
+//!   Deniability is lost if that copy, swap, or filesystem journal
+//!   survives; delete it when done and prefer storage you control.
+//! - Both slots are sized to the larger database, so a tiny decoy next to a
+//!   large real wallet produces a volume larger than the decoy needs.
+
+use rand::{Rng, RngCore};
+use zeroize::Zeroize;
+use crate::crypto::{argon2_derive_key, XChaCha20Poly1305Cipher};
+use crate::{CoreError, Result};
+
+/// Slots per volume
+const SLOT_COUNT: usize = 2;
+
+/// Argon2 salt stored at the start of each slot
+const SALT_LEN: usize = 16;
+
+/// XChaCha20 nonce plus Poly1305 tag added by the cipher
+const CIPHER_OVERHEAD: usize = 24 + 16;
+
+/// Little-endian payload length prefixed to the plaintext
+const LEN_PREFIX: usize = 8;
+
+/// Plaintext capacity is rounded up to this many bytes
+const SLOT_GRANULARITY: usize = 64 * 1024;
+
+/// Seal one or two `(password, payload)` pairs into a volume
+///
+/// Slots are assigned in random order, and an unused slot is filled with
+/// random bytes.
+pub fn seal(payloads: &[(&str, &[u8])]) -> Result<Vec<u8>> {
+    if payloads.is_empty() || payloads.len() > SLOT_COUNT {
+        return Err(CoreError::InvalidParameter("A hidden volume holds one or two payloads".into()));
+    }
+    if payloads.len() == SLOT_COUNT && payloads[0].0 == payloads[1].0 {
+        return Err(CoreError::InvalidParameter("Decoy and real passwords must differ".into()));
+    }
+    
+    let largest = payloads.iter().map(|(_, p)| p.len()).max().unwrap_or(0);
+    let capacity = (largest + LEN_PREFIX).div_ceil(SLOT_GRANULARITY) * SLOT_GRANULARITY;
+    let slot_len = SALT_LEN + CIPHER_OVERHEAD + capacity;
+    
+    let mut rng = rand::thread_rng();
+    let mut volume = vec![0u8; slot_len * SLOT_COUNT];
+    rng.fill_bytes(&mut volume);
+    
+    let first = rng.gen_range(0..SLOT_COUNT);
+    for (i, (password, payload)) in payloads.iter().enumerate() {
+        let slot = (first + i) % SLOT_COUNT;
+        let sealed = seal_slot(password, payload, capacity)?;
+        volume[slot * slot_len..(slot + 1) * slot_len].copy_from_slice(&sealed);
+    }
+    
+    Ok(volume)
+}
+
+/// Open the slot of `volume` that `password` unlocks
+///
+/// Every slot is tried so that timing does not reveal which one matched.
+pub fn open(volume: &[u8], password: &str) -> Result<Vec<u8>> {
+    if !volume.len().is_multiple_of(SLOT_COUNT) || volume.len() / SLOT_COUNT < SALT_LEN + CIPHER_OVERHEAD + LEN_PREFIX {
+        return Err(CoreError::Serialization("Invalid hidden volume length".into()));
+    }
+    
+    let mut opened = None;
+    for slot in volume.chunks_exact(volume.len() / SLOT_COUNT) {
+        if let Some(payload) = open_slot(password, slot)? {
+            opened.get_or_insert(payload);
+        }
+    }
+    
+    opened.ok_or_else(|| CoreError::Crypto("No volume slot opens with this password".into()))
+}
+
+/// Encrypt `len || payload || random padding` to exactly fill a slot
+fn seal_slot(password: &str, payload: &[u8], capacity: usize) -> Result<Vec<u8>> {
+    let mut rng = rand::thread_rng();
+    let mut salt = [0u8; SALT_LEN];
+    rng.fill_bytes(&mut salt);
+    
+    let mut plaintext = vec![0u8; capacity];
+    plaintext[..LEN_PREFIX].copy_from_slice(&(payload.len() as u64).to_le_bytes());
+    plaintext[LEN_PREFIX..LEN_PREFIX + payload.len()].copy_from_slice(payload);
+    rng.fill_bytes(&mut plaintext[LEN_PREFIX + payload.len()..]);
+    
+    let mut key = argon2_derive_key(password.as_bytes(), &salt)?;
+    let ciphertext = XChaCha20Poly1305Cipher::new(&key).encrypt(&plaintext);
+    key.zeroize();
+    plaintext.zeroize();
+    
+    let mut slot = salt.to_vec();
+    slot.extend_from_slice(&ciphertext?);
+    Ok(slot)
+}
+
+/// Decrypt a slot, returning `None` if the password does not unlock it
+fn open_slot(password: &str, slot: &[u8]) -> Result<Option<Vec<u8>>> {
+    let (salt, ciphertext) = slot.split_at(SALT_LEN);
+    
+    let mut key = argon2_derive_key(password.as_bytes(), salt)?;
+    let decrypted = XChaCha20Poly1305Cipher::new(&key).decrypt(ciphertext);
+    key.zeroize();
+    
+    let Ok(mut plaintext) = decrypted else {
+        return Ok(None);
+    };
+    
+    let len = u64::from_le_bytes(plaintext[..LEN_PREFIX].try_into().expect("8-byte prefix")) as usize;
+    let payload = plaintext.get(LEN_PREFIX..LEN_PREFIX.saturating_add(len)).map(<[u8]>::to_vec);
+    plaintext.zeroize();
+    
+    payload
+        .map(Some)
+        .ok_or_else(|| CoreError::Serialization("Corrupt hidden volume slot".into()))
+}
@@ -1,5 +1,11 @@
 //! Storage modules
 
 pub mod encrypted_db;
+mod field_crypto;
+pub mod hidden_volume;
+pub mod sync_state;
+#[cfg(test)]
+pub(crate) mod test_fixtures;
 
 pub use encrypted_db::*;
+pub use sync_state::{SyncGap, SyncSession, WalletSyncState};
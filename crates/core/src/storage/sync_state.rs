@@ -0,0 +1,299 @@
+//! Chain Sync State
+//!
+//! Tracks how far the wallet has scanned the chain so that a background
+//! sync can be interrupted and resumed. Heights skipped between sessions
+//! are kept as gaps until a later session scans them.
+
+use std::collections::BTreeMap;
+use crate::storage::{EncryptedDb, StealthOutput};
+use crate::{CoreError, Result};
+
+/// Inclusive range of block heights that has not been scanned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncGap {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// A sync session in progress
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncSession {
+    pub start_height: u64,
+    pub target_height: u64,
+    /// Next height expected by `ingest`
+    pub next_height: u64,
+    /// Accounts scanned by this session
+    pub accounts: Vec<i64>,
+}
+
+/// Persistent chain-sync progress of a wallet
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WalletSyncState {
+    /// Highest height scanned by any session
+    pub last_scanned_height: Option<u64>,
+    /// Unscanned ranges below `last_scanned_height`, in ascending order
+    pub gaps: Vec<SyncGap>,
+    /// Highest height scanned per account id
+    pub account_heights: BTreeMap<i64, u64>,
+    /// Session in progress, if any
+    pub session: Option<SyncSession>,
+}
+
+impl WalletSyncState {
+    /// Load the stored sync state, resuming any interrupted session
+    pub fn load(db: &EncryptedDb) -> Result<Self> {
+        db.load_sync_state()
+    }
+    
+    /// Whether a session is in progress
+    pub fn is_syncing(&self) -> bool {
+        self.session.is_some()
+    }
+    
+    /// Fraction of the current session already scanned (0.0 to 1.0)
+    pub fn progress(&self) -> Option<f64> {
+        self.session.as_ref().map(|s| {
+            let total = s.target_height - s.start_height + 1;
+            let done = s.next_height - s.start_height;
+            done as f64 / total as f64
+        })
+    }
+    
+    /// Start scanning `start_height..=target_height` for `accounts`
+    ///
+    /// Starting above the last scanned height records the skipped heights
+    /// as a gap.
+    pub fn begin_sync(
+        &mut self,
+        db: &EncryptedDb,
+        accounts: &[i64],
+        start_height: u64,
+        target_height: u64,
+    ) -> Result<()> {
+        if self.session.is_some() {
+            return Err(CoreError::InvalidParameter("Sync already in progress".into()));
+        }
+        if start_height > target_height {
+            return Err(CoreError::InvalidParameter("Sync start is above target height".into()));
+        }
+        
+        let mut next = self.clone();
+        if let Some(last) = next.last_scanned_height {
+            if start_height > last + 1 {
+                next.add_gap(last + 1, start_height - 1);
+            }
+        }
+        next.session = Some(SyncSession {
+            start_height,
+            target_height,
+            next_height: start_height,
+            accounts: accounts.to_vec(),
+        });
+        
+        db.save_sync_state(&next)?;
+        *self = next;
+        
+        Ok(())
+    }
+    
+    /// Record that every block up to `height` has been scanned
+    ///
+    /// `outputs` found in those blocks are stored in the same database
+    /// transaction as the new progress. Returns the stored output ids.
+    pub fn ingest(
+        &mut self,
+        db: &EncryptedDb,
+        height: u64,
+        outputs: &[StealthOutput],
+    ) -> Result<Vec<i64>> {
+        let session = self.session.as_ref()
+            .ok_or_else(|| CoreError::InvalidParameter("No sync in progress".into()))?;
+        
+        if height < session.next_height || height > session.target_height {
+            return Err(CoreError::InvalidParameter("Height outside the remaining sync range".into()));
+        }
+        if outputs.iter().any(|o| !session.accounts.contains(&o.account_id)) {
+            return Err(CoreError::InvalidParameter("Output belongs to an account not being synced".into()));
+        }
+        
+        let mut next = self.clone();
+        let from = session.next_height;
+        next.remove_gap_range(from, height);
+        next.last_scanned_height = Some(next.last_scanned_height.map_or(height, |h| h.max(height)));
+        for account in &session.accounts {
+            let entry = next.account_heights.entry(*account).or_insert(height);
+            *entry = (*entry).max(height);
+        }
+        if let Some(s) = next.session.as_mut() {
+            s.next_height = height + 1;
+        }
+        
+        let ids = db.ingest_sync_batch(&next, outputs)?;
+        *self = next;
+        
+        Ok(ids)
+    }
+    
+    /// End the current session
+    ///
+    /// Heights of the session that were never ingested become a gap, except
+    /// those at or below the high-water mark: an earlier session scanned
+    /// them, or they are already a gap.
+    pub fn finish_sync(&mut self, db: &EncryptedDb) -> Result<()> {
+        let session = self.session.as_ref()
+            .ok_or_else(|| CoreError::InvalidParameter("No sync in progress".into()))?;
+        
+        // Ingesting never moves the mark past next_height - 1, so anything
+        // above it was unscanned before this session too
+        let unscanned_from = self.last_scanned_height
+            .map_or(session.next_height, |h| session.next_height.max(h + 1));
+        
+        let mut next = self.clone();
+        if unscanned_from <= session.target_height {
+            next.add_gap(unscanned_from, session.target_height);
+        }
+        next.session = None;
+        
+        db.save_sync_state(&next)?;
+        *self = next;
+        
+        Ok(())
+    }
+    
+    /// Add an unscanned range, merging it with overlapping or adjacent gaps
+    fn add_gap(&mut self, start: u64, end: u64) {
+        let mut merged = SyncGap { start, end };
+        let mut gaps = Vec::with_capacity(self.gaps.len() + 1);
+        
+        for gap in self.gaps.drain(..) {
+            if gap.end + 1 < merged.start || merged.end + 1 < gap.start {
+                gaps.push(gap);
+            } else {
+                merged.start = merged.start.min(gap.start);
+                merged.end = merged.end.max(gap.end);
+            }
+        }
+        gaps.push(merged);
+        gaps.sort_by_key(|g| g.start);
+        
+        self.gaps = gaps;
+    }
+    
+    /// Remove a scanned range from the gaps, splitting any it falls inside
+    fn remove_gap_range(&mut self, start: u64, end: u64) {
+        let mut gaps = Vec::with_capacity(self.gaps.len() + 1);
+        
+        for gap in self.gaps.drain(..) {
+            if gap.end < start || gap.start > end {
+                gaps.push(gap);
+                continue;
+            }
+            if gap.start < start {
+                gaps.push(SyncGap { start: gap.start, end: start - 1 });
+            }
+            if gap.end > end {
+                gaps.push(SyncGap { start: end + 1, end: gap.end });
+            }
+        }
+        
+        self.gaps = gaps;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_fixtures::{test_account, test_output};
+    use tempfile::tempdir;
+    
+    #[test]
+    fn test_two_sessions_with_gap() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = EncryptedDb::new(&db_path, "password").unwrap();
+        let account_id = db.store_account(&test_account()).unwrap();
+        
+        // First session scans 100..=199 in two batches
+        let mut state = WalletSyncState::load(&db).unwrap();
+        state.begin_sync(&db, &[account_id], 100, 199).unwrap();
+        state.ingest(&db, 149, &[test_output(account_id, "0xaaa")]).unwrap();
+        assert_eq!(state.progress(), Some(0.5));
+        state.ingest(&db, 199, &[]).unwrap();
+        state.finish_sync(&db).unwrap();
+        
+        // Second session jumps ahead to the tip and is interrupted
+        state.begin_sync(&db, &[account_id], 300, 399).unwrap();
+        state.ingest(&db, 349, &[test_output(account_id, "0xbbb")]).unwrap();
+        
+        // Reopening resumes the interrupted session
+        drop(db);
+        let db = EncryptedDb::new(&db_path, "password").unwrap();
+        let mut state = WalletSyncState::load(&db).unwrap();
+        assert!(state.is_syncing());
+        assert_eq!(state.session.as_ref().unwrap().next_height, 350);
+        assert!(state.ingest(&db, 300, &[]).is_err());
+        state.finish_sync(&db).unwrap();
+        
+        let state = WalletSyncState::load(&db).unwrap();
+        assert_eq!(state.last_scanned_height, Some(349));
+        assert_eq!(state.gaps, vec![
+            SyncGap { start: 200, end: 299 },
+            SyncGap { start: 350, end: 399 },
+        ]);
+        assert_eq!(state.account_heights.get(&account_id), Some(&349));
+        assert_eq!(state.session, None);
+        assert_eq!(db.get_unspent_stealth_outputs(account_id).unwrap().len(), 2);
+    }
+    
+    #[test]
+    fn test_backfill_splits_gap() {
+        let dir = tempdir().unwrap();
+        let db = EncryptedDb::new(dir.path().join("test.db"), "password").unwrap();
+        let account_id = db.store_account(&test_account()).unwrap();
+        
+        let mut state = WalletSyncState::default();
+        state.begin_sync(&db, &[account_id], 0, 9).unwrap();
+        state.ingest(&db, 9, &[]).unwrap();
+        state.finish_sync(&db).unwrap();
+        state.begin_sync(&db, &[account_id], 100, 100).unwrap();
+        state.ingest(&db, 100, &[]).unwrap();
+        state.finish_sync(&db).unwrap();
+        
+        // Scanning the middle of the gap leaves both ends unscanned
+        state.begin_sync(&db, &[account_id], 40, 59).unwrap();
+        state.ingest(&db, 59, &[]).unwrap();
+        state.finish_sync(&db).unwrap();
+        
+        assert_eq!(state.last_scanned_height, Some(100));
+        assert_eq!(state.gaps, vec![
+            SyncGap { start: 10, end: 39 },
+            SyncGap { start: 60, end: 99 },
+        ]);
+        assert_eq!(WalletSyncState::load(&db).unwrap(), state);
+    }
+    
+    #[test]
+    fn test_interrupted_rescan_adds_no_gap() {
+        let dir = tempdir().unwrap();
+        let db = EncryptedDb::new(dir.path().join("test.db"), "password").unwrap();
+        let account_id = db.store_account(&test_account()).unwrap();
+        
+        let mut state = WalletSyncState::default();
+        state.begin_sync(&db, &[account_id], 0, 99).unwrap();
+        state.ingest(&db, 99, &[]).unwrap();
+        state.finish_sync(&db).unwrap();
+        
+        // Rescanning already-scanned heights and stopping early loses nothing
+        state.begin_sync(&db, &[account_id], 20, 79).unwrap();
+        state.ingest(&db, 39, &[]).unwrap();
+        state.finish_sync(&db).unwrap();
+        assert!(state.gaps.is_empty());
+        
+        // A rescan that runs past the mark only leaves the new heights as a gap
+        state.begin_sync(&db, &[account_id], 50, 149).unwrap();
+        state.ingest(&db, 59, &[]).unwrap();
+        state.finish_sync(&db).unwrap();
+        assert_eq!(state.last_scanned_height, Some(99));
+        assert_eq!(state.gaps, vec![SyncGap { start: 100, end: 149 }]);
+    }
+}
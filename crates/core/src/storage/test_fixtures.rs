@@ -0,0 +1,36 @@
+//! Test Fixtures
+//!
+//! Rows shared by the storage and wallet tests.
+
+use crate::storage::{StealthOutput, StoredAccount};
+
+/// Account 0 with placeholder addresses
+pub(crate) fn test_account() -> StoredAccount {
+    StoredAccount {
+        id: 0,
+        index: 0,
+        name: "Account 1".to_string(),
+        ethereum_address: "0x1234...".to_string(),
+        solana_address: "Sol1234...".to_string(),
+        bitcoin_address: "bc1q...".to_string(),
+        polygon_address: "0x1234...".to_string(),
+        zcash_address: "t1...".to_string(),
+    }
+}
+
+/// Unspent 100-unit output of `account_id` from `tx_hash`
+pub(crate) fn test_output(account_id: i64, tx_hash: &str) -> StealthOutput {
+    StealthOutput {
+        id: 0,
+        tx_hash: tx_hash.to_string(),
+        account_id,
+        ephemeral_public: vec![1; 32],
+        one_time_public: vec![2; 32],
+        one_time_private: vec![3; 32],
+        amount: "100".to_string(),
+        spent: false,
+        is_change: false,
+        coinbase_height: None,
+        output_index: None,
+    }
+}
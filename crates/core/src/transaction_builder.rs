@@ -4,12 +4,68 @@
 
 use crate::{
     CoreError, Result,
-    commitments::{PedersenCommitment, Commitment, RangeProof, random_scalar},
+    commitments::{PedersenCommitment, Commitment, RangeProof, RangeProofKind, is_supported_bit_length, random_scalar},
+    crypto::merkle::MerkleProof,
 };
+use bitcoin::secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1};
+use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::traits::IsIdentity;
-use serde::{Serialize, Deserialize};
-use rand::Rng;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use bincode::Options;
+use serde::{de::DeserializeOwned, Serialize, Deserialize};
+
+/// Largest fee accepted by structural validation
+///
+/// Keeps fees within the integer range that JSON/JavaScript clients can
+/// represent exactly (2^53).
+pub const MAX_FEE: u64 = 1 << 53;
+
+/// Blocks a coinbase output must wait before it can be spent
+pub const COINBASE_MATURITY: u64 = 100;
+
+/// Fewest range proofs `verify` checks as a batch by default
+pub const DEFAULT_BATCH_THRESHOLD: usize = 2;
+
+/// Marks a versioned `PrivateTransaction::to_bytes` encoding
+///
+/// Unversioned encodings start with the input count as a little-endian
+/// u64; only a transaction with over 22 million inputs starts the same way.
+const TX_MAGIC: [u8; 3] = *b"ZTX";
+
+/// Format version written after `TX_MAGIC`
+///
+/// Version 1 adds `range_proof_scheme` and `input_proofs` to the
+/// unversioned layout.
+const TX_FORMAT_VERSION: u8 = 1;
+
+/// Length of the magic and version byte
+const TX_HEADER_LEN: usize = TX_MAGIC.len() + 1;
+
+/// Tuning for `PrivateTransaction::verify_with_options`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyOptions {
+    /// Fewest range proofs worth batch verification; below this each proof
+    /// is verified on its own, avoiding batch setup for a single output
+    pub batch_threshold: usize,
+    /// Reject outputs whose commitment is the identity point (value 0 with
+    /// blinding 0) as malformed; on by default
+    pub reject_identity_outputs: bool,
+    /// Reject transactions whose inputs carry no opening proofs; off by
+    /// default since older transactions have none
+    pub require_input_proofs: bool,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        VerifyOptions {
+            batch_threshold: DEFAULT_BATCH_THRESHOLD,
+            reject_identity_outputs: true,
+            require_input_proofs: false,
+        }
+    }
+}
 
 /// Private transaction with hidden amounts
 #[derive(Clone, Serialize, Deserialize)]
@@ -24,43 +80,288 @@ pub struct PrivateTransaction {
     pub fee: u64,
     /// Optional metadata
     pub metadata: Option<Vec<u8>>,
+    /// Scheme the range proofs were made with (transaction header)
+    pub range_proof_scheme: RangeProofKind,
+    /// Proofs of knowing each input commitment's opening, one per input;
+    /// empty when the transaction carries none
+    pub input_proofs: Vec<InputProof>,
 }
 
 impl PrivateTransaction {
     /// Verify transaction validity
+    ///
+    /// Structural problems, including an output committing to the identity
+    /// point, are returned as `CoreError::InvalidTransaction`,
+    /// range proofs with an unsupported bit length as `CoreError::RangeProof`;
+    /// a well-formed transaction that fails cryptographic checks returns `Ok(false)`.
+    /// Every range proof is checked even after one fails, so the cost does
+    /// not reveal which output is bad; see `verify_fast` for quick rejection.
+    /// Coinbases are rejected here and checked with `verify_coinbase`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        name = "verify_transaction",
+        skip_all,
+        fields(inputs = self.inputs.len(), outputs = self.outputs.len()),
+    ))]
     pub fn verify(&self) -> Result<bool> {
-        // 1. Verify input-output balance equation
-        if !self.verify_balance()? {
+        self.verify_with_options(&VerifyOptions::default())
+    }
+    
+    /// Verify like `verify`, with explicit tuning
+    pub fn verify_with_options(&self, options: &VerifyOptions) -> Result<bool> {
+        self.verify_with(
+            false,
+            options,
+            None,
+            |proof, commitment| self.range_proof_scheme.verify(proof, commitment),
+            |proofs, commitments| self.range_proof_scheme.verify_batch(proofs, commitments),
+        )
+    }
+    
+    /// Verify like `verify`, but stop at the first invalid range proof
+    ///
+    /// For mempool-style rejection of transactions that are clearly bad.
+    /// Range proofs are always checked one at a time.
+    pub fn verify_fast(&self) -> Result<bool> {
+        let options = VerifyOptions { batch_threshold: usize::MAX, ..VerifyOptions::default() };
+        self.verify_with(
+            true,
+            &options,
+            None,
+            |proof, commitment| self.range_proof_scheme.verify(proof, commitment),
+            |proofs, commitments| self.range_proof_scheme.verify_batch(proofs, commitments),
+        )
+    }
+    
+    /// Verify a coinbase that mints `minted`
+    ///
+    /// Its outputs plus fee must balance against `minted`, committed with
+    /// a zero blinding, so the output blindings must sum to zero. Other
+    /// checks are as in `verify`; a non-coinbase is an error.
+    pub fn verify_coinbase(&self, minted: u64) -> Result<bool> {
+        if !self.is_coinbase() {
+            return Err(CoreError::InvalidTransaction("Not a coinbase transaction".into()));
+        }
+        self.verify_with(
+            false,
+            &VerifyOptions::default(),
+            Some(minted),
+            |proof, commitment| self.range_proof_scheme.verify(proof, commitment),
+            |proofs, commitments| self.range_proof_scheme.verify_batch(proofs, commitments),
+        )
+    }
+    
+    /// Run the checks cheapest first
+    ///
+    /// `minted` is the amount a coinbase creates, and must be given exactly
+    /// for coinbases. Range proofs go through `verify_batch` when there are
+    /// at least `options.batch_threshold` of them and through `verify_proof`
+    /// otherwise.
+    fn verify_with<F, B>(
+        &self,
+        stop_early: bool,
+        options: &VerifyOptions,
+        minted: Option<u64>,
+        mut verify_proof: F,
+        verify_batch: B,
+    ) -> Result<bool>
+    where
+        F: FnMut(&RangeProof, &Commitment) -> bool,
+        B: FnOnce(&[RangeProof], &[Commitment]) -> bool,
+    {
+        // 1. Reject malformed transactions before touching the curve
+        self.validate_structure()?;
+        if self.is_coinbase() && minted.is_none() {
+            return Err(CoreError::InvalidTransaction("Coinbase must be verified with verify_coinbase".into()));
+        }
+        if let Some(proof) = self.range_proofs.iter().find(|proof| !is_supported_bit_length(proof.bit_length)) {
+            return Err(CoreError::RangeProof(format!(
+                "Range proof for output {} has unsupported bit length {}",
+                proof.output_index,
+                proof.bit_length
+            )));
+        }
+        if options.reject_identity_outputs {
+            if let Some(i) = self.outputs.iter().position(|o| o.commitment.point.is_identity()) {
+                return Err(CoreError::InvalidTransaction(format!("Output {} commits to the identity point", i)));
+            }
+        }
+        
+        if options.require_input_proofs && self.input_proofs.len() != self.inputs.len() {
+            return Err(CoreError::InvalidTransaction("Transaction carries no input proofs".into()));
+        }
+        
+        // 2. Verify input-output balance equation before any range proof
+        if !self.verify_balance(minted.unwrap_or(0))? {
+            return Ok(false);
+        }
+        
+        // 3. Verify input opening proofs, when present
+        let pedersen = PedersenCommitment::new();
+        if !self.input_proofs.iter().zip(&self.inputs).all(|(proof, input)| proof.verify(&pedersen, input)) {
             return Ok(false);
         }
         
-        // 2. Verify all range proofs
-        for (i, proof) in self.range_proofs.iter().enumerate() {
-            if i >= self.outputs.len() {
-                return Err(CoreError::InvalidParameter("Too many range proofs".into()));
+        // 4. Verify all range proofs against the output they name;
+        // `validate_structure` has checked every index is in range
+        if self.range_proofs.len() >= options.batch_threshold {
+            let commitments: Vec<Commitment> = self.range_proofs.iter()
+                .map(|proof| self.outputs[proof.output_index as usize].commitment.clone())
+                .collect();
+            return Ok(verify_batch(&self.range_proofs, &commitments));
+        }
+        
+        let mut valid = true;
+        for proof in &self.range_proofs {
+            let output = &self.outputs[proof.output_index as usize];
+            valid &= verify_proof(proof, &output.commitment);
+            if !valid && stop_early {
+                break;
             }
+        }
+        
+        // 5. Verify signatures on inputs (would check UTXO ownership)
+        // TODO: Add signature verification
+        
+        Ok(valid)
+    }
+    
+    /// Check that the transaction is well-formed, independent of cryptography
+    ///
+    /// Intended for transactions deserialized from untrusted sources. A
+    /// transaction without inputs is only accepted as a coinbase, which
+    /// pays no fee.
+    pub fn validate_structure(&self) -> Result<()> {
+        if self.inputs.is_empty() && !self.is_coinbase() {
+            return Err(CoreError::InvalidTransaction("Transaction has no inputs".into()));
+        }
+        
+        if self.outputs.is_empty() {
+            return Err(CoreError::InvalidTransaction("Transaction has no outputs".into()));
+        }
+        
+        if self.range_proofs.len() != self.outputs.len() {
+            return Err(CoreError::InvalidTransaction(format!(
+                "Expected {} range proofs, found {}",
+                self.outputs.len(),
+                self.range_proofs.len()
+            )));
+        }
+        
+        let mut covered = vec![false; self.outputs.len()];
+        for proof in &self.range_proofs {
+            match covered.get_mut(proof.output_index as usize) {
+                Some(seen) if !*seen => *seen = true,
+                Some(_) => {
+                    return Err(CoreError::InvalidTransaction(
+                        format!("Output {} has more than one range proof", proof.output_index)
+                    ));
+                }
+                None => {
+                    return Err(CoreError::InvalidTransaction(
+                        format!("Range proof references missing output {}", proof.output_index)
+                    ));
+                }
+            }
+        }
+        
+        if !self.input_proofs.is_empty() && self.input_proofs.len() != self.inputs.len() {
+            return Err(CoreError::InvalidTransaction(format!(
+                "Expected {} input proofs, found {}",
+                self.inputs.len(),
+                self.input_proofs.len()
+            )));
+        }
+        
+        if let Some(i) = self.outputs.iter().position(|o| o.address.is_empty()) {
+            return Err(CoreError::InvalidTransaction(format!("Output {} has an empty address", i)));
+        }
+        
+        if self.fee > MAX_FEE {
+            return Err(CoreError::InvalidTransaction(format!("Fee {} exceeds maximum {}", self.fee, MAX_FEE)));
+        }
+        
+        Ok(())
+    }
+    
+    /// Validate the transaction against the outputs it spends at `current_height`
+    ///
+    /// Runs `validate_structure`, then rejects any input that spends a
+    /// coinbase output less than `COINBASE_MATURITY` blocks old. Inputs
+    /// are matched to `spent` by transaction hash and output index; an
+    /// input with no matching UTXO is treated as unknown.
+    pub fn validate_transaction(&self, current_height: u64, spent: &[UTXO]) -> Result<()> {
+        self.validate_structure()?;
+        
+        for (i, input) in self.inputs.iter().enumerate() {
+            let utxo = spent.iter()
+                .find(|u| u.tx_hash == input.prev_tx_hash && u.output_index == input.prev_output_index)
+                .ok_or_else(|| CoreError::InvalidTransaction(format!("Input {} spends an unknown output", i)))?;
             
-            let output = &self.outputs[i];
-            if !proof.verify(&output.commitment) {
-                return Ok(false);
+            if !utxo.is_mature(current_height) {
+                return Err(CoreError::InvalidTransaction(format!(
+                    "Input {} spends a coinbase output before maturity", i
+                )));
             }
         }
         
-        // 3. Verify signatures on inputs (would check UTXO ownership)
-        // TODO: Add signature verification
+        Ok(())
+    }
+    
+    /// Check that every input commitment is in the set committed to by `anchor_root`
+    ///
+    /// `membership_proofs[i]` proves input `i`. Requires one proof per input.
+    pub fn verify_inputs_in_set(&self, anchor_root: [u8; 32], membership_proofs: &[MerkleProof]) -> bool {
+        membership_proofs.len() == self.inputs.len()
+            && self.inputs.iter()
+                .zip(membership_proofs)
+                .all(|(input, proof)| proof.verify(&anchor_root, &input.commitment.to_bytes()))
+    }
+    
+    /// Summarize how well this transaction hides which output is which
+    ///
+    /// Output values are only known to the transaction's creator (they are
+    /// not serialized), so this is meant for scoring a transaction before
+    /// it is broadcast. Outputs with unknown values count as distinct.
+    pub fn anonymity_metrics(&self) -> AnonymityMetrics {
+        let mut counts: std::collections::HashMap<u64, usize> = std::collections::HashMap::new();
+        for value in self.outputs.iter().filter_map(|o| o.commitment.value) {
+            *counts.entry(value).or_default() += 1;
+        }
+        // With no known values, every output is its own group of one
+        let equal_value_outputs = counts.values().copied().max()
+            .unwrap_or(usize::from(!self.outputs.is_empty()));
         
-        Ok(true)
+        AnonymityMetrics {
+            // Inputs name the single output they spend; there are no rings
+            ring_size: None,
+            equal_value_outputs,
+            output_uniformity: if self.outputs.is_empty() {
+                0.0
+            } else {
+                equal_value_outputs as f64 / self.outputs.len() as f64
+            },
+            change_distinguishable: self.outputs.len() > 1 && equal_value_outputs < self.outputs.len(),
+        }
+    }
+    
+    /// Whether this is a coinbase (no inputs, no fee)
+    pub fn is_coinbase(&self) -> bool {
+        self.inputs.is_empty() && self.fee == 0
     }
     
-    /// Verify balance equation: sum(inputs) = sum(outputs) + fee
-    fn verify_balance(&self) -> Result<bool> {
+    /// Verify balance equation: sum(inputs) + minted = sum(outputs) + fee
+    fn verify_balance(&self, minted: u64) -> Result<bool> {
         let pc = PedersenCommitment::new();
         
-        // Sum input commitments
+        // Sum input commitments, plus the minted amount (blinding 0) of a coinbase
         let mut input_commitments: Vec<&Commitment> = Vec::new();
         for input in &self.inputs {
             input_commitments.push(&input.commitment);
         }
+        let minted_commitment = pc.commit(minted, &Scalar::ZERO);
+        if self.is_coinbase() {
+            input_commitments.push(&minted_commitment);
+        }
         
         // Sum output commitments
         let mut output_commitments: Vec<&Commitment> = Vec::new();
@@ -79,8 +380,8 @@ impl PrivateTransaction {
         }
         
         let mut result = input_commitments[0].clone();
-        for i in 1..input_commitments.len() {
-            result = PedersenCommitment::add_commitments(&result, input_commitments[i]);
+        for input_c in input_commitments.iter().skip(1) {
+            result = PedersenCommitment::add_commitments(&result, input_c);
         }
         
         for output_c in output_commitments {
@@ -92,19 +393,213 @@ impl PrivateTransaction {
         Ok(result.point.is_identity())
     }
     
+    /// Merge partial transactions from several participants (coinjoin)
+    ///
+    /// Inputs, outputs and range proofs are concatenated and fees are summed.
+    /// The parts need not balance individually, but participants must coordinate
+    /// their blindings (see `TransactionBuilder::calculate_change_blinding`) so
+    /// that the combined transaction does. Metadata of `self` is kept.
+    pub fn merge(&self, others: &[PrivateTransaction]) -> Result<PrivateTransaction> {
+        // Input proofs stay aligned with inputs only if every part has them
+        let parts = || std::iter::once(self).chain(others);
+        if parts().any(|part| !part.input_proofs.is_empty())
+            && parts().any(|part| part.input_proofs.len() != part.inputs.len())
+        {
+            return Err(CoreError::InvalidTransaction("Cannot merge transactions with and without input proofs".into()));
+        }
+        
+        let mut merged = self.clone();
+        
+        for other in others {
+            if other.range_proof_scheme != merged.range_proof_scheme {
+                return Err(CoreError::InvalidTransaction("Cannot merge transactions using different range proof schemes".into()));
+            }
+            
+            // Proof indices are relative to each part's own outputs
+            let offset = merged.outputs.len() as u32;
+            merged.inputs.extend(other.inputs.iter().cloned());
+            merged.input_proofs.extend(other.input_proofs.iter().cloned());
+            merged.outputs.extend(other.outputs.iter().cloned());
+            merged.range_proofs.extend(other.range_proofs.iter().map(|proof| {
+                let output_index = proof.output_index + offset;
+                proof.clone().with_output_index(output_index)
+            }));
+            merged.fee = merged.fee.checked_add(other.fee)
+                .ok_or_else(|| CoreError::InvalidParameter("Merged fee overflows".into()))?;
+        }
+        
+        if !merged.verify_balance(0)? {
+            return Err(CoreError::InvalidParameter(
+                "Merged transaction does not balance".into()
+            ));
+        }
+        
+        Ok(merged)
+    }
+    
+    /// Hash that input signatures commit to
+    ///
+    /// Covers the whole transaction with every input signature cleared, so
+    /// signatures can be added in any order without invalidating others.
+    pub fn signing_hash(&self) -> Result<[u8; 32]> {
+        use sha2::{Digest, Sha256};
+        
+        let mut unsigned = self.clone();
+        for input in &mut unsigned.inputs {
+            input.signature.clear();
+        }
+        
+        let mut hasher = Sha256::new();
+        hasher.update(b"Zetaris-Tx-Signing-v1");
+        hasher.update(unsigned.to_bytes()?);
+        Ok(hasher.finalize().into())
+    }
+    
+    /// Check input `index`'s signature over `signing_hash` against `public_key`
+    ///
+    /// A missing or malformed signature is `Ok(false)`.
+    pub fn verify_input_signature(&self, index: usize, public_key: &PublicKey) -> Result<bool> {
+        use sha2::{Digest, Sha256};
+        
+        let input = self.inputs.get(index)
+            .ok_or_else(|| CoreError::InvalidParameter(format!("No input {}", index)))?;
+        let Ok(signature) = Signature::from_compact(&input.signature) else {
+            return Ok(false);
+        };
+        
+        let digest = Sha256::digest(self.signing_hash()?);
+        let msg = Message::from_digest_slice(&digest)
+            .map_err(|e| CoreError::Crypto(e.to_string()))?;
+        Ok(Secp256k1::verification_only().verify_ecdsa(&msg, &signature, public_key).is_ok())
+    }
+    
+    /// Check every input's signature, `public_keys[i]` signing input `i`
+    pub fn verify_signatures(&self, public_keys: &[PublicKey]) -> Result<bool> {
+        if public_keys.len() != self.inputs.len() {
+            return Err(CoreError::InvalidParameter(format!(
+                "Expected {} public keys, got {}", self.inputs.len(), public_keys.len()
+            )));
+        }
+        
+        for (index, public_key) in public_keys.iter().enumerate() {
+            if !self.verify_input_signature(index, public_key)? {
+                return Ok(false);
+            }
+        }
+        
+        Ok(true)
+    }
+    
     /// Serialize to bytes for transmission
+    ///
+    /// Writes `TX_MAGIC` and the format version ahead of the fields.
     pub fn to_bytes(&self) -> Result<Vec<u8>> {
-        bincode::serialize(self)
-            .map_err(|e| CoreError::Serialization(format!("Failed to serialize transaction: {}", e)))
+        let mut bytes = Vec::with_capacity(self.serialized_size()?);
+        bytes.extend_from_slice(&TX_MAGIC);
+        bytes.push(TX_FORMAT_VERSION);
+        bincode::serialize_into(&mut bytes, self)
+            .map_err(|e| CoreError::Serialization(format!("Failed to serialize transaction: {}", e)))?;
+        Ok(bytes)
+    }
+    
+    /// Exact length of `to_bytes`, computed without serializing
+    pub fn serialized_size(&self) -> Result<usize> {
+        bincode::serialized_size(self)
+            .map(|size| size as usize + TX_HEADER_LEN)
+            .map_err(|e| CoreError::Serialization(format!("Failed to size transaction: {}", e)))
+    }
+    
+    /// Whether the fee covers `fee_rate` per byte of the serialized transaction
+    ///
+    /// Uses the true size, unlike `TransactionBuilder::estimate_size`, so
+    /// check after signing: signatures count towards the size.
+    pub fn pays_fee_rate(&self, fee_rate: u64) -> Result<bool> {
+        let required = (self.serialized_size()? as u64).checked_mul(fee_rate)
+            .ok_or_else(|| CoreError::InvalidParameter("Required fee overflows".into()))?;
+        Ok(self.fee >= required)
     }
     
     /// Deserialize from bytes
+    ///
+    /// Accepts every versioned format and the unversioned layouts written
+    /// before the header existed, which carry no input proofs and either
+    /// use Bulletproofs or end with their range proof scheme. The whole of
+    /// `bytes` must be consumed.
     pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
-        bincode::deserialize(bytes)
-            .map_err(|e| CoreError::Serialization(format!("Failed to deserialize transaction: {}", e)))
+        let Some(versioned) = bytes.strip_prefix(&TX_MAGIC) else {
+            if let Ok(legacy) = decode_exact::<LegacyPrivateTransaction>(bytes) {
+                return Ok(legacy.into());
+            }
+            let schemed: SchemedLegacyPrivateTransaction = decode_exact(bytes)?;
+            return Ok(PrivateTransaction {
+                range_proof_scheme: schemed.range_proof_scheme,
+                ..schemed.legacy.into()
+            });
+        };
+        
+        match versioned.split_first() {
+            Some((&TX_FORMAT_VERSION, body)) => decode_exact(body),
+            Some((version, _)) => Err(CoreError::Serialization(
+                format!("Unsupported transaction format version {}", version)
+            )),
+            None => Err(CoreError::Serialization("Transaction is missing its format version".into())),
+        }
     }
 }
 
+/// Decode bincode as `bincode::serialize` writes it, rejecting trailing bytes
+fn decode_exact<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .deserialize(bytes)
+        .map_err(|e| CoreError::Serialization(format!("Failed to deserialize transaction: {}", e)))
+}
+
+/// `PrivateTransaction` fields as encoded before the format header
+#[derive(Deserialize)]
+struct LegacyPrivateTransaction {
+    inputs: Vec<TransactionInput>,
+    outputs: Vec<TransactionOutput>,
+    range_proofs: Vec<RangeProof>,
+    fee: u64,
+    metadata: Option<Vec<u8>>,
+}
+
+/// Unversioned layout that recorded the range proof scheme
+#[derive(Deserialize)]
+struct SchemedLegacyPrivateTransaction {
+    legacy: LegacyPrivateTransaction,
+    range_proof_scheme: RangeProofKind,
+}
+
+impl From<LegacyPrivateTransaction> for PrivateTransaction {
+    fn from(legacy: LegacyPrivateTransaction) -> Self {
+        PrivateTransaction {
+            inputs: legacy.inputs,
+            outputs: legacy.outputs,
+            range_proofs: legacy.range_proofs,
+            fee: legacy.fee,
+            metadata: legacy.metadata,
+            range_proof_scheme: RangeProofKind::Bulletproofs,
+            input_proofs: Vec::new(),
+        }
+    }
+}
+
+/// Privacy summary of a transaction, from `PrivateTransaction::anonymity_metrics`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnonymityMetrics {
+    /// Ring members per input, if inputs are ring signatures
+    pub ring_size: Option<usize>,
+    /// Size of the largest group of outputs sharing one value
+    pub equal_value_outputs: usize,
+    /// `equal_value_outputs` as a fraction of all outputs (1.0 = all equal)
+    pub output_uniformity: f64,
+    /// Whether an output's value sets it apart as likely change
+    pub change_distinguishable: bool,
+}
+
 /// Transaction input (UTXO reference)
 #[derive(Clone, Serialize, Deserialize)]
 pub struct TransactionInput {
@@ -118,6 +613,57 @@ pub struct TransactionInput {
     pub signature: Vec<u8>,
 }
 
+/// Proof of knowing the opening (value, blinding) of an input commitment
+///
+/// A Schnorr proof over both generators: T = a·G + b·H, e = H(C, T, outpoint),
+/// and responses s_v = a + e·v, s_r = b + e·r satisfy s_v·G + s_r·H = T + e·C.
+/// Binding the outpoint stops a proof being reused for another input.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InputProof {
+    /// Nonce commitment T
+    pub nonce_commitment: RistrettoPoint,
+    /// Response for the value, s_v
+    pub value_response: Scalar,
+    /// Response for the blinding factor, s_r
+    pub blinding_response: Scalar,
+}
+
+impl InputProof {
+    /// Prove knowledge of `value` and `blinding` opening `input.commitment`
+    pub fn prove(pedersen: &PedersenCommitment, input: &TransactionInput, value: u64, blinding: &Scalar) -> Self {
+        let value_nonce = random_scalar();
+        let blinding_nonce = random_scalar();
+        let nonce_commitment = value_nonce * pedersen.g + blinding_nonce * pedersen.h;
+        
+        let challenge = Self::challenge(input, &nonce_commitment);
+        InputProof {
+            nonce_commitment,
+            value_response: value_nonce + challenge * Scalar::from(value),
+            blinding_response: blinding_nonce + challenge * blinding,
+        }
+    }
+    
+    /// Check the proof against `input`'s commitment and outpoint
+    pub fn verify(&self, pedersen: &PedersenCommitment, input: &TransactionInput) -> bool {
+        let challenge = Self::challenge(input, &self.nonce_commitment);
+        self.value_response * pedersen.g + self.blinding_response * pedersen.h
+            == self.nonce_commitment + challenge * input.commitment.point
+    }
+    
+    /// Fiat-Shamir challenge e = H(C || T || prev_tx_hash || prev_output_index)
+    fn challenge(input: &TransactionInput, nonce_commitment: &RistrettoPoint) -> Scalar {
+        use sha2::{Digest, Sha512};
+        let hash = Sha512::new()
+            .chain_update(b"Zetaris-Input-Proof-v1")
+            .chain_update(input.commitment.point.compress().as_bytes())
+            .chain_update(nonce_commitment.compress().as_bytes())
+            .chain_update(input.prev_tx_hash)
+            .chain_update(input.prev_output_index.to_le_bytes())
+            .finalize();
+        Scalar::from_bytes_mod_order_wide(&hash.into())
+    }
+}
+
 /// Transaction output
 #[derive(Clone, Serialize, Deserialize)]
 pub struct TransactionOutput {
@@ -136,6 +682,9 @@ pub struct TransactionBuilder {
     outputs: Vec<BuilderOutput>,
     fee: u64,
     metadata: Option<Vec<u8>>,
+    range_proof_scheme: RangeProofKind,
+    max_value: Option<u64>,
+    prove_inputs: bool,
 }
 
 #[derive(Clone)]
@@ -164,9 +713,33 @@ impl TransactionBuilder {
             outputs: Vec::new(),
             fee: 0,
             metadata: None,
+            range_proof_scheme: RangeProofKind::default(),
+            max_value: None,
+            prove_inputs: false,
         }
     }
     
+    /// Choose the range proof scheme used by `build` (default Bulletproofs)
+    pub fn set_range_proof_scheme(&mut self, scheme: RangeProofKind) -> &mut Self {
+        self.range_proof_scheme = scheme;
+        self
+    }
+    
+    /// Attach an `InputProof` for every input in `build`
+    pub fn set_prove_inputs(&mut self, prove_inputs: bool) -> &mut Self {
+        self.prove_inputs = prove_inputs;
+        self
+    }
+    
+    /// Reject outputs worth more than `max`, such as a capped token's
+    /// total supply
+    ///
+    /// Checked as outputs are added, before any range proof work.
+    pub fn set_max_value(&mut self, max: u64) -> &mut Self {
+        self.max_value = Some(max);
+        self
+    }
+    
     /// Add input to transaction
     pub fn add_input(
         &mut self,
@@ -195,7 +768,7 @@ impl TransactionBuilder {
         &mut self,
         address: Vec<u8>,
         value: u64,
-    ) -> &mut Self {
+    ) -> Result<&mut Self> {
         // Generate random blinding factor for output
         let blinding = random_scalar();
         self.add_output_with_blinding(address, value, blinding)
@@ -208,7 +781,15 @@ impl TransactionBuilder {
         address: Vec<u8>,
         value: u64,
         blinding: Scalar,
-    ) -> &mut Self {
+    ) -> Result<&mut Self> {
+        if let Some(max) = self.max_value {
+            if value > max {
+                return Err(CoreError::InvalidParameter(
+                    format!("Output value {} exceeds maximum {}", value, max)
+                ));
+            }
+        }
+        
         let commitment = self.pedersen.commit(value, &blinding);
         
         self.outputs.push(BuilderOutput {
@@ -218,7 +799,30 @@ impl TransactionBuilder {
             commitment,
         });
         
-        self
+        Ok(self)
+    }
+    
+    /// Add an output for whatever the inputs leave after outputs and fee
+    ///
+    /// Takes the balancing blinding factor, so add it after every other
+    /// output; `WalletState::next_change_address` supplies a fresh address.
+    /// Nothing is added when there is no change.
+    pub fn add_change_output(&mut self, address: impl Into<Vec<u8>>) -> Result<&mut Self> {
+        let total_input = self.inputs.iter()
+            .try_fold(0u64, |acc, i| acc.checked_add(i.value));
+        let spent = self.outputs.iter()
+            .try_fold(self.fee, |acc, o| acc.checked_add(o.value));
+        
+        let change = total_input.zip(spent)
+            .and_then(|(input, spent)| input.checked_sub(spent))
+            .ok_or_else(|| CoreError::InvalidParameter("Outputs and fee exceed inputs".into()))?;
+        
+        if change > 0 {
+            let blinding = self.calculate_change_blinding();
+            self.add_output_with_blinding(address.into(), change, blinding)?;
+        }
+        
+        Ok(self)
     }
     
     /// Set transaction fee
@@ -233,7 +837,29 @@ impl TransactionBuilder {
         self
     }
     
+    /// Shuffle outputs with system randomness
+    ///
+    /// Hides which output is change. Range proofs are generated in the
+    /// shuffled order by `build`.
+    pub fn shuffle_outputs(&mut self) -> &mut Self {
+        self.shuffle_outputs_seeded(rand::thread_rng().gen())
+    }
+    
+    /// Shuffle outputs deterministically from `seed`
+    ///
+    /// The same seed and outputs always give the same order, which makes
+    /// transactions reproducible for tests and audits.
+    pub fn shuffle_outputs_seeded(&mut self, seed: [u8; 32]) -> &mut Self {
+        self.outputs.shuffle(&mut ChaCha20Rng::from_seed(seed));
+        self
+    }
+    
     /// Build and sign transaction
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        name = "build_transaction",
+        skip_all,
+        fields(inputs = self.inputs.len(), outputs = self.outputs.len()),
+    ))]
     pub fn build(&self) -> Result<PrivateTransaction> {
         // Verify balance before building
         let total_input: u64 = self.inputs.iter().map(|i| i.value).sum();
@@ -266,9 +892,21 @@ impl TransactionBuilder {
         }).collect();
         
         // Generate range proofs for each output
-        let range_proofs: Vec<RangeProof> = self.outputs.iter()
-            .map(|o| RangeProof::prove(o.value, &o.blinding, 64))
-            .collect::<Result<Vec<_>>>()?;
+        let values: Vec<u64> = self.outputs.iter().map(|o| o.value).collect();
+        let blindings: Vec<Scalar> = self.outputs.iter().map(|o| o.blinding).collect();
+        let range_proofs = self.range_proof_scheme.prove_multi(&values, &blindings, 64)
+            .map_err(|e| match e {
+                CoreError::RangeProof(_) => e,
+                other => CoreError::RangeProof(format!("Failed to prove outputs: {}", other)),
+            })?;
+        
+        let input_proofs = if self.prove_inputs {
+            self.inputs.iter().zip(&inputs)
+                .map(|(i, input)| InputProof::prove(&self.pedersen, input, i.value, &i.blinding))
+                .collect()
+        } else {
+            Vec::new()
+        };
         
         Ok(PrivateTransaction {
             inputs,
@@ -276,14 +914,51 @@ impl TransactionBuilder {
             range_proofs,
             fee: self.fee,
             metadata: self.metadata.clone(),
+            range_proof_scheme: self.range_proof_scheme,
+            input_proofs,
         })
     }
     
+    /// Build, also returning the outputs the wallet owns as spendable UTXOs
+    ///
+    /// `is_own` picks the wallet's outputs by address, e.g. its change
+    /// address. The UTXOs carry their values and blindings and reference the
+    /// transaction by `signing_hash`, which signing the inputs does not change.
+    pub fn build_with_utxos<F>(&self, is_own: F) -> Result<(PrivateTransaction, Vec<UTXO>)>
+    where
+        F: Fn(&[u8]) -> bool,
+    {
+        let tx = self.build()?;
+        let tx_hash = tx.signing_hash()?;
+        
+        let utxos = self.outputs.iter()
+            .enumerate()
+            .filter(|(_, output)| is_own(&output.address))
+            .map(|(i, output)| UTXO::new(
+                tx_hash,
+                i as u32,
+                output.value,
+                output.commitment.clone(),
+                output.blinding,
+                output.address.clone(),
+            ))
+            .collect();
+        
+        Ok((tx, utxos))
+    }
+    
     /// Calculate required blinding factor for change output
     /// 
     /// To maintain balance: sum(input_blindings) = sum(output_blindings)
     /// Change blinding = sum(input_blindings) - sum(other_output_blindings)
     pub fn calculate_change_blinding(&self) -> Scalar {
+        self.blinding_imbalance()
+    }
+    
+    /// Residual `sum(input_blindings) - sum(output_blindings)`
+    ///
+    /// Zero once the outputs balance the inputs' blinding factors.
+    pub fn blinding_imbalance(&self) -> Scalar {
         let input_sum: Scalar = self.inputs.iter()
             .fold(Scalar::ZERO, |acc, i| acc + i.blinding);
         
@@ -293,6 +968,59 @@ impl TransactionBuilder {
         input_sum - output_sum
     }
     
+    /// Whether the blinding factors of inputs and outputs balance
+    pub fn is_blinding_balanced(&self) -> bool {
+        self.blinding_imbalance() == Scalar::ZERO
+    }
+    
+    /// Build a transaction splitting one UTXO into several outputs
+    ///
+    /// The outputs go back to the UTXO's address and must sum exactly to its
+    /// value (no fee). The last output takes the balancing blinding factor.
+    pub fn split_output(utxo: UTXO, amounts: &[u64]) -> Result<PrivateTransaction> {
+        let (last, rest) = amounts.split_last()
+            .ok_or_else(|| CoreError::InvalidParameter("No split amounts given".into()))?;
+        
+        let total = amounts.iter()
+            .try_fold(0u64, |acc, a| acc.checked_add(*a))
+            .ok_or_else(|| CoreError::InvalidParameter("Split amounts overflow".into()))?;
+        if total != utxo.value {
+            return Err(CoreError::InvalidParameter(
+                format!("Split amounts sum to {}, UTXO holds {}", total, utxo.value)
+            ));
+        }
+        
+        let mut builder = TransactionBuilder::new();
+        builder.add_input(utxo.tx_hash, utxo.output_index, utxo.value, utxo.blinding);
+        for amount in rest {
+            builder.add_output(utxo.address.clone(), *amount)?;
+        }
+        let change_blinding = builder.calculate_change_blinding();
+        builder.add_output_with_blinding(utxo.address, *last, change_blinding)?;
+        
+        builder.build()
+    }
+    
+    /// Build a transaction consolidating several UTXOs into one output (no fee)
+    pub fn merge_outputs(utxos: &[UTXO], address: Vec<u8>) -> Result<PrivateTransaction> {
+        if utxos.is_empty() {
+            return Err(CoreError::InvalidParameter("No UTXOs to merge".into()));
+        }
+        
+        let total = utxos.iter()
+            .try_fold(0u64, |acc, u| acc.checked_add(u.value))
+            .ok_or_else(|| CoreError::InvalidParameter("UTXO values overflow".into()))?;
+        
+        let mut builder = TransactionBuilder::new();
+        for utxo in utxos {
+            builder.add_input(utxo.tx_hash, utxo.output_index, utxo.value, utxo.blinding);
+        }
+        let blinding = builder.calculate_change_blinding();
+        builder.add_output_with_blinding(address, total, blinding)?;
+        
+        builder.build()
+    }
+    
     /// Estimate transaction size (for fee calculation)
     pub fn estimate_size(&self) -> usize {
         // Rough estimate:
@@ -301,13 +1029,79 @@ impl TransactionBuilder {
         // - Each range proof: ~650 bytes (Bulletproofs)
         // - Overhead: ~50 bytes
         
-        let input_size = self.inputs.len() * 150;
-        let output_size = self.outputs.len() * 100;
-        let proof_size = self.outputs.len() * 650;
+        Self::estimate_size_for(self.inputs.len(), self.outputs.len())
+    }
+    
+    /// Estimated size of a transaction with the given input and output counts
+    fn estimate_size_for(inputs: usize, outputs: usize) -> usize {
+        let input_size = inputs * 150;
+        let output_size = outputs * 100;
+        let proof_size = outputs * 650;
         let overhead = 50;
         
         input_size + output_size + proof_size + overhead
     }
+    
+    /// Select inputs from `utxos` to pay `target` plus a size-based fee
+    ///
+    /// `target` is the value of the outputs already added. UTXOs are taken
+    /// largest first; the fee is `fee_rate` per estimated byte, and more
+    /// inputs are added whenever the fee they cost pushes the total over the
+    /// selection. The fee counts a change output only when there is change
+    /// left to pay into it; a remainder too small to cover that output is
+    /// added to the fee instead. Any change returns to the first selected
+    /// UTXO's address with the balancing blinding factor, so add all payment
+    /// outputs first. Without change the last output's blinding factor is
+    /// adjusted to balance instead. Returns the fee.
+    pub fn fund_with_auto_fee(&mut self, utxos: &[UTXO], target: u64, fee_rate: u64) -> Result<u64> {
+        let mut candidates: Vec<&UTXO> = utxos.iter().collect();
+        candidates.sort_by_key(|u| std::cmp::Reverse(u.value));
+        
+        let fee_for = |inputs: usize, outputs: usize| {
+            (Self::estimate_size_for(inputs, outputs) as u64)
+                .checked_mul(fee_rate)
+                .ok_or_else(|| CoreError::InvalidParameter("Fee overflows".into()))
+        };
+        let mut selected = 0;
+        let mut selected_value = 0u64;
+        
+        let fee = loop {
+            let inputs = self.inputs.len() + selected;
+            let fee = fee_for(inputs, self.outputs.len())?;
+            let needed = target.checked_add(fee)
+                .ok_or_else(|| CoreError::InvalidParameter("Target plus fee overflows".into()))?;
+            
+            if selected > 0 && selected_value >= needed {
+                let fee_with_change = fee_for(inputs, self.outputs.len() + 1)?;
+                break match target.checked_add(fee_with_change) {
+                    Some(spent) if selected_value > spent => fee_with_change,
+                    _ => selected_value - target,
+                };
+            }
+            
+            let next = candidates.get(selected).ok_or_else(|| CoreError::InvalidParameter(format!(
+                "Insufficient funds: need {} including fee {}, have {}",
+                needed, fee, selected_value
+            )))?;
+            selected_value = selected_value.checked_add(next.value)
+                .ok_or_else(|| CoreError::InvalidParameter("UTXO values overflow".into()))?;
+            selected += 1;
+        };
+        
+        for utxo in &candidates[..selected] {
+            self.add_input(utxo.tx_hash, utxo.output_index, utxo.value, utxo.blinding);
+        }
+        self.set_fee(fee);
+        
+        if selected_value - target > fee {
+            self.add_change_output(candidates[0].address.clone())?;
+        } else if let Some(last) = self.outputs.pop() {
+            let blinding = self.blinding_imbalance();
+            self.add_output_with_blinding(last.address, last.value, blinding)?;
+        }
+        
+        Ok(fee)
+    }
 }
 
 impl Default for TransactionBuilder {
@@ -325,6 +1119,13 @@ pub struct UTXO {
     pub commitment: Commitment,
     pub blinding: Scalar,
     pub address: Vec<u8>,
+    /// Height of the coinbase transaction that created this output
+    ///
+    /// `serde(default)` lets self-describing formats such as JSON read UTXOs
+    /// written before this field existed. Bincode is not self-describing, so
+    /// older bincode-encoded UTXOs fail to decode rather than default.
+    #[serde(default)]
+    pub coinbase_height: Option<u64>,
 }
 
 impl UTXO {
@@ -344,15 +1145,73 @@ impl UTXO {
             commitment,
             blinding,
             address,
+            coinbase_height: None,
         }
     }
+    
+    /// Mark this UTXO as a coinbase output mined at `height`
+    pub fn with_coinbase_height(mut self, height: u64) -> Self {
+        self.coinbase_height = Some(height);
+        self
+    }
+    
+    /// Whether this output can be spent at `current_height`
+    ///
+    /// Outputs that are not coinbase are always mature.
+    pub fn is_mature(&self, current_height: u64) -> bool {
+        is_mature(self.coinbase_height, current_height)
+    }
+}
+
+/// Whether an output mined as coinbase at `coinbase_height`, if at all,
+/// can be spent at `current_height`
+pub(crate) fn is_mature(coinbase_height: Option<u64>, current_height: u64) -> bool {
+    match coinbase_height {
+        Some(height) => current_height.saturating_sub(height) >= COINBASE_MATURITY,
+        None => true,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crypto::merkle::MerkleTree;
     use crate::random_scalar;
     
+    fn spend_of(commitments: &[Commitment]) -> PrivateTransaction {
+        PrivateTransaction {
+            inputs: commitments.iter().map(|c| TransactionInput {
+                prev_tx_hash: [0u8; 32],
+                prev_output_index: 0,
+                commitment: c.clone(),
+                signature: vec![],
+            }).collect(),
+            outputs: vec![],
+            range_proofs: vec![],
+            fee: 0,
+            metadata: None,
+            range_proof_scheme: RangeProofKind::default(),
+            input_proofs: vec![],
+        }
+    }
+    
+    #[test]
+    fn test_inputs_in_anchored_set() {
+        let pc = PedersenCommitment::new();
+        let set: Vec<Commitment> = (0..6).map(|v| pc.commit(v, &random_scalar())).collect();
+        let tree = MerkleTree::new(&set.iter().map(Commitment::to_bytes).collect::<Vec<_>>());
+        let anchor = tree.root();
+        
+        let tx = spend_of(&[set[1].clone(), set[4].clone()]);
+        let proofs = vec![tree.proof(1).unwrap(), tree.proof(4).unwrap()];
+        assert!(tx.verify_inputs_in_set(anchor, &proofs));
+        assert!(!tx.verify_inputs_in_set(anchor, &proofs[..1]));
+        
+        // A forged input is not in the set, even with a real member's proof
+        let forged = spend_of(&[set[1].clone(), pc.commit(4, &random_scalar())]);
+        assert!(!forged.verify_inputs_in_set(anchor, &proofs));
+    }
+    
     #[test]
     fn test_simple_transaction() {
         let mut builder = TransactionBuilder::new();
@@ -362,7 +1221,7 @@ mod tests {
         builder.add_input([0u8; 32], 0, 100, input_blinding);
         
         // Output: 80 coins to recipient (random blinding)
-        builder.add_output(vec![1, 2, 3, 4], 80);
+        builder.add_output(vec![1, 2, 3, 4], 80).unwrap();
         
         // Change: 10 coins back to sender (needs calculated blinding for balance)
         // First calculate what blinding we need
@@ -430,7 +1289,7 @@ mod tests {
         builder.add_input([0u8; 32], 0, 100, blinding);
         
         // Output more than input (should fail)
-        builder.add_output(vec![1, 2, 3, 4], 150);
+        builder.add_output(vec![1, 2, 3, 4], 150).unwrap();
         builder.set_fee(0);
         
         let result = builder.build();
@@ -443,7 +1302,7 @@ mod tests {
         
         let blinding = random_scalar();
         builder.add_input([0u8; 32], 0, 100, blinding);
-        builder.add_output(vec![1, 2, 3, 4], 90);
+        builder.add_output(vec![1, 2, 3, 4], 90).unwrap();
         builder.set_fee(10);
         
         let tx = builder.build().unwrap();
@@ -457,13 +1316,90 @@ mod tests {
         assert_eq!(tx.fee, deserialized.fee);
     }
     
+    #[test]
+    fn test_transaction_bytes_are_versioned() {
+        let tx = well_formed_transaction();
+        let bytes = tx.to_bytes().unwrap();
+        assert_eq!(&bytes[..TX_HEADER_LEN], b"ZTX\x01");
+        
+        // Layout written before the header existed
+        #[derive(Serialize)]
+        struct Legacy<'a> {
+            inputs: &'a [TransactionInput],
+            outputs: &'a [TransactionOutput],
+            range_proofs: &'a [RangeProof],
+            fee: u64,
+            metadata: &'a Option<Vec<u8>>,
+        }
+        let legacy = bincode::serialize(&Legacy {
+            inputs: &tx.inputs,
+            outputs: &tx.outputs,
+            range_proofs: &tx.range_proofs,
+            fee: tx.fee,
+            metadata: &tx.metadata,
+        }).unwrap();
+        let decoded = PrivateTransaction::from_bytes(&legacy).unwrap();
+        assert_eq!(decoded.range_proof_scheme, RangeProofKind::Bulletproofs);
+        assert_eq!(decoded.to_bytes().unwrap(), bytes);
+        assert!(decoded.verify().unwrap());
+        
+        // Both layouts must be consumed exactly
+        for encoded in [&bytes, &legacy] {
+            let truncated = &encoded[..encoded.len() - 1];
+            assert!(matches!(PrivateTransaction::from_bytes(truncated), Err(CoreError::Serialization(_))));
+            let mut extended = encoded.clone();
+            extended.push(0);
+            assert!(matches!(PrivateTransaction::from_bytes(&extended), Err(CoreError::Serialization(_))));
+        }
+        
+        let mut future = bytes.clone();
+        future[TX_MAGIC.len()] = TX_FORMAT_VERSION + 1;
+        assert!(matches!(PrivateTransaction::from_bytes(&future), Err(CoreError::Serialization(_))));
+        assert!(PrivateTransaction::from_bytes(&TX_MAGIC).is_err());
+    }
+    
+    #[test]
+    fn test_serialized_size_matches_bytes() {
+        let mut builder = TransactionBuilder::new();
+        builder.add_input([1u8; 32], 0, 100, random_scalar());
+        builder.add_output(vec![1u8; 32], 60).unwrap();
+        builder.set_fee(5).add_change_output(vec![2u8; 20]).unwrap();
+        let plain = builder.build().unwrap();
+        
+        builder.set_metadata(b"memo".to_vec()).set_prove_inputs(true);
+        let with_extras = builder.build().unwrap();
+        
+        let mut signed = plain.clone();
+        signed.inputs[0].signature = vec![7u8; 64];
+        
+        let merged = builder_single(300).merge(&[builder_single(200)]).unwrap();
+        
+        for tx in [&plain, &with_extras, &signed, &merged] {
+            assert_eq!(tx.serialized_size().unwrap(), tx.to_bytes().unwrap().len());
+        }
+        
+        let size = signed.serialized_size().unwrap() as u64;
+        assert!(!signed.pays_fee_rate(1).unwrap());
+        signed.fee = size;
+        assert!(signed.pays_fee_rate(1).unwrap());
+        assert!(!signed.pays_fee_rate(2).unwrap());
+    }
+    
+    /// One input paying all of `value` to one output
+    fn builder_single(value: u64) -> PrivateTransaction {
+        let mut builder = TransactionBuilder::new();
+        builder.add_input([value as u8; 32], 0, value, random_scalar());
+        builder.add_change_output(vec![3u8; 32]).unwrap();
+        builder.build().unwrap()
+    }
+    
     #[test]
     fn test_estimate_size() {
         let mut builder = TransactionBuilder::new();
         
         let blinding = random_scalar();
         builder.add_input([0u8; 32], 0, 100, blinding);
-        builder.add_output(vec![1, 2, 3, 4], 90);
+        builder.add_output(vec![1, 2, 3, 4], 90).unwrap();
         builder.set_fee(10);
         
         let size = builder.estimate_size();
@@ -472,6 +1408,95 @@ mod tests {
         assert!(size > 800 && size < 1100);
     }
     
+    #[test]
+    fn test_add_change_output() {
+        let mut builder = TransactionBuilder::new();
+        builder.add_input([1u8; 32], 0, 100, random_scalar());
+        builder.add_output(vec![2u8; 32], 60).unwrap();
+        builder.set_fee(5);
+        builder.add_change_output("bc1qchange".to_string()).unwrap();
+        
+        assert_eq!(builder.outputs[1].value, 35);
+        assert_eq!(builder.outputs[1].address, b"bc1qchange");
+        assert!(builder.build().unwrap().verify().unwrap());
+        
+        builder.set_fee(50);
+        assert!(builder.add_change_output(vec![3u8; 32]).is_err());
+    }
+    
+    #[test]
+    fn test_build_with_utxos_returns_spendable_change() {
+        let mut builder = TransactionBuilder::new();
+        builder.add_input([1u8; 32], 0, 100, random_scalar());
+        builder.add_output(vec![2u8; 32], 60).unwrap();
+        builder.set_fee(5);
+        builder.add_change_output("bc1qchange".to_string()).unwrap();
+        
+        let (tx, utxos) = builder.build_with_utxos(|address| address == b"bc1qchange").unwrap();
+        assert_eq!(utxos.len(), 1);
+        let change = &utxos[0];
+        assert_eq!(change.value, 35);
+        assert_eq!(change.tx_hash, tx.signing_hash().unwrap());
+        assert_eq!(change.commitment.point, tx.outputs[change.output_index as usize].commitment.point);
+        assert!(PedersenCommitment::new().verify_opening(&change.commitment, change.value, &change.blinding));
+        
+        // The change can fund a follow-up transaction
+        let mut next = TransactionBuilder::new();
+        next.add_input(change.tx_hash, change.output_index, change.value, change.blinding);
+        let blinding = next.calculate_change_blinding();
+        next.add_output_with_blinding(vec![3u8; 32], 30, blinding).unwrap();
+        next.set_fee(5);
+        assert!(next.build().unwrap().verify().unwrap());
+    }
+    
+    #[test]
+    fn test_fund_with_auto_fee_reselects() {
+        let utxos: Vec<UTXO> = [2000, 5000, 2000].iter().enumerate()
+            .map(|(i, value)| test_utxo(i as u8, *value))
+            .collect();
+        
+        // 5000 covers the 4200 payment but not the 950 fee of a one-input spend
+        let mut builder = TransactionBuilder::new();
+        builder.add_output(vec![1u8; 32], 4200).unwrap();
+        let fee = builder.fund_with_auto_fee(&utxos, 4200, 1).unwrap();
+        
+        assert_eq!(builder.inputs.len(), 2);
+        assert_eq!(fee, TransactionBuilder::estimate_size_for(2, 2) as u64);
+        assert_eq!(builder.outputs[1].value, 7000 - 4200 - fee);
+        assert_eq!(builder.estimate_size() as u64, fee);
+        assert!(builder.build().unwrap().verify().unwrap());
+        
+        // Three inputs hold 9000, short of 7800 plus their 1250 fee
+        let mut builder = TransactionBuilder::new();
+        builder.add_output(vec![1u8; 32], 7800).unwrap();
+        assert!(matches!(
+            builder.fund_with_auto_fee(&utxos, 7800, 1),
+            Err(CoreError::InvalidParameter(_))
+        ));
+    }
+    
+    #[test]
+    fn test_fund_with_auto_fee_counts_change_only_when_paid() {
+        let utxos = vec![test_utxo(0, 5000)];
+        let fee_without_change = TransactionBuilder::estimate_size_for(1, 1) as u64;
+        
+        // An exact fit pays the one-output fee and adds no change
+        let target = 5000 - fee_without_change;
+        let mut builder = TransactionBuilder::new();
+        builder.add_output(vec![1u8; 32], target).unwrap();
+        assert_eq!(builder.fund_with_auto_fee(&utxos, target, 1).unwrap(), fee_without_change);
+        assert_eq!(builder.outputs.len(), 1);
+        assert!(builder.build().unwrap().verify().unwrap());
+        
+        // A remainder too small to pay for a change output goes to the fee
+        let target = 4000;
+        let mut builder = TransactionBuilder::new();
+        builder.add_output(vec![1u8; 32], target).unwrap();
+        assert_eq!(builder.fund_with_auto_fee(&utxos, target, 1).unwrap(), 1000);
+        assert_eq!(builder.outputs.len(), 1);
+        assert!(builder.build().unwrap().verify().unwrap());
+    }
+    
     #[test]
     fn test_change_blinding_calculation() {
         let mut builder = TransactionBuilder::new();
@@ -480,7 +1505,7 @@ mod tests {
         builder.add_input([0u8; 32], 0, 100, input_blinding);
         
         // Add first output - this will have a random blinding
-        builder.add_output(vec![1, 2, 3, 4], 90);
+        builder.add_output(vec![1, 2, 3, 4], 90).unwrap();
         
         // Calculate what the change blinding should be to balance
         let change_blinding = builder.calculate_change_blinding();
@@ -500,4 +1525,496 @@ mod tests {
         let tx = builder.build().unwrap();
         assert!(tx.verify().unwrap());
     }
+    
+    #[test]
+    fn test_merge_partial_transactions() {
+        // Participant A: 100 in, 95 out, fee 5 (blindings don't cancel alone)
+        let blinding_a = random_scalar();
+        let mut builder_a = TransactionBuilder::new();
+        builder_a.add_input([1u8; 32], 0, 100, blinding_a);
+        builder_a.add_output(vec![1, 2, 3, 4], 95).unwrap();
+        builder_a.set_fee(5);
+        let part_a = builder_a.build().unwrap();
+        
+        // Participant B: 50 in, 45 out, fee 5, output blinding chosen so that
+        // all blindings across both parts cancel
+        let blinding_b = random_scalar();
+        let mut builder_b = TransactionBuilder::new();
+        builder_b.add_input([2u8; 32], 0, 50, blinding_b);
+        let joint_blinding = blinding_a + blinding_b - builder_a.outputs[0].blinding;
+        builder_b.add_output_with_blinding(vec![5, 6, 7, 8], 45, joint_blinding).unwrap();
+        builder_b.set_fee(5);
+        let part_b = builder_b.build().unwrap();
+        
+        assert!(!part_a.verify().unwrap());
+        assert!(!part_b.verify().unwrap());
+        
+        let merged = part_a.merge(std::slice::from_ref(&part_b)).unwrap();
+        
+        assert_eq!(merged.inputs.len(), 2);
+        assert_eq!(merged.outputs.len(), 2);
+        assert_eq!(merged.range_proofs.len(), 2);
+        assert_eq!(merged.fee, 10);
+        assert!(merged.verify().unwrap());
+        
+        // A part merged with itself does not balance
+        assert!(part_b.merge(std::slice::from_ref(&part_b)).is_err());
+    }
+    
+    fn well_formed_transaction() -> PrivateTransaction {
+        let mut builder = TransactionBuilder::new();
+        let blinding = random_scalar();
+        builder.add_input([1u8; 32], 0, 100, blinding);
+        let change_blinding = builder.calculate_change_blinding();
+        builder.add_output_with_blinding(vec![2u8; 32], 90, change_blinding).unwrap();
+        builder.set_fee(10);
+        builder.build().unwrap()
+    }
+    
+    #[test]
+    fn test_input_proofs_verify_and_reject_forgeries() {
+        let mut builder = TransactionBuilder::new();
+        builder.add_input([1u8; 32], 0, 100, random_scalar());
+        builder.add_input([1u8; 32], 1, 50, random_scalar());
+        let change_blinding = builder.calculate_change_blinding();
+        builder.add_output_with_blinding(vec![2u8; 32], 140, change_blinding).unwrap();
+        builder.set_fee(10);
+        builder.set_prove_inputs(true);
+        let tx = builder.build().unwrap();
+        assert_eq!(tx.input_proofs.len(), 2);
+        assert!(tx.verify().unwrap());
+        
+        // Without the opening, a spender can only guess
+        let pedersen = PedersenCommitment::new();
+        let mut forged = tx.clone();
+        forged.input_proofs[0] = InputProof::prove(&pedersen, &tx.inputs[0], 100, &random_scalar());
+        assert!(!forged.verify().unwrap());
+        
+        // A valid proof does not transfer to another input
+        let mut swapped = tx.clone();
+        swapped.input_proofs.swap(0, 1);
+        assert!(!swapped.verify().unwrap());
+        
+        let mut partial = tx.clone();
+        partial.input_proofs.pop();
+        assert!(matches!(partial.verify(), Err(CoreError::InvalidTransaction(_))));
+        
+        let strict = VerifyOptions { require_input_proofs: true, ..VerifyOptions::default() };
+        assert!(tx.verify_with_options(&strict).unwrap());
+        let mut unproven = tx;
+        unproven.input_proofs.clear();
+        assert!(unproven.verify().unwrap());
+        assert!(matches!(unproven.verify_with_options(&strict), Err(CoreError::InvalidTransaction(_))));
+    }
+    
+    #[test]
+    fn test_bytes_without_input_proofs_decode() {
+        let tx = well_formed_transaction();
+        
+        // Unversioned layout with the scheme but before input proofs
+        #[derive(Serialize)]
+        struct WithoutInputProofs<'a> {
+            inputs: &'a [TransactionInput],
+            outputs: &'a [TransactionOutput],
+            range_proofs: &'a [RangeProof],
+            fee: u64,
+            metadata: &'a Option<Vec<u8>>,
+            range_proof_scheme: RangeProofKind,
+        }
+        let old = bincode::serialize(&WithoutInputProofs {
+            inputs: &tx.inputs,
+            outputs: &tx.outputs,
+            range_proofs: &tx.range_proofs,
+            fee: tx.fee,
+            metadata: &tx.metadata,
+            range_proof_scheme: tx.range_proof_scheme,
+        }).unwrap();
+        
+        let decoded = PrivateTransaction::from_bytes(&old).unwrap();
+        assert!(decoded.input_proofs.is_empty());
+        assert_eq!(decoded.inputs.len(), tx.inputs.len());
+        assert!(decoded.verify().unwrap());
+    }
+    
+    #[test]
+    fn test_validate_structure_rejects_missing_parts() {
+        let mut no_inputs = well_formed_transaction();
+        no_inputs.inputs.clear();
+        assert!(matches!(no_inputs.verify(), Err(CoreError::InvalidTransaction(_))));
+        
+        let mut no_outputs = well_formed_transaction();
+        no_outputs.outputs.clear();
+        no_outputs.range_proofs.clear();
+        assert!(matches!(no_outputs.validate_structure(), Err(CoreError::InvalidTransaction(_))));
+        
+        let mut missing_proof = well_formed_transaction();
+        missing_proof.range_proofs.clear();
+        assert!(matches!(missing_proof.validate_structure(), Err(CoreError::InvalidTransaction(_))));
+    }
+    
+    #[test]
+    fn test_range_proof_errors_are_distinguished() {
+        let blinding = random_scalar();
+        assert!(matches!(RangeProof::prove(1 << 32, &blinding, 32), Err(CoreError::RangeProof(_))));
+        assert!(matches!(RangeProof::prove(1, &blinding, 65), Err(CoreError::RangeProof(_))));
+        assert!(RangeProof::prove((1 << 32) - 1, &blinding, 32).is_ok());
+        
+        let mut bad_bit_length = well_formed_transaction();
+        bad_bit_length.range_proofs[0].bit_length = 0;
+        assert!(matches!(bad_bit_length.verify(), Err(CoreError::RangeProof(_))));
+    }
+    
+    #[test]
+    fn test_validate_structure_rejects_bad_fields() {
+        let tx = well_formed_transaction();
+        assert!(tx.validate_structure().is_ok());
+        
+        let mut empty_address = tx.clone();
+        empty_address.outputs[0].address.clear();
+        assert!(matches!(empty_address.validate_structure(), Err(CoreError::InvalidTransaction(_))));
+        
+        let mut huge_fee = tx.clone();
+        huge_fee.fee = MAX_FEE + 1;
+        assert!(matches!(huge_fee.validate_structure(), Err(CoreError::InvalidTransaction(_))));
+        
+        // A fee-less transaction without inputs is a coinbase
+        let mut coinbase = tx;
+        coinbase.inputs.clear();
+        coinbase.fee = 0;
+        assert!(coinbase.validate_structure().is_ok());
+    }
+    
+    #[test]
+    fn test_out_of_range_proof_index_is_an_error() {
+        let mut tx = well_formed_transaction();
+        tx.range_proofs[0].output_index = 5;
+        
+        let batched = VerifyOptions { batch_threshold: 1, ..VerifyOptions::default() };
+        assert!(matches!(tx.verify(), Err(CoreError::InvalidTransaction(_))));
+        assert!(matches!(tx.verify_fast(), Err(CoreError::InvalidTransaction(_))));
+        assert!(matches!(tx.verify_with_options(&batched), Err(CoreError::InvalidTransaction(_))));
+    }
+    
+    #[test]
+    fn test_coinbase_balances_against_minted_amount() {
+        let pedersen = PedersenCommitment::new();
+        let blinding = random_scalar();
+        let mut coinbase = well_formed_transaction();
+        coinbase.inputs.clear();
+        coinbase.input_proofs.clear();
+        coinbase.fee = 0;
+        coinbase.outputs = [(60u64, blinding), (40, -blinding)].iter().map(|(value, r)| TransactionOutput {
+            address: vec![2u8; 32],
+            commitment: pedersen.commit(*value, r),
+            encrypted_amount: None,
+        }).collect();
+        coinbase.range_proofs = [(60u64, blinding), (40, -blinding)].iter().enumerate().map(|(i, (value, r))| {
+            let mut proof = RangeProof::prove(*value, r, 64).unwrap();
+            proof.output_index = i as u32;
+            proof
+        }).collect();
+        
+        assert!(coinbase.verify_coinbase(100).unwrap());
+        assert!(!coinbase.verify_coinbase(101).unwrap());
+        assert!(matches!(coinbase.verify(), Err(CoreError::InvalidTransaction(_))));
+        assert!(matches!(well_formed_transaction().verify_coinbase(100), Err(CoreError::InvalidTransaction(_))));
+    }
+    
+    #[test]
+    fn test_identity_output_rejected() {
+        // 0 with blinding 0 balances against a 0-valued input, and its
+        // range proof is valid, so only the identity check catches it
+        let mut builder = TransactionBuilder::new();
+        builder.add_input([1u8; 32], 0, 0, Scalar::ZERO);
+        builder.add_output_with_blinding(vec![1u8; 32], 0, Scalar::ZERO).unwrap();
+        let tx = builder.build().unwrap();
+        assert!(tx.outputs[0].commitment.point.is_identity());
+        
+        assert!(matches!(tx.verify(), Err(CoreError::InvalidTransaction(_))));
+        assert!(matches!(tx.verify_fast(), Err(CoreError::InvalidTransaction(_))));
+        let permissive = VerifyOptions { reject_identity_outputs: false, ..VerifyOptions::default() };
+        assert!(tx.verify_with_options(&permissive).unwrap());
+    }
+    
+    #[test]
+    fn test_blinding_imbalance() {
+        let mut builder = TransactionBuilder::new();
+        let input_blinding = random_scalar();
+        builder.add_input([1u8; 32], 0, 100, input_blinding);
+        builder.add_output(vec![1u8; 32], 60).unwrap();
+        
+        assert!(!builder.is_blinding_balanced());
+        assert_eq!(builder.blinding_imbalance(), builder.calculate_change_blinding());
+        
+        let change_blinding = builder.calculate_change_blinding();
+        builder.add_output_with_blinding(vec![2u8; 32], 40, change_blinding).unwrap();
+        
+        assert_eq!(builder.blinding_imbalance(), Scalar::ZERO);
+        assert!(builder.is_blinding_balanced());
+        assert!(builder.build().unwrap().verify().unwrap());
+    }
+    
+    #[test]
+    fn test_verify_checks_balance_before_range_proofs() {
+        let input_blinding = random_scalar();
+        let mut builder = TransactionBuilder::new();
+        builder.add_input([1u8; 32], 0, 100, input_blinding);
+        builder.add_output(vec![1u8; 32], 40).unwrap();
+        let change_blinding = builder.calculate_change_blinding();
+        builder.add_output_with_blinding(vec![2u8; 32], 60, change_blinding).unwrap();
+        let mut tx = builder.build().unwrap();
+        
+        let calls = std::cell::Cell::new(0);
+        let rejecting = |_: &RangeProof, _: &Commitment| { calls.set(calls.get() + 1); false };
+        let no_batch = |_: &[RangeProof], _: &[Commitment]| -> bool { unreachable!() };
+        let individual = VerifyOptions { batch_threshold: usize::MAX, ..VerifyOptions::default() };
+        assert!(!tx.verify_with(true, &individual, None, rejecting, no_batch).unwrap());
+        assert_eq!(calls.get(), 1);
+        assert!(!tx.verify_with(false, &individual, None, rejecting, no_batch).unwrap());
+        assert_eq!(calls.get(), 3);
+        
+        // Unbalanced: rejected without any range proof work
+        tx.fee += 1;
+        calls.set(0);
+        let accepting = |_: &RangeProof, _: &Commitment| { calls.set(calls.get() + 1); true };
+        assert!(!tx.verify_with(true, &individual, None, accepting, no_batch).unwrap());
+        assert!(!tx.verify_fast().unwrap());
+        assert_eq!(calls.get(), 0);
+    }
+    
+    #[test]
+    fn test_batch_threshold_selects_verification_path() {
+        let build = |outputs: u64| {
+            let mut builder = TransactionBuilder::new();
+            builder.add_input([1u8; 32], 0, 100, random_scalar());
+            for i in 1..outputs {
+                builder.add_output(vec![i as u8; 32], 10).unwrap();
+            }
+            let change_blinding = builder.calculate_change_blinding();
+            builder.add_output_with_blinding(vec![0u8; 32], 100 - 10 * (outputs - 1), change_blinding).unwrap();
+            builder.build().unwrap()
+        };
+        
+        let single = std::cell::Cell::new(0);
+        let batched = std::cell::Cell::new(0);
+        let count_single = |_: &RangeProof, _: &Commitment| { single.set(single.get() + 1); true };
+        let count_batch = |proofs: &[RangeProof], _: &[Commitment]| { batched.set(batched.get() + proofs.len()); true };
+        let options = VerifyOptions::default();
+        
+        assert!(build(1).verify_with(false, &options, None, count_single, count_batch).unwrap());
+        assert_eq!((single.get(), batched.get()), (1, 0));
+        
+        let four = build(4);
+        assert!(four.verify_with(false, &options, None, count_single, count_batch).unwrap());
+        assert_eq!((single.get(), batched.get()), (1, 4));
+        assert!(four.verify_with_options(&VerifyOptions::default()).unwrap());
+        assert!(four.verify_with_options(&VerifyOptions { batch_threshold: 5, ..options }).unwrap());
+    }
+    
+    #[test]
+    fn test_max_value_rejects_large_outputs() {
+        let mut builder = TransactionBuilder::new();
+        builder.add_input([1u8; 32], 0, 5000, random_scalar());
+        builder.set_max_value(1000);
+        
+        assert!(builder.add_output(vec![1u8; 32], 1000).is_ok());
+        assert!(matches!(builder.add_output(vec![1u8; 32], 1001), Err(CoreError::InvalidParameter(_))));
+        
+        // Change is held to the same cap
+        assert!(builder.add_change_output(vec![2u8; 32]).is_err());
+        assert_eq!(builder.outputs.len(), 1);
+    }
+    
+    #[test]
+    fn test_seeded_shuffle_is_reproducible() {
+        let input_blinding = random_scalar();
+        let output_blindings: Vec<Scalar> = (0..5).map(|_| random_scalar()).collect();
+        
+        let build = |seed: [u8; 32]| {
+            let mut builder = TransactionBuilder::new();
+            builder.add_input([1u8; 32], 0, 150, input_blinding);
+            for (i, blinding) in output_blindings.iter().enumerate() {
+                builder.add_output_with_blinding(vec![i as u8; 32], 10 * (i as u64 + 1), *blinding).unwrap();
+            }
+            builder.shuffle_outputs_seeded(seed);
+            builder.build().unwrap()
+        };
+        
+        let addresses = |tx: &PrivateTransaction| -> Vec<Vec<u8>> {
+            tx.outputs.iter().map(|o| o.address.clone()).collect()
+        };
+        
+        let first = build([7u8; 32]);
+        let second = build([7u8; 32]);
+        assert_eq!(addresses(&first), addresses(&second));
+        assert_eq!(
+            first.range_proofs.iter().map(|p| p.proof_bytes.clone()).collect::<Vec<_>>(),
+            second.range_proofs.iter().map(|p| p.proof_bytes.clone()).collect::<Vec<_>>()
+        );
+        
+        // Proofs follow their outputs through the shuffle
+        for (output, proof) in first.outputs.iter().zip(&first.range_proofs) {
+            assert!(proof.verify(&output.commitment));
+        }
+        
+        let unshuffled: Vec<Vec<u8>> = (0..5).map(|i| vec![i as u8; 32]).collect();
+        let differs = (0..8u8).any(|b| addresses(&build([b; 32])) != unshuffled);
+        assert!(differs);
+    }
+    
+    fn test_utxo(tx_byte: u8, value: u64) -> UTXO {
+        let blinding = random_scalar();
+        let commitment = PedersenCommitment::new().commit(value, &blinding);
+        UTXO::new([tx_byte; 32], 0, value, commitment, blinding, vec![9u8; 32])
+    }
+    
+    #[test]
+    fn test_split_output_balances() {
+        let tx = TransactionBuilder::split_output(test_utxo(1, 100), &[60, 40]).unwrap();
+        
+        assert_eq!(tx.outputs.len(), 2);
+        assert_eq!(tx.range_proofs.len(), 2);
+        assert!(tx.verify().unwrap());
+        
+        assert!(TransactionBuilder::split_output(test_utxo(1, 100), &[60, 50]).is_err());
+    }
+    
+    #[test]
+    fn test_merge_outputs_balances() {
+        let utxos = vec![test_utxo(1, 30), test_utxo(2, 70)];
+        let tx = TransactionBuilder::merge_outputs(&utxos, vec![9u8; 32]).unwrap();
+        
+        assert_eq!(tx.inputs.len(), 2);
+        assert_eq!(tx.outputs.len(), 1);
+        assert!(tx.verify().unwrap());
+        
+        // The single output opens to the merged value
+        let blinding = utxos[0].blinding + utxos[1].blinding;
+        assert!(PedersenCommitment::new().verify_opening(&tx.outputs[0].commitment, 100, &blinding));
+    }
+    
+    #[test]
+    fn test_reordered_outputs_detected() {
+        let tx = TransactionBuilder::split_output(test_utxo(1, 100), &[60, 40]).unwrap();
+        assert!(tx.verify().unwrap());
+        
+        // Proofs follow their index, not their position
+        let mut proofs_shuffled = tx.clone();
+        proofs_shuffled.range_proofs.swap(0, 1);
+        assert!(proofs_shuffled.verify().unwrap());
+        
+        // Moving outputs without updating proof indices breaks the pairing
+        let mut outputs_shuffled = tx.clone();
+        outputs_shuffled.outputs.swap(0, 1);
+        assert!(!outputs_shuffled.verify().unwrap());
+        
+        let mut duplicate_index = tx;
+        duplicate_index.range_proofs[1].output_index = 0;
+        assert!(matches!(duplicate_index.verify(), Err(CoreError::InvalidTransaction(_))));
+    }
+    
+    #[test]
+    fn test_range_proofs_swapped_between_outputs_rejected() {
+        // Fixed openings so the pairing, not chance, decides the outcome
+        let blindings = [Scalar::from(3u64), Scalar::from(5u64)];
+        let mut builder = TransactionBuilder::new();
+        builder.add_input([1u8; 32], 0, 100, blindings[0] + blindings[1]);
+        builder.add_output_with_blinding(vec![2u8; 32], 60, blindings[0]).unwrap();
+        builder.add_output_with_blinding(vec![3u8; 32], 40, blindings[1]).unwrap();
+        let tx = builder.build().unwrap();
+        assert!(tx.verify().unwrap());
+        assert_eq!(
+            tx.range_proofs.iter().map(|proof| proof.output_index).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        
+        // Each proof now claims the other output
+        let mut retagged = tx.clone();
+        retagged.range_proofs[0].output_index = 1;
+        retagged.range_proofs[1].output_index = 0;
+        
+        // Each output index now carries the other output's proof
+        let mut swapped = tx;
+        let first = swapped.range_proofs[0].proof_bytes.clone();
+        swapped.range_proofs[0].proof_bytes = std::mem::replace(&mut swapped.range_proofs[1].proof_bytes, first);
+        
+        let batched = VerifyOptions { batch_threshold: 1, ..VerifyOptions::default() };
+        for tampered in [retagged, swapped] {
+            assert!(!tampered.verify().unwrap());
+            assert!(!tampered.verify_with_options(&batched).unwrap());
+        }
+    }
+    
+    #[test]
+    fn test_range_proof_scheme_recorded_in_header() {
+        let tx = well_formed_transaction();
+        assert_eq!(tx.range_proof_scheme, RangeProofKind::Bulletproofs);
+        
+        // Verifiers follow the header, so relabelling the proofs breaks them
+        let mut relabelled = PrivateTransaction::from_bytes(&tx.to_bytes().unwrap()).unwrap();
+        assert!(relabelled.verify().unwrap());
+        relabelled.range_proof_scheme = RangeProofKind::BulletproofsPlus;
+        let relabelled = PrivateTransaction::from_bytes(&relabelled.to_bytes().unwrap()).unwrap();
+        assert_eq!(relabelled.range_proof_scheme, RangeProofKind::BulletproofsPlus);
+        assert!(!relabelled.verify().unwrap());
+        
+        let mut builder = TransactionBuilder::new();
+        let blinding = random_scalar();
+        builder.add_input([1u8; 32], 0, 100, blinding);
+        builder.add_output_with_blinding(vec![2u8; 32], 100, blinding).unwrap();
+        builder.set_range_proof_scheme(RangeProofKind::BulletproofsPlus);
+        
+        #[cfg(feature = "bulletproofs-plus")]
+        {
+            let plus = PrivateTransaction::from_bytes(&builder.build().unwrap().to_bytes().unwrap()).unwrap();
+            assert_eq!(plus.range_proof_scheme, RangeProofKind::BulletproofsPlus);
+            assert!(plus.verify().unwrap());
+        }
+        #[cfg(not(feature = "bulletproofs-plus"))]
+        assert!(matches!(builder.build(), Err(CoreError::RangeProof(_))));
+    }
+    
+    #[test]
+    fn test_equal_outputs_score_higher_uniformity() {
+        let distinct = TransactionBuilder::split_output(test_utxo(1, 100), &[70, 30]).unwrap();
+        let equal = TransactionBuilder::split_output(test_utxo(1, 100), &[25, 25, 25, 25]).unwrap();
+        
+        let distinct_metrics = distinct.anonymity_metrics();
+        let equal_metrics = equal.anonymity_metrics();
+        assert!(distinct_metrics.output_uniformity < equal_metrics.output_uniformity);
+        assert_eq!(equal_metrics.equal_value_outputs, 4);
+        assert!(distinct_metrics.change_distinguishable);
+        assert!(!equal_metrics.change_distinguishable);
+        assert_eq!(equal_metrics.ring_size, None);
+        
+        // Values are not serialized, so a received transaction scores as all-distinct
+        let received = PrivateTransaction::from_bytes(&equal.to_bytes().unwrap()).unwrap();
+        assert_eq!(received.anonymity_metrics().equal_value_outputs, 1);
+    }
+    
+    #[test]
+    fn test_coinbase_spend_requires_maturity() {
+        let coinbase = test_utxo(1, 100).with_coinbase_height(500);
+        let tx = TransactionBuilder::split_output(coinbase.clone(), &[60, 40]).unwrap();
+        let spent = [coinbase];
+        
+        let immature = tx.validate_transaction(500 + COINBASE_MATURITY - 1, &spent);
+        assert!(matches!(immature, Err(CoreError::InvalidTransaction(_))));
+        assert!(tx.validate_transaction(500 + COINBASE_MATURITY, &spent).is_ok());
+        
+        // Ordinary outputs have no maturity period, but must be known
+        let ordinary = [test_utxo(1, 100)];
+        assert!(tx.validate_transaction(0, &ordinary).is_ok());
+        assert!(tx.validate_transaction(10_000, &[]).is_err());
+    }
+    
+    #[test]
+    fn test_utxo_json_without_coinbase_height() {
+        let mut json = serde_json::to_value(test_utxo(1, 100).with_coinbase_height(7)).unwrap();
+        json.as_object_mut().unwrap().remove("coinbase_height");
+        
+        let utxo: UTXO = serde_json::from_value(json).unwrap();
+        assert_eq!(utxo.coinbase_height, None);
+        assert!(utxo.is_mature(0));
+    }
 }
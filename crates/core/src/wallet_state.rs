@@ -4,13 +4,94 @@
 
 use crate::{
     CoreError, Result,
-    key_manager::{KeyManager, Account, CoinType},
-    storage::{EncryptedDb, StoredAccount, StoredTransaction},
-    crypto::stealth::{StealthMasterKey, StealthAddress},
+    clock::{Clock, SystemClock},
+    key_manager::{KeyManager, Account, CoinType, EXTERNAL_BRANCH, INTERNAL_BRANCH},
+    storage::{EncryptedDb, Lockout, MetadataKey, StoredAccount, StoredCommitmentProof, StoredTransaction, StealthOutput, check_amount},
+    crypto::{
+        argon2_derive_key, compress_point, decompress_point_slice, ChaCha20Cipher,
+        stealth::{StealthAddress, ViewKey},
+    },
+    commitments::{Commitment, PedersenCommitment, ReservesProof},
+    secret_registry::SecretRegistry,
+    partial_transaction::PartialTransaction,
+    transaction_builder::{self, PrivateTransaction, TransactionBuilder},
 };
+use bitcoin::secp256k1::PublicKey;
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT as G,
+    ristretto::RistrettoPoint,
+    scalar::Scalar,
+    traits::Identity,
+};
+use rand::RngCore;
 use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
 use std::path::Path;
-use zeroize::ZeroizeOnDrop;
+use std::sync::Arc;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+use subtle::ConstantTimeEq;
+
+/// How long a UTXO reservation lasts before it expires (seconds)
+pub const UTXO_RESERVATION_TTL_SECS: u64 = 600;
+
+/// Wrong passwords allowed before any backoff is imposed
+pub const PASSWORD_FREE_ATTEMPTS: u32 = 3;
+
+/// First backoff delay (seconds); doubles with each further failure
+pub const PASSWORD_BACKOFF_BASE_SECS: u64 = 30;
+
+/// Failures after which the wallet refuses attempts for a full cooldown
+pub const PASSWORD_MAX_ATTEMPTS: u32 = 10;
+
+/// Cooldown once `PASSWORD_MAX_ATTEMPTS` is reached (seconds)
+pub const PASSWORD_LOCKOUT_SECS: u64 = 24 * 60 * 60;
+
+/// Wrong PINs allowed before the PIN is discarded and the password is required
+pub const PIN_MAX_ATTEMPTS: u32 = 5;
+
+/// Shortest device secret `set_pin` accepts (bytes)
+pub const MIN_DEVICE_SECRET_LEN: usize = 32;
+
+/// `MetadataKey::MnemonicHashScheme` of salted Argon2 mnemonic hashes
+const MNEMONIC_HASH_ARGON2: &str = "argon2";
+
+/// How `add_account` and `discover_accounts` name new accounts
+#[derive(Clone)]
+pub enum NamingPolicy {
+    /// Template where `{index}` is the zero-based account index and
+    /// `{number}` is the index plus one
+    Template(String),
+    /// Name computed from the account index
+    Custom(Arc<dyn Fn(u32) -> String + Send + Sync>),
+}
+
+impl NamingPolicy {
+    /// Default name for the account at `index`
+    pub fn name_for(&self, index: u32) -> String {
+        match self {
+            NamingPolicy::Template(template) => template
+                .replace("{index}", &index.to_string())
+                .replace("{number}", &(index as u64 + 1).to_string()),
+            NamingPolicy::Custom(name) => name(index),
+        }
+    }
+}
+
+impl Default for NamingPolicy {
+    /// "Account 1", "Account 2", ... as `KeyManager::derive_account` names them
+    fn default() -> Self {
+        NamingPolicy::Template("Account {number}".into())
+    }
+}
+
+/// One-shot approval to sign a single transaction
+///
+/// Returned by `authorize_spend` and consumed by `sign_transaction`; it
+/// only matches the transaction its challenge was created for.
+#[derive(Debug)]
+pub struct SpendAuthorization {
+    challenge: [u8; 32],
+}
 
 /// Main wallet state manager
 pub struct WalletState {
@@ -18,6 +99,8 @@ pub struct WalletState {
     key_manager: KeyManager,
     accounts: Vec<Account>,
     current_account_index: u32,
+    pending_spend_challenge: Option<[u8; 32]>,
+    naming_policy: NamingPolicy,
 }
 
 impl WalletState {
@@ -27,13 +110,25 @@ impl WalletState {
         password: &str,
         mnemonic: &str,
     ) -> Result<Self> {
-        let db = EncryptedDb::new(db_path, password)?;
+        Self::new_wallet_with_clock(db_path, password, mnemonic, Arc::new(SystemClock))
+    }
+    
+    /// Create new wallet from mnemonic using the given time source
+    pub fn new_wallet_with_clock<P: AsRef<Path>>(
+        db_path: P,
+        password: &str,
+        mnemonic: &str,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self> {
+        let db = EncryptedDb::with_clock(db_path, password, clock)?;
         let key_manager = KeyManager::new_from_mnemonic(mnemonic)?;
         
         // Store wallet metadata
-        db.set_metadata("wallet_version", "1.0.0")?;
-        db.set_metadata("created_at", &chrono::Utc::now().to_rfc3339())?;
-        db.set_metadata("mnemonic_hash", &Self::hash_mnemonic(mnemonic))?;
+        let created_at = chrono::DateTime::<chrono::Utc>::from(db.clock().now());
+        db.set_meta(MetadataKey::WalletVersion, "1.0.0")?;
+        db.set_meta(MetadataKey::CreatedAt, &created_at.to_rfc3339())?;
+        Self::store_mnemonic_hash(&db, mnemonic)?;
+        Self::store_password_verifier(&db, password)?;
         
         // Derive first account
         let account = key_manager.derive_account(0)?;
@@ -56,6 +151,8 @@ impl WalletState {
             key_manager,
             accounts: vec![account],
             current_account_index: 0,
+            pending_spend_challenge: None,
+            naming_policy: NamingPolicy::default(),
         })
     }
     
@@ -65,16 +162,21 @@ impl WalletState {
         password: &str,
         mnemonic: &str,
     ) -> Result<Self> {
-        let db = EncryptedDb::new(db_path, password)?;
-        
-        // Verify mnemonic matches
-        let stored_hash = db.get_metadata("mnemonic_hash")?
-            .ok_or_else(|| CoreError::Storage("Wallet not initialized".into()))?;
+        Self::open_wallet_with_clock(db_path, password, mnemonic, Arc::new(SystemClock))
+    }
+    
+    /// Open existing wallet using the given time source
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "open_wallet", skip_all))]
+    pub fn open_wallet_with_clock<P: AsRef<Path>>(
+        db_path: P,
+        password: &str,
+        mnemonic: &str,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self> {
+        let db = Self::open_db(db_path.as_ref(), password, clock)?;
+        Self::verify_password(&db, password)?;
         
-        let provided_hash = Self::hash_mnemonic(mnemonic);
-        if stored_hash != provided_hash {
-            return Err(CoreError::InvalidMnemonic("Mnemonic mismatch".into()));
-        }
+        Self::verify_mnemonic(&db, mnemonic)?;
         
         let key_manager = KeyManager::new_from_mnemonic(mnemonic)?;
         
@@ -83,42 +185,423 @@ impl WalletState {
         let mut accounts = Vec::new();
         
         for stored in stored_accounts {
-            let account = key_manager.derive_account(stored.index)?;
+            let mut account = key_manager.derive_account(stored.index)?;
+            account.name = stored.name;
             accounts.push(account);
         }
         
-        // Default to first account if available
-        let current_account_index = if accounts.is_empty() { 0 } else { accounts[0].index };
+        // Restore the selected account, defaulting to the first one
+        let selected = db.get_meta(MetadataKey::CurrentAccount)?
+            .and_then(|index| index.parse::<u32>().ok())
+            .filter(|index| accounts.iter().any(|a| a.index == *index));
+        let current_account_index = match selected {
+            Some(index) => index,
+            None if accounts.is_empty() => 0,
+            None => accounts[0].index,
+        };
         
         Ok(WalletState {
             db,
             key_manager,
             accounts,
             current_account_index,
+            pending_spend_challenge: None,
+            naming_policy: NamingPolicy::default(),
+        })
+    }
+    
+    /// Copy a plaintext development wallet into a new encrypted database
+    ///
+    /// `password` must be the source wallet's password. The source is
+    /// only read; the destination must not exist yet and is removed again
+    /// if the migration fails.
+    pub fn migrate_to_encrypted<P: AsRef<Path>, Q: AsRef<Path>>(
+        plaintext_path: P,
+        encrypted_path: Q,
+        password: &str,
+    ) -> Result<()> {
+        let encrypted_path = encrypted_path.as_ref();
+        if encrypted_path.exists() {
+            return Err(CoreError::InvalidParameter("Migration destination already exists".into()));
+        }
+        
+        let migrated = (|| {
+            let mut db = EncryptedDb::new(encrypted_path, password)?;
+            db.import_plaintext(plaintext_path, password)?;
+            Self::verify_password(&db, password)
+        })();
+        
+        if migrated.is_err() {
+            let _ = std::fs::remove_file(encrypted_path);
+        }
+        migrated
+    }
+    
+    /// Change the wallet password
+    ///
+    /// The old password is checked with the same backoff as `open_wallet`.
+    /// Under `sqlcipher` the database file is re-encrypted first; the field
+    /// key, verifier and audit entry then change together or not at all.
+    pub fn change_password(&mut self, old_password: &str, new_password: &str) -> Result<()> {
+        Self::verify_password(&self.db, old_password)?;
+        let (salt, hash) = Self::password_verifier(new_password)?;
+        
+        self.db.rekey(new_password)?;
+        let verifier = [(MetadataKey::PasswordSalt, salt.as_str()), (MetadataKey::PasswordHash, hash.as_str())];
+        if let Err(e) = self.db.rewrap_field_key(new_password, &verifier) {
+            // Keep the file openable with the password the metadata matches
+            self.db.rekey(old_password)?;
+            return Err(e);
+        }
+        
+        Ok(())
+    }
+    
+    /// Compact the database and re-key it from `old_password` to `new_password`
+    ///
+    /// Writes a vacuumed copy with a fresh field key, checks that it opens
+    /// with `new_password`, then swaps it in place of the original; if the
+    /// swap fails the original is reopened. Free pages holding old data are
+    /// not carried over. Any PIN is cleared.
+    pub fn secure_compact(&mut self, old_password: &str, new_password: &str) -> Result<()> {
+        Self::verify_password(&self.db, old_password)?;
+        self.db.compact_rekeyed(old_password, new_password, |compacted| {
+            Self::store_password_verifier(compacted, new_password)
         })
     }
     
+    /// Allow `unlock_with_pin` to unlock the wallet with a short PIN
+    ///
+    /// The PIN wraps the same field key as the password, so the wallet
+    /// must be unlocked. A PIN alone is small enough to brute-force
+    /// offline, so the wrapping also needs `device_secret`: at least
+    /// `MIN_DEVICE_SECRET_LEN` random bytes the app keeps in the platform
+    /// keystore, never beside the wallet file. Setting a PIN resets its
+    /// failure count.
+    pub fn set_pin(&self, pin: &str, device_secret: &[u8]) -> Result<()> {
+        if pin.is_empty() {
+            return Err(CoreError::InvalidParameter("PIN must not be empty".into()));
+        }
+        if device_secret.len() < MIN_DEVICE_SECRET_LEN {
+            return Err(CoreError::InvalidParameter(format!(
+                "Device secret must be at least {} bytes", MIN_DEVICE_SECRET_LEN
+            )));
+        }
+        self.db.wrap_field_key_with_pin(&pin_secret(pin, device_secret))?;
+        self.db.set_meta(MetadataKey::PinFailedAttempts, "0")
+    }
+    
+    /// Lock the wallet until `unlock_with_pin` or `unlock_with_password`
+    ///
+    /// Forgets the field key, so secret columns are unreadable, and refuses
+    /// signing, key export and derivation with `CoreError::KeysLocked`.
+    pub fn lock(&mut self) {
+        self.db.lock_field_key();
+        self.key_manager.set_locked(true);
+    }
+    
+    /// Whether secret columns can currently be read
+    pub fn is_unlocked(&self) -> bool {
+        self.db.is_unlocked()
+    }
+    
+    /// Check that the audit log is intact
+    ///
+    /// Each entry hashes the one before it, so editing, deleting or
+    /// reordering entries breaks the chain.
+    pub fn verify_audit_chain(&self) -> Result<bool> {
+        self.db.verify_audit_chain()
+    }
+    
+    /// Zeroize every secret the wallet holds, for destroying it
+    ///
+    /// Unlike `lock`, this cannot be undone: the mnemonic, master key and
+    /// account keys are gone, so signing, key export and derivation fail
+    /// with `CoreError::SecretUnavailable`. Reopen the wallet to use it again.
+    pub fn wipe_secrets(&mut self) {
+        self.secret_registry().wipe_all();
+    }
+    
+    /// Every live value in the wallet holding secrets
+    fn secret_registry(&mut self) -> SecretRegistry<'_> {
+        let mut registry = SecretRegistry::default();
+        registry.register(&mut self.key_manager).register(&mut self.db);
+        for account in &mut self.accounts {
+            registry.register(account);
+        }
+        registry
+    }
+    
+    /// Whether the wallet database file is encrypted at rest
+    ///
+    /// Development builds without the `sqlcipher` feature return false;
+    /// apps can check this before trusting the wallet with real funds.
+    pub fn is_encrypted(&self) -> bool {
+        self.db.is_encrypted()
+    }
+    
+    /// Unlock with the PIN and device secret given to `set_pin`
+    ///
+    /// After `PIN_MAX_ATTEMPTS` wrong PINs the PIN wrapping is deleted and
+    /// only `unlock_with_password` can unlock the wallet.
+    pub fn unlock_with_pin(&mut self, pin: &str, device_secret: &[u8]) -> Result<()> {
+        if !self.db.has_pin()? {
+            return Err(CoreError::InvalidParameter("No PIN set; unlock with the password".into()));
+        }
+        
+        if self.db.unlock_field_key_with_pin(&pin_secret(pin, device_secret))? {
+            self.key_manager.set_locked(false);
+            return self.db.set_meta(MetadataKey::PinFailedAttempts, "0");
+        }
+        
+        let failures = Self::meta_number::<u32>(&self.db, MetadataKey::PinFailedAttempts)?.saturating_add(1);
+        if failures >= PIN_MAX_ATTEMPTS {
+            self.db.clear_pin()?;
+            return Err(CoreError::InvalidParameter("Too many incorrect PINs; unlock with the password".into()));
+        }
+        self.db.set_meta(MetadataKey::PinFailedAttempts, &failures.to_string())?;
+        
+        Err(CoreError::InvalidParameter("Incorrect PIN".into()))
+    }
+    
+    /// Unlock with the wallet password, subject to the usual backoff
+    pub fn unlock_with_password(&mut self, password: &str) -> Result<()> {
+        Self::verify_password(&self.db, password)?;
+        if !self.db.unlock_field_key(password)? {
+            return Err(CoreError::Crypto("Password does not unwrap the field key".into()));
+        }
+        self.key_manager.set_locked(false);
+        Ok(())
+    }
+    
+    /// Store a fresh salt and Argon2 verifier for `password`
+    fn store_password_verifier(db: &EncryptedDb, password: &str) -> Result<()> {
+        let (salt, hash) = Self::password_verifier(password)?;
+        db.set_metas(&[(MetadataKey::PasswordSalt, &salt), (MetadataKey::PasswordHash, &hash)])
+    }
+    
+    /// A fresh salt and Argon2 verifier for `password`, as hex
+    fn password_verifier(password: &str) -> Result<(String, String)> {
+        let mut salt = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let mut key = argon2_derive_key(password.as_bytes(), &salt)?;
+        let hash = hex::encode(key);
+        key.zeroize();
+        
+        Ok((hex::encode(salt), hash))
+    }
+    
+    /// Open the wallet database for an existing wallet
+    ///
+    /// Under `sqlcipher` a wrong password already fails here, so the
+    /// backoff is enforced and counted before the database is readable.
+    fn open_db(db_path: &Path, password: &str, clock: Arc<dyn Clock>) -> Result<EncryptedDb> {
+        if !cfg!(feature = "sqlcipher") {
+            return EncryptedDb::with_clock(db_path, password, clock);
+        }
+        
+        let now = clock.now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .map_err(|e| CoreError::Storage(format!("System clock is before unix epoch: {}", e)))?;
+        let lockout = EncryptedDb::read_lockout(db_path)?;
+        if now < lockout.locked_until {
+            return Err(CoreError::WalletLocked((lockout.locked_until - now) as u64));
+        }
+        
+        match EncryptedDb::with_clock(db_path, password, clock) {
+            Err(CoreError::InvalidParameter(message)) => {
+                EncryptedDb::write_lockout(db_path, &record_failed_password(lockout, now))?;
+                Err(CoreError::InvalidParameter(message))
+            }
+            result => result,
+        }
+    }
+    
+    /// Store a password verifier for a wallet created before passwords were verified
+    ///
+    /// Such wallets cannot be opened until migrated. The mnemonic must match
+    /// the wallet's, so only its owner can choose the password; a wallet
+    /// that already has a verifier is left alone.
+    pub fn migrate_password_verifier<P: AsRef<Path>>(db_path: P, password: &str, mnemonic: &str) -> Result<()> {
+        let db = Self::open_db(db_path.as_ref(), password, Arc::new(SystemClock))?;
+        if db.get_meta(MetadataKey::PasswordHash)?.is_some() {
+            return Err(CoreError::InvalidParameter("Wallet already has a password verifier".into()));
+        }
+        if !db.is_unlocked() {
+            return Err(CoreError::InvalidParameter("Incorrect password".into()));
+        }
+        Self::verify_mnemonic(&db, mnemonic)?;
+        
+        Self::store_password_verifier(&db, password)
+    }
+    
+    /// Check `password` against the stored verifier
+    ///
+    /// While a backoff is active no attempt is made. Each wrong password
+    /// increments the failure count and may extend the backoff; a correct
+    /// one resets both. Wallets without a verifier are refused; see
+    /// `migrate_password_verifier`.
+    fn verify_password(db: &EncryptedDb, password: &str) -> Result<()> {
+        let now = db.unix_now()?;
+        let lockout = db.lockout()?;
+        if now < lockout.locked_until {
+            return Err(CoreError::WalletLocked((lockout.locked_until - now) as u64));
+        }
+        
+        let (Some(salt), Some(expected)) = (
+            db.get_meta(MetadataKey::PasswordSalt)?,
+            db.get_meta(MetadataKey::PasswordHash)?,
+        ) else {
+            return Err(CoreError::Storage(
+                "Wallet has no password verifier; migrate it with migrate_password_verifier".into()
+            ));
+        };
+        
+        let salt = hex::decode(salt)
+            .map_err(|e| CoreError::Storage(format!("Corrupt password salt: {}", e)))?;
+        let expected = hex::decode(expected)
+            .map_err(|e| CoreError::Storage(format!("Corrupt password hash: {}", e)))?;
+        let mut key = argon2_derive_key(password.as_bytes(), &salt)?;
+        let matches: bool = key.as_slice().ct_eq(&expected).into();
+        key.zeroize();
+        
+        if !matches {
+            db.set_lockout(&record_failed_password(lockout, now))?;
+            return Err(CoreError::InvalidParameter("Incorrect password".into()));
+        }
+        
+        db.set_lockout(&Lockout::default())
+    }
+    
+    /// Read a numeric metadata value, defaulting to zero when unset
+    fn meta_number<T: std::str::FromStr + Default>(db: &EncryptedDb, key: MetadataKey) -> Result<T> {
+        match db.get_meta(key)? {
+            Some(value) => value.parse()
+                .map_err(|_| CoreError::Storage(format!("Corrupt {} value", key.as_str()))),
+            None => Ok(T::default()),
+        }
+    }
+    
+    /// Fingerprint identifying this wallet across devices
+    ///
+    /// A hash of the BIP32 master public key, so two devices can confirm
+    /// they hold the same wallet without revealing the seed.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        self.key_manager.fingerprint()
+    }
+    
+    /// Import an auditor token as a view-only scanner
+    ///
+    /// The resulting key detects payments to the exporting wallet but
+    /// cannot spend them.
+    pub fn import_auditor_token(token: &str) -> Result<ViewKey> {
+        ViewKey::from_token(token)
+    }
+    
     /// Generate new mnemonic for wallet creation
     pub fn generate_mnemonic() -> Result<String> {
         KeyManager::generate_mnemonic()
     }
     
-    /// Hash mnemonic for verification (not reversible)
-    fn hash_mnemonic(mnemonic: &str) -> String {
+    /// Legacy mnemonic verification hash: unsalted SHA-256
+    fn legacy_mnemonic_hash(mnemonic: &str) -> String {
         use sha2::{Sha256, Digest};
         let hash = Sha256::digest(mnemonic.as_bytes());
         hex::encode(hash)
     }
     
+    /// Store a salted Argon2 verification hash of `mnemonic`
+    fn store_mnemonic_hash(db: &EncryptedDb, mnemonic: &str) -> Result<()> {
+        let mut salt = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let mut key = argon2_derive_key(mnemonic.as_bytes(), &salt)?;
+        let hash = hex::encode(key);
+        key.zeroize();
+        
+        db.set_metas(&[
+            (MetadataKey::MnemonicSalt, &hex::encode(salt)),
+            (MetadataKey::MnemonicHash, &hash),
+            (MetadataKey::MnemonicHashScheme, MNEMONIC_HASH_ARGON2),
+        ])
+    }
+    
+    /// Check `mnemonic` against the stored hash
+    ///
+    /// Wallets without a recorded scheme hold the legacy SHA-256 hash,
+    /// which is replaced by an Argon2 hash once the mnemonic matches.
+    fn verify_mnemonic(db: &EncryptedDb, mnemonic: &str) -> Result<()> {
+        let stored_hash = db.get_meta(MetadataKey::MnemonicHash)?
+            .ok_or_else(|| CoreError::Storage("Wallet not initialized".into()))?;
+        let scheme = db.get_meta(MetadataKey::MnemonicHashScheme)?;
+        
+        let matches: bool = match scheme.as_deref() {
+            None => Self::legacy_mnemonic_hash(mnemonic).as_bytes().ct_eq(stored_hash.as_bytes()).into(),
+            Some(MNEMONIC_HASH_ARGON2) => {
+                let salt = db.get_meta(MetadataKey::MnemonicSalt)?
+                    .ok_or_else(|| CoreError::Storage("Missing mnemonic salt".into()))?;
+                let salt = hex::decode(salt)
+                    .map_err(|e| CoreError::Storage(format!("Corrupt mnemonic salt: {}", e)))?;
+                let mut key = argon2_derive_key(mnemonic.as_bytes(), &salt)?;
+                let hash = Zeroizing::new(hex::encode(key));
+                key.zeroize();
+                hash.as_bytes().ct_eq(stored_hash.as_bytes()).into()
+            }
+            Some(other) => {
+                return Err(CoreError::Storage(format!("Unknown mnemonic hash scheme {}", other)));
+            }
+        };
+        
+        if !matches {
+            return Err(CoreError::InvalidMnemonic("Mnemonic mismatch".into()));
+        }
+        if scheme.is_none() {
+            Self::store_mnemonic_hash(db, mnemonic)?;
+        }
+        Ok(())
+    }
+    
     /// Add new account to wallet
     pub fn add_account(&mut self, name: Option<String>) -> Result<&Account> {
         let next_index = self.accounts.len() as u32;
         let mut account = self.key_manager.derive_account(next_index)?;
+        account.name = name.unwrap_or_else(|| self.naming_policy.name_for(next_index));
         
-        if let Some(custom_name) = name {
-            account.name = custom_name;
-        }
+        self.store_new_account(account)
+    }
+    
+    /// Set how new accounts are named when no name is given
+    pub fn set_naming_policy(&mut self, policy: NamingPolicy) {
+        self.naming_policy = policy;
+    }
+    
+    /// Add accounts that have been used elsewhere, BIP44 style
+    ///
+    /// Derives accounts after the last loaded one and adds each for which
+    /// `has_activity` returns true, stopping at the first unused account.
+    /// New accounts are named by the naming policy. Returns how many were
+    /// added.
+    pub fn discover_accounts<F>(&mut self, mut has_activity: F) -> Result<usize>
+    where
+        F: FnMut(&Account) -> Result<bool>,
+    {
+        let mut added = 0;
         
+        loop {
+            let next_index = self.accounts.len() as u32;
+            let mut account = self.key_manager.derive_account(next_index)?;
+            if !has_activity(&account)? {
+                return Ok(added);
+            }
+            
+            account.name = self.naming_policy.name_for(next_index);
+            self.store_new_account(account)?;
+            added += 1;
+        }
+    }
+    
+    /// Persist a freshly derived account and load it
+    fn store_new_account(&mut self, account: Account) -> Result<&Account> {
         // Store in database
         let stored_account = StoredAccount {
             id: 0,
@@ -136,6 +619,54 @@ impl WalletState {
         Ok(&self.accounts[self.accounts.len() - 1])
     }
     
+    /// Derive the account at `index` without storing it
+    ///
+    /// Lets a UI show what an account will look like before `add_account`
+    /// commits it. Neither the database nor the loaded accounts change.
+    pub fn preview_account(&self, index: u32) -> Result<Account> {
+        self.key_manager.derive_account(index)
+    }
+    
+    /// Derive accounts lazily, starting at index `from`
+    ///
+    /// Each account is derived only when the iterator reaches it, and none
+    /// are stored, so a UI can scroll past the loaded accounts. Stops at
+    /// the last hardened account index, 2^31 - 1.
+    pub fn accounts_iter(&self, from: u32) -> impl Iterator<Item = Result<Account>> + '_ {
+        (from..1 << 31).map(move |index| self.key_manager.derive_account(index))
+    }
+    
+    /// Re-derive every stored account and compare its chain addresses
+    ///
+    /// Returns `(index, matches)` per stored account. A sanity check after
+    /// restoring from a mnemonic.
+    pub fn verify_all_accounts(&self) -> Result<Vec<(u32, bool)>> {
+        self.db.get_all_accounts()?
+            .into_iter()
+            .map(|stored| {
+                let derived = self.key_manager.derive_account(stored.index)?;
+                let matches = derived.ethereum_address == stored.ethereum_address
+                    && derived.solana_address == stored.solana_address
+                    && derived.bitcoin_address == stored.bitcoin_address
+                    && derived.polygon_address == stored.polygon_address
+                    && derived.zcash_address == stored.zcash_address;
+                Ok((stored.index, matches))
+            })
+            .collect()
+    }
+    
+    /// Whether `address` belongs to one of this wallet's accounts
+    ///
+    /// Ethereum and Polygon addresses match regardless of EIP-55 checksum
+    /// casing; other chains compare exactly.
+    pub fn is_own_address(&self, address: &str) -> Result<bool> {
+        let is_evm = address.len() == 42
+            && address.starts_with("0x")
+            && address[2..].bytes().all(|b| b.is_ascii_hexdigit());
+        let address = if is_evm { address.to_ascii_lowercase() } else { address.to_string() };
+        Ok(self.db.account_for_address(&address)?.is_some())
+    }
+    
     /// Get current account
     pub fn current_account(&self) -> Result<&Account> {
         self.accounts.iter()
@@ -144,11 +675,14 @@ impl WalletState {
     }
     
     /// Switch to different account
+    ///
+    /// The choice is saved and restored by `open_wallet`.
     pub fn switch_account(&mut self, index: u32) -> Result<()> {
         if !self.accounts.iter().any(|a| a.index == index) {
             return Err(CoreError::InvalidParameter("Account not found".into()));
         }
         
+        self.db.set_meta(MetadataKey::CurrentAccount, &index.to_string())?;
         self.current_account_index = index;
         Ok(())
     }
@@ -169,11 +703,33 @@ impl WalletState {
         account_index: u32,
         tx: TransactionRecord,
     ) -> Result<()> {
+        let stored_tx = self.to_stored_transaction(account_index, tx)?;
+        self.db.store_transaction(&stored_tx)?;
+        Ok(())
+    }
+    
+    /// Record a fee-bump replacement of a pending transaction
+    ///
+    /// The new transaction is stored and `old_hash` is marked `replaced`.
+    pub fn record_replacement(
+        &self,
+        account_index: u32,
+        old_hash: &str,
+        tx: TransactionRecord,
+    ) -> Result<()> {
+        let stored_tx = self.to_stored_transaction(account_index, tx)?;
+        self.db.replace_transaction(old_hash, &stored_tx)?;
+        Ok(())
+    }
+    
+    /// Convert a transaction record for storage under the given account
+    fn to_stored_transaction(&self, account_index: u32, tx: TransactionRecord) -> Result<StoredTransaction> {
         // Find account in database
+        tx.validate()?;
         let account = self.db.get_account(account_index)?
             .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
         
-        let stored_tx = StoredTransaction {
+        Ok(StoredTransaction {
             id: 0,
             tx_hash: tx.tx_hash,
             account_id: account.id,
@@ -186,10 +742,9 @@ impl WalletState {
             timestamp: tx.timestamp,
             block_number: tx.block_number,
             gas_used: tx.gas_used,
-        };
-        
-        self.db.store_transaction(&stored_tx)?;
-        Ok(())
+            fee: tx.fee,
+            confirmations: None,
+        })
     }
     
     /// Get transaction history for account
@@ -204,75 +759,727 @@ impl WalletState {
         self.db.get_transactions(account.id, limit)
     }
     
-    /// Generate stealth address for current account
-    pub fn generate_stealth_address(&self) -> Result<StealthAddress> {
-        let master_key = StealthMasterKey::generate();
-        let address = master_key.get_stealth_address();
+    /// Claim the next fresh receive address of an account on `coin`
+    ///
+    /// Derived at m/44'/coin'/account'/0/index. Index 0 is the account's
+    /// primary address, so the first call returns index 1. Addresses from
+    /// `pregenerate_addresses` are handed out first.
+    pub fn next_receive_address(&self, account_index: u32, coin: CoinType) -> Result<(String, u32)> {
+        self.next_address(account_index, coin, EXTERNAL_BRANCH, 1)
+    }
+    
+    /// Claim the next fresh change address of an account on `coin`
+    ///
+    /// Derived from the internal chain m/44'/coin'/account'/1/index, with a
+    /// counter separate from receive addresses. Pass the address to
+    /// `TransactionBuilder::add_change_output`.
+    pub fn next_change_address(&self, account_index: u32, coin: CoinType) -> Result<(String, u32)> {
+        self.next_address(account_index, coin, INTERNAL_BRANCH, 0)
+    }
+    
+    /// Derive and store `count` receive addresses to hand out offline
+    ///
+    /// The addresses are claimed from the receive counter, so they are
+    /// never derived again, and are returned by `next_receive_address` in
+    /// order until the pool runs out.
+    pub fn pregenerate_addresses(&self, account_index: u32, coin: CoinType, count: u32) -> Result<Vec<String>> {
+        let account = self.db.get_account(account_index)?
+            .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
+        
+        self.db.pregenerate_addresses(account.id, coin as u32, EXTERNAL_BRANCH, 1, count, |indices| {
+            self.key_manager.derive_addresses(coin, account_index, EXTERNAL_BRANCH, indices)
+        })
+    }
+    
+    /// Claim the next address on an HD branch, from the pool if possible
+    fn next_address(&self, account_index: u32, coin: CoinType, branch: u32, first: u32) -> Result<(String, u32)> {
+        let account = self.db.get_account(account_index)?
+            .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
+        
+        if let Some(pooled) = self.db.take_pooled_address(account.id, coin as u32, branch)? {
+            return Ok(pooled);
+        }
         
-        // TODO: Store stealth keys in database for scanning
+        let index = self.db.take_address_index(account.id, coin as u32, branch, first)?;
+        let address = self.key_manager
+            .derive_addresses(coin, account_index, branch, index..index + 1)?
+            .remove(0);
         
-        Ok(address)
+        Ok((address, index))
     }
     
-    /// Export account private keys (DANGEROUS - use with caution)
-    pub fn export_private_keys(&self, account_index: u32) -> Result<ExportedKeys> {
-        let account = self.get_account(account_index)
+    /// Chains the account has any transactions on
+    ///
+    /// Empty for a fresh account.
+    pub fn active_chains(&self, account_index: u32) -> Result<Vec<String>> {
+        let account = self.db.get_account(account_index)?
             .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
         
-        Ok(ExportedKeys {
-            ethereum: self.key_manager.export_private_key(account, CoinType::Ethereum)?,
-            solana: self.key_manager.export_private_key(account, CoinType::Solana)?,
-            bitcoin: self.key_manager.export_private_key(account, CoinType::Bitcoin)?,
-        })
+        self.db.get_active_chains(account.id)
     }
     
-    /// Get mnemonic phrase (for backup)
-    pub fn get_mnemonic(&self) -> String {
-        self.key_manager.get_mnemonic()
+    /// List unspent, unreserved outputs for account, excluding dust
+    pub fn list_spendable_utxos(&self, account_index: u32) -> Result<Vec<StealthOutput>> {
+        let account = self.db.get_account(account_index)?
+            .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
+        
+        self.db.list_spendable_utxos(account.id, false)
     }
     
-    /// Sign message with account
-    pub fn sign_message(
+    /// List unspent, unreserved outputs for account, including dust
+    ///
+    /// Spending dust alongside real funds can link them; only use this when
+    /// the user has explicitly chosen to.
+    pub fn list_spendable_utxos_including_dust(&self, account_index: u32) -> Result<Vec<StealthOutput>> {
+        let account = self.db.get_account(account_index)?
+            .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
+        
+        self.db.list_spendable_utxos(account.id, true)
+    }
+    
+    /// Flag unspent outputs below `threshold` as dust across all accounts
+    ///
+    /// Dust is a common tracking vector: an attacker sends tiny outputs and
+    /// watches whether they are later spent together with real funds.
+    /// Returns the number of outputs newly flagged.
+    pub fn flag_dust_outputs(&self, threshold: u64) -> Result<usize> {
+        self.db.flag_dust_outputs(threshold)
+    }
+    
+    /// Unspent outputs flagged as dust, for warning the user
+    pub fn get_dust_outputs(&self, account_index: u32) -> Result<Vec<StealthOutput>> {
+        let account = self.db.get_account(account_index)?
+            .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
+        
+        self.db.get_dust_outputs(account.id)
+    }
+    
+    /// Reserve an output while a transaction spending it is being built
+    ///
+    /// The reservation expires after `UTXO_RESERVATION_TTL_SECS`.
+    pub fn reserve_utxo(&self, output_id: i64) -> Result<()> {
+        self.db.reserve_stealth_output(output_id, UTXO_RESERVATION_TTL_SECS)
+    }
+    
+    /// Release a previously reserved output
+    pub fn release_utxo(&self, output_id: i64) -> Result<()> {
+        self.db.release_stealth_output(output_id)
+    }
+    
+    /// Commit to `value` with a blinding derived from the account's HD key
+    ///
+    /// The same `(account_index, output_index, value)` always yields the same
+    /// commitment and blinding, so outputs can be reconstructed from the mnemonic.
+    pub fn deterministic_output_commitment(
         &self,
-        message: &[u8],
         account_index: u32,
-        coin_type: CoinType,
-    ) -> Result<Vec<u8>> {
-        let account = self.get_account(account_index)
-            .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
+        output_index: u32,
+        value: u64,
+    ) -> Result<(Commitment, Scalar)> {
+        if self.get_account(account_index).is_none() {
+            return Err(CoreError::InvalidParameter("Account not found".into()));
+        }
         
-        self.key_manager.sign_message(message, account, coin_type)
+        let blinding = self.key_manager.derive_blinding_factor(account_index, output_index)?;
+        let commitment = PedersenCommitment::new().commit(value, &blinding);
+        
+        Ok((commitment, blinding))
     }
     
-    /// Backup wallet database
-    pub fn backup<P: AsRef<Path>>(&self, backup_path: P) -> Result<()> {
-        self.db.backup(backup_path)
+    /// Rebuild an account's balance commitment from its unspent outputs
+    ///
+    /// Sums every unspent output commitment from scratch, overwrites the
+    /// stored value, and returns the result. Use this to repair drift left by
+    /// interrupted writes.
+    pub fn recompute_balance_commitment(&self, account_index: u32) -> Result<Commitment> {
+        let account = self.db.get_account(account_index)?
+            .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
+        
+        let mut balance = RistrettoPoint::identity();
+        for bytes in self.db.get_unspent_output_commitments(account.id)? {
+            let array: [u8; 32] = bytes.as_slice().try_into()
+                .map_err(|_| CoreError::Serialization("Invalid stored commitment length".into()))?;
+            balance += Commitment::from_bytes(&array)?.point;
+        }
+        
+        let commitment = Commitment::from_point(balance);
+        self.db.set_balance_commitment(account_index, &commitment.to_bytes())?;
+        
+        Ok(commitment)
     }
     
-    /// Get wallet statistics
-    pub fn get_statistics(&self) -> Result<WalletStatistics> {
-        let total_accounts = self.accounts.len() as u32;
+    /// Prove the wallet holds at least `min_amount` without revealing the total
+    ///
+    /// Sums the openings of every unspent output across all accounts. The
+    /// proof's total commitment equals the sum of those output commitments.
+    pub fn prove_reserves(&self, min_amount: u64) -> Result<ReservesProof> {
+        let mut total: u64 = 0;
+        let mut blinding = Scalar::ZERO;
         
-        // Count total transactions across all accounts
-        let mut total_transactions = 0;
-        for account in &self.accounts {
-            if let Ok(Some(stored_account)) = self.db.get_account(account.index) {
-                let txs = self.db.get_transactions(stored_account.id, 1000)?;
-                total_transactions += txs.len() as u32;
+        for account in self.db.get_all_accounts()? {
+            for opening in self.db.get_unspent_output_openings(account.id)? {
+                let (value, r) = parse_opening(&opening)?;
+                total = total.checked_add(value)
+                    .ok_or_else(|| CoreError::InvalidParameter("Reserves overflow u64".into()))?;
+                blinding += r;
             }
         }
         
-        let wallet_version = self.db.get_metadata("wallet_version")?
-            .unwrap_or_else(|| "unknown".to_string());
+        ReservesProof::prove(total, &blinding, min_amount)
+    }
+    
+    /// Build a transaction sending everything an account holds to `destination`
+    ///
+    /// Spends every unspent, unreserved output of the account that is mature
+    /// at `current_height`, dust included, and pays their total minus `fee`
+    /// to a single output. The spent outputs are reserved for
+    /// `UTXO_RESERVATION_TTL_SECS`. Fails if any of them was stored without
+    /// its index within the funding transaction.
+    pub fn build_sweep_transaction(
+        &self,
+        account_index: u32,
+        destination: Vec<u8>,
+        fee: u64,
+        current_height: u64,
+    ) -> Result<PrivateTransaction> {
+        let account = self.db.get_account(account_index)?
+            .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
         
-        let created_at = self.db.get_metadata("created_at")?
-            .unwrap_or_else(|| "unknown".to_string());
+        let outputs: Vec<StealthOutput> = self.db.list_spendable_utxos(account.id, true)?
+            .into_iter()
+            .filter(|output| transaction_builder::is_mature(output.coinbase_height, current_height))
+            .collect();
+        if outputs.is_empty() {
+            return Err(CoreError::InvalidParameter(format!("Account {} has no funds to sweep", account_index)));
+        }
+        let openings: HashMap<i64, _> = self.db.get_unspent_output_openings(account.id)?
+            .into_iter()
+            .filter_map(|opening| opening.output_id.map(|id| (id, opening)))
+            .collect();
         
-        Ok(WalletStatistics {
-            total_accounts,
-            total_transactions,
-            wallet_version,
-            created_at,
+        let mut builder = TransactionBuilder::new();
+        let mut total: u64 = 0;
+        for output in &outputs {
+            let opening = openings.get(&output.id)
+                .ok_or_else(|| CoreError::InvalidParameter(format!("Output {} has no stored opening", output.id)))?;
+            let (value, blinding) = parse_opening(opening)?;
+            
+            total = total.checked_add(value)
+                .ok_or_else(|| CoreError::InvalidParameter("Sweep total overflows u64".into()))?;
+            let output_index = output.output_index
+                .ok_or_else(|| CoreError::InvalidParameter(format!("Output {} has no recorded output index", output.id)))?;
+            builder.add_input(parse_tx_hash(&output.tx_hash)?, output_index, value, blinding);
+        }
+        
+        if total <= fee {
+            return Err(CoreError::InvalidParameter(
+                format!("Fee {} leaves nothing of the {} to sweep", fee, total)
+            ));
+        }
+        
+        builder.set_fee(fee).add_change_output(destination)?;
+        
+        let mut reserved = Vec::with_capacity(outputs.len());
+        let mut result = Ok(());
+        for output in &outputs {
+            result = self.reserve_utxo(output.id);
+            if result.is_err() {
+                break;
+            }
+            reserved.push(output.id);
+        }
+        let tx = result.and_then(|()| builder.build());
+        if tx.is_err() {
+            for output_id in reserved {
+                self.release_utxo(output_id)?;
+            }
+        }
+        tx
+    }
+    
+    /// Find outputs of an account committed with the same blinding factor
+    ///
+    /// Two commitments sharing a blinding factor reveal the difference of
+    /// their values. Returns each offending `(output_id, output_id)` pair,
+    /// lower id first, spent outputs included. Openings not linked to an
+    /// output are skipped.
+    pub fn audit_blinding_reuse(&self, account_index: u32) -> Result<Vec<(i64, i64)>> {
+        let account = self.db.get_account(account_index)?
+            .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
+        
+        let mut by_blinding: HashMap<Vec<u8>, Vec<i64>> = HashMap::new();
+        for proof in self.db.get_commitment_proofs(account.id)? {
+            if let Some(output_id) = proof.output_id {
+                by_blinding.entry(proof.blinding.clone()).or_default().push(output_id);
+            }
+        }
+        
+        let mut pairs = Vec::new();
+        for mut outputs in by_blinding.into_values() {
+            outputs.sort_unstable();
+            for (i, first) in outputs.iter().enumerate() {
+                for second in &outputs[i + 1..] {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(first, second, "Stored outputs share a blinding factor");
+                    pairs.push((*first, *second));
+                }
+            }
+        }
+        pairs.sort_unstable();
+        
+        Ok(pairs)
+    }
+    
+    /// Designate the key whose signature is required to authorize spends
+    ///
+    /// Needs the wallet password, checked with the usual backoff. Only for
+    /// wallets without one yet; an existing key can only be replaced by
+    /// `rotate_spend_auth_key` with the approver's signature.
+    pub fn set_spend_auth_key(&self, public_key: &PublicKey, password: &str) -> Result<()> {
+        if self.db.get_meta(MetadataKey::SpendAuthKey)?.is_some() {
+            return Err(CoreError::InvalidParameter(
+                "Spend auth key already configured; rotate it with the approver's signature".into()
+            ));
+        }
+        Self::verify_password(&self.db, password)?;
+        self.db.set_meta(MetadataKey::SpendAuthKey, &hex::encode(public_key.serialize()))
+    }
+    
+    /// Replace the spend auth key with `public_key`
+    ///
+    /// `approval_signature` must be a compact ECDSA signature by the current
+    /// spend auth key over `spend_auth_key_digest(public_key)`.
+    pub fn rotate_spend_auth_key(&self, public_key: &PublicKey, approval_signature: &[u8]) -> Result<()> {
+        let current = self.spend_auth_key()?;
+        let digest = Self::spend_auth_key_digest(public_key);
+        if !self.key_manager.verify_message(&digest, approval_signature, &current)? {
+            return Err(CoreError::Crypto("Invalid spend auth key approval signature".into()));
+        }
+        
+        self.db.set_meta(MetadataKey::SpendAuthKey, &hex::encode(public_key.serialize()))
+    }
+    
+    /// Digest the current approver signs to hand over to `public_key`
+    pub fn spend_auth_key_digest(public_key: &PublicKey) -> [u8; 32] {
+        use sha2::{Sha256, Digest};
+        
+        let mut hasher = Sha256::new();
+        hasher.update(b"Zetaris-Spend-Auth-Key-v1");
+        hasher.update(public_key.serialize());
+        hasher.finalize().into()
+    }
+    
+    /// The designated spend auth key
+    fn spend_auth_key(&self) -> Result<PublicKey> {
+        let key_hex = self.db.get_meta(MetadataKey::SpendAuthKey)?
+            .ok_or_else(|| CoreError::Crypto("No spend auth key configured".into()))?;
+        let key_bytes = hex::decode(key_hex)
+            .map_err(|e| CoreError::Serialization(format!("Invalid spend auth key: {}", e)))?;
+        PublicKey::from_slice(&key_bytes)
+            .map_err(|e| CoreError::Serialization(format!("Invalid spend auth key: {}", e)))
+    }
+    
+    /// Create the digest the user must approve before `tx` is signed
+    ///
+    /// The challenge becomes the pending spend; only an approval of this
+    /// exact digest is accepted by `authorize_spend`. It covers
+    /// `PrivateTransaction::signing_hash`, so signing does not change it.
+    pub fn create_spend_challenge(&mut self, tx: &PrivateTransaction) -> Result<[u8; 32]> {
+        let challenge = Self::spend_challenge(tx)?;
+        self.pending_spend_challenge = Some(challenge);
+        Ok(challenge)
+    }
+    
+    fn spend_challenge(tx: &PrivateTransaction) -> Result<[u8; 32]> {
+        use sha2::{Sha256, Digest};
+        
+        let mut hasher = Sha256::new();
+        hasher.update(b"Zetaris-Spend-Challenge-v1");
+        hasher.update(tx.signing_hash()?);
+        Ok(hasher.finalize().into())
+    }
+    
+    /// Authorize the pending spend
+    ///
+    /// `approval_signature` must be a compact ECDSA signature over `challenge`
+    /// by the designated spend auth key, and `challenge` must match the one
+    /// returned by the last `create_spend_challenge`. The returned token
+    /// lets `sign_transaction` sign that transaction once.
+    pub fn authorize_spend(&mut self, challenge: &[u8; 32], approval_signature: &[u8]) -> Result<SpendAuthorization> {
+        if self.pending_spend_challenge.as_ref() != Some(challenge) {
+            return Err(CoreError::InvalidParameter("Challenge does not match pending spend".into()));
+        }
+        
+        let public_key = self.spend_auth_key()?;
+        if !self.key_manager.verify_message(challenge, approval_signature, &public_key)? {
+            return Err(CoreError::Crypto("Invalid spend approval signature".into()));
+        }
+        
+        self.pending_spend_challenge = None;
+        Ok(SpendAuthorization { challenge: *challenge })
+    }
+    
+    /// Authorize the pending spend with the wallet password
+    ///
+    /// Only for wallets without a spend auth key; once one is set, spends
+    /// need its approval through `authorize_spend`. The password is checked
+    /// with the usual backoff.
+    pub fn authorize_spend_with_password(&mut self, challenge: &[u8; 32], password: &str) -> Result<SpendAuthorization> {
+        if self.pending_spend_challenge.as_ref() != Some(challenge) {
+            return Err(CoreError::InvalidParameter("Challenge does not match pending spend".into()));
+        }
+        if self.db.get_meta(MetadataKey::SpendAuthKey)?.is_some() {
+            return Err(CoreError::Crypto("Spend auth key configured; spends need its approval".into()));
+        }
+        
+        Self::verify_password(&self.db, password)?;
+        
+        self.pending_spend_challenge = None;
+        Ok(SpendAuthorization { challenge: *challenge })
+    }
+    
+    /// Sum fees paid per chain for transactions with `from_ts <= timestamp < to_ts`
+    ///
+    /// Fees are read from `fee`, an integer amount in the chain's base
+    /// unit. Incoming and replaced transactions, and those without fee
+    /// data, are skipped; so are fees that are not integers, logged as a warning
+    /// with the `tracing` feature.
+    pub fn total_fees_paid(
+        &self,
+        account_index: u32,
+        from_ts: i64,
+        to_ts: i64,
+    ) -> Result<HashMap<String, u128>> {
+        let account = self.db.get_account(account_index)?
+            .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
+        
+        let mut totals: HashMap<String, u128> = HashMap::new();
+        for tx in self.db.get_transactions_between(account.id, from_ts, to_ts)? {
+            if tx.tx_type == "receive" || tx.status == "replaced" {
+                continue;
+            }
+            let Some(fee) = tx.fee.as_deref() else {
+                continue;
+            };
+            
+            let fee: u128 = match fee.parse() {
+                Ok(fee) => fee,
+                Err(_e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(tx_hash = %tx.tx_hash, "Skipping invalid fee {:?}: {}", fee, _e);
+                    continue;
+                }
+            };
+            let total = totals.entry(tx.chain).or_insert(0);
+            *total = total.saturating_add(fee);
+        }
+        
+        Ok(totals)
+    }
+    
+    /// Refresh confirmation counts for a new chain tip
+    ///
+    /// Returns the number of transactions updated.
+    pub fn bulk_update_confirmations(&self, current_height: u64) -> Result<usize> {
+        self.db.update_confirmations_for_height(current_height)
+    }
+    
+    /// Stealth address of the current account
+    ///
+    /// Derived with `KeyManager::derive_account_stealth_key`, so it is the
+    /// same on every call, recoverable from the mnemonic and visible to the
+    /// account's auditor token. The keys are stored with the account the
+    /// first time; see `list_stealth_addresses`.
+    pub fn generate_stealth_address(&self) -> Result<StealthAddress> {
+        let account = self.db.get_account(self.current_account_index)?
+            .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
+        
+        let master_key = self.key_manager.derive_account_stealth_key(self.current_account_index)?;
+        let address = master_key.get_stealth_address();
+        
+        let spend_public = compress_point(&address.spend_public);
+        if self.db.list_stealth_public_keys(account.id)?.iter().any(|(spend, _)| spend[..] == spend_public[..]) {
+            return Ok(address);
+        }
+        
+        let mut spend_private = master_key.export_spend_private();
+        let mut view_private = master_key.export_view_private();
+        let stored = self.db.store_stealth_keys(
+            account.id,
+            &spend_public,
+            &compress_point(&address.view_public),
+            &spend_private,
+            &view_private,
+        );
+        spend_private.zeroize();
+        view_private.zeroize();
+        stored?;
+        
+        Ok(address)
+    }
+    
+    /// Stealth addresses generated for an account, oldest first
+    ///
+    /// Only the spend and view keys are stored; the amount key is taken from
+    /// the account's derived address when the stored keys match it.
+    pub fn list_stealth_addresses(&self, account_index: u32) -> Result<Vec<StealthAddress>> {
+        let account = self.db.get_account(account_index)?
+            .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
+        let derived = self.key_manager.derive_account_stealth_key(account_index)?.get_stealth_address();
+        
+        self.db.list_stealth_public_keys(account.id)?
+            .into_iter()
+            .map(|(spend_public, view_public)| {
+                let spend_public = decompress_point_slice(&spend_public)?;
+                let view_public = decompress_point_slice(&view_public)?;
+                let amount_public = (spend_public == derived.spend_public && view_public == derived.view_public)
+                    .then_some(derived.amount_public)
+                    .flatten();
+                Ok(StealthAddress {
+                    spend_public,
+                    view_public,
+                    hash_algo: Default::default(),
+                    output_counter: Default::default(),
+                    is_subaddress: false,
+                    amount_public,
+                })
+            })
+            .collect()
+    }
+    
+    /// Attach a private note to an account; an empty note clears it
+    ///
+    /// Notes are field-encrypted and never part of `Account` or any export.
+    pub fn set_account_note(&self, account_index: u32, note: &str) -> Result<()> {
+        if self.get_account(account_index).is_none() {
+            return Err(CoreError::InvalidParameter("Account not found".into()));
+        }
+        self.db.set_account_note(account_index, Some(note).filter(|note| !note.is_empty()))
+    }
+    
+    /// Private note attached to an account, if any
+    pub fn get_account_note(&self, account_index: u32) -> Result<Option<String>> {
+        if self.get_account(account_index).is_none() {
+            return Err(CoreError::InvalidParameter("Account not found".into()));
+        }
+        self.db.get_account_note(account_index)
+    }
+    
+    /// Export an auditor token limited to one account
+    ///
+    /// The token scans that account's stealth payments only; see
+    /// `KeyManager::derive_account_view_key`.
+    pub fn export_account_auditor_token(&self, account_index: u32) -> Result<String> {
+        if self.get_account(account_index).is_none() {
+            return Err(CoreError::InvalidParameter("Account not found".into()));
+        }
+        Ok(self.key_manager.derive_account_view_key(account_index)?.to_token())
+    }
+    
+    /// Export account private keys (DANGEROUS - use with caution)
+    pub fn export_private_keys(&self, account_index: u32) -> Result<ExportedKeys> {
+        let account = self.get_account(account_index)
+            .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
+        
+        Ok(ExportedKeys {
+            ethereum: self.key_manager.export_private_key(account, CoinType::Ethereum)?,
+            solana: self.key_manager.export_private_key(account, CoinType::Solana)?,
+            bitcoin: self.key_manager.export_private_key(account, CoinType::Bitcoin)?,
+        })
+    }
+    
+    /// Export account private keys encrypted to a recipient's public key
+    ///
+    /// Output is `R || nonce || ciphertext`: an ephemeral Ristretto key
+    /// R = r·G, then the JSON keys sealed with ChaCha20-Poly1305 under a key
+    /// hashed from the ECDH secret r·recipient.
+    pub fn export_keys_encrypted(
+        &self,
+        account_index: u32,
+        recipient_pubkey: RistrettoPoint,
+    ) -> Result<Vec<u8>> {
+        if recipient_pubkey == RistrettoPoint::identity() {
+            return Err(CoreError::InvalidParameter("Recipient key is the identity point".into()));
+        }
+        
+        let keys = self.export_private_keys(account_index)?;
+        let mut plaintext = serde_json::to_vec(&keys)
+            .map_err(|e| CoreError::Serialization(e.to_string()))?;
+        
+        let ephemeral_private = crate::commitments::random_scalar();
+        let ephemeral_public = compress_point(&(ephemeral_private * G));
+        let mut key = Self::key_export_secret(&(ephemeral_private * recipient_pubkey), &ephemeral_public);
+        
+        let sealed = ChaCha20Cipher::new(&key).encrypt(&plaintext);
+        key.zeroize();
+        plaintext.zeroize();
+        
+        let mut out = ephemeral_public.to_vec();
+        out.extend_from_slice(&sealed?);
+        Ok(out)
+    }
+    
+    /// Decrypt keys produced by `export_keys_encrypted` with the recipient's private key
+    pub fn decrypt_exported_keys(encrypted: &[u8], recipient_private: &Scalar) -> Result<ExportedKeys> {
+        if encrypted.len() < 32 {
+            return Err(CoreError::Crypto("Encrypted keys too short".into()));
+        }
+        
+        let (ephemeral_bytes, sealed) = encrypted.split_at(32);
+        let ephemeral_public = decompress_point_slice(ephemeral_bytes)?;
+        let ephemeral_bytes: [u8; 32] = ephemeral_bytes.try_into().expect("32-byte prefix");
+        
+        let mut key = Self::key_export_secret(&(recipient_private * ephemeral_public), &ephemeral_bytes);
+        let decrypted = ChaCha20Cipher::new(&key).decrypt(sealed);
+        key.zeroize();
+        
+        let mut plaintext = decrypted?;
+        let keys = serde_json::from_slice(&plaintext)
+            .map_err(|e| CoreError::Serialization(e.to_string()));
+        plaintext.zeroize();
+        
+        keys
+    }
+    
+    /// Symmetric key for an encrypted key export
+    fn key_export_secret(shared_secret: &RistrettoPoint, ephemeral_public: &[u8; 32]) -> [u8; 32] {
+        use sha2::{Digest, Sha256};
+        Sha256::new()
+            .chain_update(b"Zetaris-Key-Export-v1")
+            .chain_update(compress_point(shared_secret))
+            .chain_update(ephemeral_public)
+            .finalize()
+            .into()
+    }
+    
+    /// Get mnemonic phrase (for backup)
+    pub fn get_mnemonic(&self) -> String {
+        self.key_manager.get_mnemonic()
+    }
+    
+    /// Sign message with account
+    pub fn sign_message(
+        &self,
+        message: &[u8],
+        account_index: u32,
+        coin_type: CoinType,
+    ) -> Result<Vec<u8>> {
+        let account = self.get_account(account_index)
+            .ok_or_else(|| CoreError::InvalidParameter("Account not found".into()))?;
+        
+        self.key_manager.sign_message(message, account, coin_type)
+    }
+    
+    /// Sign inputs of `tx` with the current account
+    ///
+    /// Each `(input_index, coin_type)` signs that input with the account's
+    /// `coin_type` key over `PrivateTransaction::signing_hash`. Nothing is
+    /// signed if any index is out of range or `authorization` was not
+    /// issued for `tx`.
+    pub fn sign_transaction(
+        &self,
+        tx: &mut PrivateTransaction,
+        input_keys: &[(usize, CoinType)],
+        authorization: SpendAuthorization,
+    ) -> Result<()> {
+        if Self::spend_challenge(tx)? != authorization.challenge {
+            return Err(CoreError::InvalidParameter("Spend authorization is for another transaction".into()));
+        }
+        if let Some((index, _)) = input_keys.iter().find(|(index, _)| *index >= tx.inputs.len()) {
+            return Err(CoreError::InvalidParameter(format!("No input {}", index)));
+        }
+        
+        let account = self.current_account()?;
+        let hash = tx.signing_hash()?;
+        for (index, coin_type) in input_keys {
+            tx.inputs[*index].signature = self.key_manager.sign_message(&hash, account, *coin_type)?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Sign every unsigned input of `partial` that this wallet's keys own
+    ///
+    /// As with `sign_transaction`, `authorization` must have been issued for
+    /// `partial.tx`. Returns the number of inputs signed.
+    pub fn sign_partial_transaction(
+        &self,
+        partial: &mut PartialTransaction,
+        authorization: SpendAuthorization,
+    ) -> Result<usize> {
+        if Self::spend_challenge(&partial.tx)? != authorization.challenge {
+            return Err(CoreError::InvalidParameter("Spend authorization is for another transaction".into()));
+        }
+        
+        partial.sign(&self.key_manager)
+    }
+    
+    /// Backup wallet database
+    pub fn backup<P: AsRef<Path>>(&self, backup_path: P) -> Result<()> {
+        self.db.backup(backup_path)
+    }
+    
+    /// Describe the wallet's current contents in a signed manifest
+    ///
+    /// Keep it with a backup; `verify_manifest` on the restored wallet
+    /// then detects a partial or altered backup.
+    pub fn export_manifest(&self) -> Result<SignedManifest> {
+        let mut manifest = self.unsigned_manifest()?;
+        manifest.signature = self.key_manager.sign_manifest(&manifest.signing_bytes())?;
+        Ok(manifest)
+    }
+    
+    /// Check that this wallet matches `manifest` and the manifest is signed
+    /// by this wallet's seed
+    pub fn verify_manifest(&self, manifest: &SignedManifest) -> Result<bool> {
+        let public_key = self.key_manager.manifest_public_key()?;
+        let Ok(signed) = self.key_manager.verify_message(&manifest.signing_bytes(), &manifest.signature, &public_key) else {
+            return Ok(false);
+        };
+        if !signed {
+            return Ok(false);
+        }
+        
+        let current = self.unsigned_manifest()?;
+        Ok(SignedManifest { signature: manifest.signature.clone(), ..current } == *manifest)
+    }
+    
+    /// Manifest of the current contents, without a signature
+    fn unsigned_manifest(&self) -> Result<SignedManifest> {
+        Ok(SignedManifest {
+            account_count: self.accounts.len() as u32,
+            account_fingerprints: self.accounts.iter().map(Account::fingerprint).collect(),
+            wallet_fingerprint: self.fingerprint(),
+            created_at: self.db.get_meta(MetadataKey::CreatedAt)?.unwrap_or_default(),
+            content_hash: self.db.content_hash()?,
+            signature: Vec::new(),
+        })
+    }
+    
+    /// Get wallet statistics
+    pub fn get_statistics(&self) -> Result<WalletStatistics> {
+        let total_accounts = self.accounts.len() as u32;
+        
+        // Count total transactions across all accounts
+        let mut total_transactions = 0;
+        for account in &self.accounts {
+            if let Ok(Some(stored_account)) = self.db.get_account(account.index) {
+                let txs = self.db.get_transactions(stored_account.id, 1000)?;
+                total_transactions += txs.len() as u32;
+            }
+        }
+        
+        let wallet_version = self.db.get_meta(MetadataKey::WalletVersion)?
+            .unwrap_or_else(|| "unknown".to_string());
+        
+        let created_at = self.db.get_meta(MetadataKey::CreatedAt)?
+            .unwrap_or_else(|| "unknown".to_string());
+        
+        Ok(WalletStatistics {
+            total_accounts,
+            total_transactions,
+            wallet_version,
+            created_at,
         })
     }
 }
@@ -290,16 +1497,120 @@ pub struct TransactionRecord {
     pub timestamp: i64,
     pub block_number: Option<i64>,
     pub gas_used: Option<String>,
+    /// Fee paid as an integer in the chain's base unit
+    pub fee: Option<String>,
+}
+
+impl TransactionRecord {
+    /// Check that `amount` is a valid decimal for `chain`
+    pub fn validate(&self) -> Result<()> {
+        check_amount(&self.chain, &self.amount)
+    }
+}
+
+/// Value and blinding factor of a stored commitment opening
+fn parse_opening(opening: &StoredCommitmentProof) -> Result<(u64, Scalar)> {
+    let value: u64 = opening.value.parse()
+        .map_err(|_| CoreError::Serialization("Invalid stored commitment value".into()))?;
+    let bytes: [u8; 32] = opening.blinding.as_slice().try_into()
+        .map_err(|_| CoreError::Serialization("Invalid stored blinding length".into()))?;
+    let blinding = Option::<Scalar>::from(Scalar::from_canonical_bytes(bytes))
+        .ok_or_else(|| CoreError::Serialization("Invalid stored blinding factor".into()))?;
+    
+    Ok((value, blinding))
+}
+
+/// Decode a stored hex transaction hash, with or without `0x`
+fn parse_tx_hash(tx_hash: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(tx_hash.strip_prefix("0x").unwrap_or(tx_hash))
+        .map_err(|e| CoreError::Serialization(format!("Invalid transaction hash {}: {}", tx_hash, e)))?;
+    bytes.try_into()
+        .map_err(|_| CoreError::Serialization(format!("Transaction hash {} is not 32 bytes", tx_hash)))
+}
+
+/// Secret wrapping the field key in PIN mode: the PIN and the device secret
+fn pin_secret(pin: &str, device_secret: &[u8]) -> Zeroizing<String> {
+    Zeroizing::new(format!("{}:{}", hex::encode(device_secret), pin))
+}
+
+/// Count one more wrong password at `now`, starting or extending the backoff
+fn record_failed_password(lockout: Lockout, now: i64) -> Lockout {
+    let failed_attempts = lockout.failed_attempts.saturating_add(1);
+    let locked_until = match password_backoff_secs(failed_attempts) {
+        Some(delay) => now.saturating_add(delay as i64),
+        None => lockout.locked_until,
+    };
+    Lockout { failed_attempts, locked_until }
+}
+
+/// Backoff imposed after `failures` consecutive wrong passwords, if any
+fn password_backoff_secs(failures: u32) -> Option<u64> {
+    if failures >= PASSWORD_MAX_ATTEMPTS {
+        return Some(PASSWORD_LOCKOUT_SECS);
+    }
+    
+    let doublings = failures.checked_sub(PASSWORD_FREE_ATTEMPTS + 1)?;
+    Some(PASSWORD_BACKOFF_BASE_SECS << doublings)
 }
 
 /// Exported private keys
-#[derive(Debug, Serialize, Deserialize, ZeroizeOnDrop)]
+#[derive(Debug, Serialize, Deserialize, Zeroize)]
 pub struct ExportedKeys {
     pub ethereum: String,
     pub solana: String,
     pub bitcoin: String,
 }
 
+impl Drop for ExportedKeys {
+    fn drop(&mut self) {
+        self.zeroize();
+        
+        // SAFETY: each string was just wiped across its full capacity
+        #[cfg(debug_assertions)]
+        unsafe {
+            use crate::zeroize_verify::{allocation_bytes, record};
+            record::<Self>(&[
+                allocation_bytes(self.ethereum.as_ptr(), self.ethereum.capacity()),
+                allocation_bytes(self.solana.as_ptr(), self.solana.capacity()),
+                allocation_bytes(self.bitcoin.as_ptr(), self.bitcoin.capacity()),
+            ]);
+        }
+    }
+}
+
+impl ZeroizeOnDrop for ExportedKeys {}
+
+/// Summary of a wallet backup, signed with a key derived from the seed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedManifest {
+    pub account_count: u32,
+    /// `Account::fingerprint` of each account, by index
+    pub account_fingerprints: Vec<[u8; 32]>,
+    pub wallet_fingerprint: [u8; 32],
+    pub created_at: String,
+    /// `EncryptedDb::content_hash` of the backed-up database
+    pub content_hash: [u8; 32],
+    /// Compact secp256k1 signature over `signing_bytes`
+    pub signature: Vec<u8>,
+}
+
+impl SignedManifest {
+    /// Canonical encoding of every field except the signature
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = b"Zetaris-Backup-Manifest-v1".to_vec();
+        bytes.extend_from_slice(&self.account_count.to_be_bytes());
+        bytes.extend_from_slice(&(self.account_fingerprints.len() as u32).to_be_bytes());
+        for fingerprint in &self.account_fingerprints {
+            bytes.extend_from_slice(fingerprint);
+        }
+        bytes.extend_from_slice(&self.wallet_fingerprint);
+        bytes.extend_from_slice(&(self.created_at.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(self.created_at.as_bytes());
+        bytes.extend_from_slice(&self.content_hash);
+        bytes
+    }
+}
+
 /// Wallet statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletStatistics {
@@ -312,6 +1623,8 @@ pub struct WalletStatistics {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crypto::stealth::StealthMasterKey;
+    use crate::storage::test_fixtures::test_output;
     use tempfile::tempdir;
     
     const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
@@ -327,6 +1640,18 @@ mod tests {
         assert_eq!(wallet.current_account_index, 0);
     }
     
+    #[test]
+    fn test_created_at_uses_clock() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let clock = Arc::new(crate::clock::MockClock::from_unix(1_700_000_000));
+        
+        let wallet = WalletState::new_wallet_with_clock(&db_path, "password123", TEST_MNEMONIC, clock).unwrap();
+        
+        let created_at = wallet.db.get_metadata("created_at").unwrap().unwrap();
+        assert_eq!(created_at, "2023-11-14T22:13:20+00:00");
+    }
+    
     #[test]
     fn test_open_existing_wallet() {
         let dir = tempdir().unwrap();
@@ -344,47 +1669,288 @@ mod tests {
     }
     
     #[test]
-    fn test_wrong_mnemonic_fails() {
+    fn test_secure_compact_rekeys_and_shrinks() {
         let dir = tempdir().unwrap();
         let db_path = dir.path().join("wallet.db");
+        let size = |path: &Path| std::fs::metadata(path).unwrap().len();
         
-        // Create wallet
-        {
-            let _wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
-        }
+        let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        wallet.db.set_metadata("scratch", &"x".repeat(1 << 20)).unwrap();
+        wallet.db.set_metadata("scratch", "").unwrap();
+        wallet.db.backup(dir.path().join("checkpoint.db")).unwrap();
+        let before = size(&db_path);
         
-        // Try to open with wrong mnemonic
-        let wrong_mnemonic = KeyManager::generate_mnemonic().unwrap();
-        let result = WalletState::open_wallet(&db_path, "password123", &wrong_mnemonic);
+        // A failed swap keeps the original open and unchanged
+        let blocker = dir.path().join("wallet.db.original");
+        std::fs::create_dir(&blocker).unwrap();
+        std::fs::write(blocker.join("file"), b"x").unwrap();
+        assert!(wallet.secure_compact("password123", "new-password").is_err());
+        wallet.db.set_metadata("after_failure", "kept").unwrap();
+        assert_eq!(wallet.db.get_metadata("after_failure").unwrap().as_deref(), Some("kept"));
+        assert!(!dir.path().join("wallet.db.compact").exists());
+        std::fs::remove_dir_all(&blocker).unwrap();
         
-        assert!(result.is_err());
+        assert!(wallet.secure_compact("wrong", "new-password").is_err());
+        wallet.secure_compact("password123", "new-password").unwrap();
+        assert!(size(&db_path) < before);
+        assert!(!blocker.exists());
+        assert!(wallet.is_unlocked());
+        drop(wallet);
+        
+        assert!(WalletState::open_wallet(&db_path, "password123", TEST_MNEMONIC).is_err());
+        let wallet = WalletState::open_wallet(&db_path, "new-password", TEST_MNEMONIC).unwrap();
+        assert!(wallet.is_unlocked());
+        assert_eq!(wallet.accounts.len(), 1);
+        assert_eq!(wallet.db.get_metadata("after_failure").unwrap().as_deref(), Some("kept"));
     }
     
     #[test]
-    fn test_add_account() {
+    fn test_legacy_mnemonic_hash_upgraded_on_open() {
         let dir = tempdir().unwrap();
         let db_path = dir.path().join("wallet.db");
         
-        let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        // Rewrite a new wallet's mnemonic hash into the legacy form
+        drop(WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap());
+        {
+            let conn = EncryptedDb::raw_connection(&db_path, "password123");
+            conn.execute("DELETE FROM wallet_meta WHERE key IN ('mnemonic_salt', 'mnemonic_hash_scheme')", []).unwrap();
+            conn.execute(
+                "UPDATE wallet_meta SET value = ?1 WHERE key = 'mnemonic_hash'",
+                [WalletState::legacy_mnemonic_hash(TEST_MNEMONIC)],
+            ).unwrap();
+        }
         
-        wallet.add_account(Some("Savings".to_string())).unwrap();
+        let wrong_mnemonic = KeyManager::generate_mnemonic().unwrap();
+        assert!(WalletState::open_wallet(&db_path, "password123", &wrong_mnemonic).is_err());
         
-        assert_eq!(wallet.accounts.len(), 2);
-        assert_eq!(wallet.accounts[1].name, "Savings");
+        let wallet = WalletState::open_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        let meta = |key| wallet.db.get_meta(key).unwrap();
+        assert_eq!(meta(MetadataKey::MnemonicHashScheme).as_deref(), Some(MNEMONIC_HASH_ARGON2));
+        assert!(meta(MetadataKey::MnemonicSalt).is_some());
+        assert_ne!(meta(MetadataKey::MnemonicHash).unwrap(), WalletState::legacy_mnemonic_hash(TEST_MNEMONIC));
+        drop(wallet);
+        
+        assert!(WalletState::open_wallet(&db_path, "password123", TEST_MNEMONIC).is_ok());
+        assert!(WalletState::open_wallet(&db_path, "password123", &wrong_mnemonic).is_err());
     }
     
     #[test]
-    fn test_switch_account() {
+    fn test_manifest_verifies_restored_backup() {
         let dir = tempdir().unwrap();
         let db_path = dir.path().join("wallet.db");
+        let backup_path = dir.path().join("backup.db");
         
         let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
         wallet.add_account(None).unwrap();
+        wallet.backup(&backup_path).unwrap();
+        let manifest = wallet.export_manifest().unwrap();
+        assert_eq!(manifest.account_count, 2);
         
-        assert_eq!(wallet.current_account_index, 0);
+        let mut restored = WalletState::open_wallet(&backup_path, "password123", TEST_MNEMONIC).unwrap();
+        assert!(restored.verify_manifest(&manifest).unwrap());
         
-        wallet.switch_account(1).unwrap();
-        assert_eq!(wallet.current_account_index, 1);
+        let mut altered = manifest.clone();
+        altered.account_count = 3;
+        assert!(!restored.verify_manifest(&altered).unwrap());
+        
+        // A backup that no longer matches its manifest fails too
+        restored.add_account(None).unwrap();
+        assert!(!restored.verify_manifest(&manifest).unwrap());
+    }
+    
+    #[test]
+    fn test_wrong_mnemonic_fails() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        
+        // Create wallet
+        {
+            let _wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        }
+        
+        // Try to open with wrong mnemonic
+        let wrong_mnemonic = KeyManager::generate_mnemonic().unwrap();
+        let result = WalletState::open_wallet(&db_path, "password123", &wrong_mnemonic);
+        
+        assert!(result.is_err());
+    }
+    
+    #[test]
+    fn test_add_account() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        
+        let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        
+        wallet.add_account(Some("Savings".to_string())).unwrap();
+        
+        assert_eq!(wallet.accounts.len(), 2);
+        assert_eq!(wallet.accounts[1].name, "Savings");
+    }
+    
+    #[test]
+    fn test_discover_accounts_uses_naming_policy() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        
+        let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        wallet.set_naming_policy(NamingPolicy::Custom(Arc::new(|i| format!("Vault #{}", i * 10))));
+        
+        let added = wallet.discover_accounts(|account| Ok(account.index <= 3)).unwrap();
+        assert_eq!(added, 3);
+        let names: Vec<_> = wallet.accounts.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["Account 1", "Vault #10", "Vault #20", "Vault #30"]);
+        
+        wallet.set_naming_policy(NamingPolicy::Template("Wallet {index}".into()));
+        assert_eq!(wallet.add_account(None).unwrap().name, "Wallet 4");
+        drop(wallet);
+        
+        // Stored names survive reopening
+        let wallet = WalletState::open_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        assert_eq!(wallet.accounts[2].name, "Vault #20");
+    }
+    
+    #[test]
+    fn test_preview_account_does_not_store() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        
+        let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        
+        let preview = wallet.preview_account(5).unwrap();
+        assert_eq!(preview.index, 5);
+        assert_eq!(wallet.accounts.len(), 1);
+        assert_eq!(wallet.db.get_all_accounts().unwrap().len(), 1);
+        
+        // The next account previewed matches the one add_account stores
+        let next = wallet.preview_account(1).unwrap();
+        let added = wallet.add_account(None).unwrap();
+        assert_eq!(added.ethereum_address, next.ethereum_address);
+        assert_eq!(added.solana_address, next.solana_address);
+    }
+    
+    #[test]
+    fn test_change_addresses_advance_independently() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        let km = &wallet.key_manager;
+        
+        let (receive, receive_index) = wallet.next_receive_address(0, CoinType::Bitcoin).unwrap();
+        assert_eq!(receive_index, 1);
+        assert_eq!(receive, km.derive_addresses(CoinType::Bitcoin, 0, 0, 1..2).unwrap()[0]);
+        
+        let changes: Vec<(String, u32)> = (0..3)
+            .map(|_| wallet.next_change_address(0, CoinType::Bitcoin).unwrap())
+            .collect();
+        assert_eq!(changes.iter().map(|(_, i)| *i).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(
+            changes.into_iter().map(|(a, _)| a).collect::<Vec<_>>(),
+            km.derive_addresses(CoinType::Bitcoin, 0, 1, 0..3).unwrap()
+        );
+        
+        // Receive numbering was not disturbed, and counters survive a restart
+        drop(wallet);
+        let wallet = WalletState::open_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        assert_eq!(wallet.next_receive_address(0, CoinType::Bitcoin).unwrap().1, 2);
+        assert_eq!(wallet.next_change_address(0, CoinType::Bitcoin).unwrap().1, 3);
+        assert_eq!(wallet.next_change_address(0, CoinType::Solana).unwrap().1, 0);
+    }
+    
+    #[test]
+    fn test_pregenerated_addresses_are_handed_out_first() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        let pool = wallet.pregenerate_addresses(0, CoinType::Bitcoin, 50).unwrap();
+        assert_eq!(pool, wallet.key_manager.derive_addresses(CoinType::Bitcoin, 0, 0, 1..51).unwrap());
+        
+        let handed_out: Vec<String> = (0..50)
+            .map(|_| wallet.next_receive_address(0, CoinType::Bitcoin).unwrap().0)
+            .collect();
+        assert_eq!(handed_out, pool);
+        
+        // Once the pool is drained, derivation continues past it
+        assert_eq!(wallet.next_receive_address(0, CoinType::Bitcoin).unwrap().1, 51);
+    }
+    
+    #[test]
+    fn test_list_stealth_addresses() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        
+        let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        let first = wallet.generate_stealth_address().unwrap();
+        let second = wallet.generate_stealth_address().unwrap();
+        assert_eq!(first.to_base58(), second.to_base58());
+        wallet.add_account(None).unwrap();
+        
+        let listed = wallet.list_stealth_addresses(0).unwrap();
+        assert_eq!(
+            listed.iter().map(|a| a.to_base58()).collect::<Vec<_>>(),
+            vec![first.to_base58()]
+        );
+        assert!(wallet.list_stealth_addresses(1).unwrap().is_empty());
+        
+        wallet.switch_account(1).unwrap();
+        assert_ne!(wallet.generate_stealth_address().unwrap().to_base58(), first.to_base58());
+    }
+    
+    #[test]
+    fn test_is_own_address() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        let account = wallet.current_account().unwrap();
+        
+        assert!(wallet.is_own_address(&account.solana_address).unwrap());
+        assert!(!wallet.is_own_address("7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU").unwrap());
+        
+        let checksummed = format!("0x{}", account.ethereum_address[2..].to_ascii_uppercase());
+        assert!(wallet.is_own_address(&checksummed).unwrap());
+    }
+    
+    #[test]
+    fn test_verify_all_accounts_flags_mismatched_address() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        
+        let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        wallet.add_account(None).unwrap();
+        wallet.add_account(None).unwrap();
+        assert_eq!(wallet.verify_all_accounts().unwrap(), vec![(0, true), (1, true), (2, true)]);
+        
+        let conn = EncryptedDb::raw_connection(&db_path, "password123");
+        conn.execute("UPDATE accounts SET bitcoin_address = 'bc1qtampered' WHERE account_index = 1", []).unwrap();
+        
+        assert_eq!(wallet.verify_all_accounts().unwrap(), vec![(0, true), (1, false), (2, true)]);
+    }
+    
+    #[test]
+    fn test_switch_account() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        
+        let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        wallet.add_account(None).unwrap();
+        
+        assert_eq!(wallet.current_account_index, 0);
+        
+        wallet.switch_account(1).unwrap();
+        assert_eq!(wallet.current_account_index, 1);
+        drop(wallet);
+        
+        // The selection survives a restart
+        let wallet = WalletState::open_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        assert_eq!(wallet.current_account_index, 1);
+        
+        // A stale selection falls back to the first account
+        wallet.db.set_meta(MetadataKey::CurrentAccount, "7").unwrap();
+        drop(wallet);
+        let wallet = WalletState::open_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        assert_eq!(wallet.current_account_index, 0);
     }
     
     #[test]
@@ -405,6 +1971,7 @@ mod tests {
             timestamp: 1700000000,
             block_number: Some(18500000),
             gas_used: Some("21000".to_string()),
+            fee: None,
         };
         
         wallet.record_transaction(0, tx).unwrap();
@@ -414,6 +1981,117 @@ mod tests {
         assert_eq!(history[0].tx_hash, "0xabc123");
     }
     
+    #[test]
+    fn test_migrate_plaintext_wallet_to_encrypted() {
+        let dir = tempdir().unwrap();
+        let plain_path = dir.path().join("plain.db");
+        let encrypted_path = dir.path().join("encrypted.db");
+        let source_path = if cfg!(feature = "sqlcipher") { dir.path().join("source.db") } else { plain_path.clone() };
+        
+        {
+            let mut wallet = WalletState::new_wallet(&source_path, "password123", TEST_MNEMONIC).unwrap();
+            wallet.add_account(None).unwrap();
+            wallet.record_transaction(1, TransactionRecord {
+                tx_hash: "0xabc123".to_string(),
+                chain: "ethereum".to_string(),
+                tx_type: "send".to_string(),
+                amount: "1.5".to_string(),
+                from_address: Some("0x1234".to_string()),
+                to_address: Some("0x5678".to_string()),
+                status: "confirmed".to_string(),
+                timestamp: 1700000000,
+                block_number: Some(18500000),
+                gas_used: Some("21000".to_string()),
+                fee: None,
+            }).unwrap();
+        }
+        
+        // Write the plaintext file a build without `sqlcipher` would have
+        #[cfg(feature = "sqlcipher")]
+        EncryptedDb::raw_connection(&source_path, "password123").execute_batch(&format!(
+            "ATTACH DATABASE '{}' AS plaintext KEY '';
+             SELECT sqlcipher_export('plaintext');
+             DETACH DATABASE plaintext;",
+            plain_path.display(),
+        )).unwrap();
+        
+        // Dev builds wrote secret columns without field encryption
+        let account_id: i64 = {
+            let conn = rusqlite::Connection::open(&plain_path).unwrap();
+            let account_id = conn.query_row("SELECT id FROM accounts WHERE account_index = 1", [], |row| row.get(0)).unwrap();
+            conn.execute(
+                "INSERT INTO stealth_outputs (tx_hash, account_id, ephemeral_public, one_time_public,
+                    one_time_private, amount, spent, created_at)
+                 VALUES ('0xdef', ?1, ?2, ?3, ?4, '100', 0, 0)",
+                rusqlite::params![account_id, vec![1u8; 32], vec![2u8; 32], vec![3u8; 32]],
+            ).unwrap();
+            account_id
+        };
+        
+        assert!(WalletState::migrate_to_encrypted(&plain_path, &encrypted_path, "wrong").is_err());
+        assert!(!encrypted_path.exists());
+        WalletState::migrate_to_encrypted(&plain_path, &encrypted_path, "password123").unwrap();
+        assert!(WalletState::migrate_to_encrypted(&plain_path, &encrypted_path, "password123").is_err());
+        
+        let plain = WalletState::open_wallet(&source_path, "password123", TEST_MNEMONIC).unwrap();
+        let migrated = WalletState::open_wallet(&encrypted_path, "password123", TEST_MNEMONIC).unwrap();
+        assert_eq!(migrated.accounts.len(), 2);
+        assert_eq!(migrated.fingerprint(), plain.fingerprint());
+        
+        let history = migrated.get_transaction_history(1, 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].tx_hash, "0xabc123");
+        assert_eq!(history[0].block_number, Some(18500000));
+        
+        let outputs = migrated.db.get_unspent_stealth_outputs(account_id).unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].one_time_private, vec![3; 32]);
+    }
+    
+    #[test]
+    fn test_sign_transaction_inputs() {
+        let dir = tempdir().unwrap();
+        let mut wallet = WalletState::new_wallet(dir.path().join("wallet.db"), "password123", TEST_MNEMONIC).unwrap();
+        
+        let mut builder = crate::TransactionBuilder::new();
+        for i in 0..2u8 {
+            builder.add_input([i; 32], 0, 50, crate::random_scalar());
+        }
+        let change_blinding = builder.calculate_change_blinding();
+        builder.add_output_with_blinding(vec![9u8; 32], 100, change_blinding).unwrap();
+        let mut tx = builder.build().unwrap();
+        
+        let account = wallet.current_account().unwrap();
+        let keys = [
+            wallet.key_manager.public_key(account, CoinType::Bitcoin).unwrap(),
+            wallet.key_manager.public_key(account, CoinType::Ethereum).unwrap(),
+        ];
+        let challenge = wallet.create_spend_challenge(&tx).unwrap();
+        let authorization = wallet.authorize_spend_with_password(&challenge, "password123").unwrap();
+        assert!(wallet.sign_transaction(&mut tx, &[(2, CoinType::Bitcoin)], authorization).is_err());
+        assert!(!tx.verify_signatures(&keys).unwrap());
+        
+        let challenge = wallet.create_spend_challenge(&tx).unwrap();
+        let authorization = wallet.authorize_spend_with_password(&challenge, "password123").unwrap();
+        wallet.sign_transaction(&mut tx, &[(0, CoinType::Bitcoin), (1, CoinType::Ethereum)], authorization).unwrap();
+        assert!(tx.verify_signatures(&keys).unwrap());
+        assert!(tx.verify().unwrap());
+        
+        // Signatures commit to the rest of the transaction
+        tx.fee += 1;
+        assert!(!tx.verify_signatures(&keys).unwrap());
+    }
+    
+    #[test]
+    fn test_encryption_posture_is_recorded() {
+        let dir = tempdir().unwrap();
+        let wallet = WalletState::new_wallet(dir.path().join("wallet.db"), "password123", TEST_MNEMONIC).unwrap();
+        
+        let expected = if cfg!(feature = "sqlcipher") { "sqlcipher" } else { "none" };
+        assert_eq!(wallet.is_encrypted(), cfg!(feature = "sqlcipher"));
+        assert_eq!(wallet.db.get_meta(MetadataKey::Encryption).unwrap().as_deref(), Some(expected));
+    }
+    
     #[test]
     fn test_get_statistics() {
         let dir = tempdir().unwrap();
@@ -442,6 +2120,30 @@ mod tests {
         assert!(!keys.bitcoin.is_empty());
     }
     
+    #[test]
+    fn test_encrypted_key_export_round_trip() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        
+        let recipient_private = crate::commitments::random_scalar();
+        let encrypted = wallet.export_keys_encrypted(0, recipient_private * G).unwrap();
+        
+        let plain = wallet.export_private_keys(0).unwrap();
+        assert!(!encrypted.windows(plain.ethereum.len()).any(|w| w == plain.ethereum.as_bytes()));
+        
+        let decrypted = WalletState::decrypt_exported_keys(&encrypted, &recipient_private).unwrap();
+        assert_eq!(decrypted.ethereum, plain.ethereum);
+        assert_eq!(decrypted.solana, plain.solana);
+        assert_eq!(decrypted.bitcoin, plain.bitcoin);
+        
+        let stranger = crate::commitments::random_scalar();
+        assert!(matches!(
+            WalletState::decrypt_exported_keys(&encrypted, &stranger),
+            Err(CoreError::Crypto(_))
+        ));
+    }
+    
     #[test]
     fn test_get_mnemonic() {
         let dir = tempdir().unwrap();
@@ -452,4 +2154,836 @@ mod tests {
         let mnemonic = wallet.get_mnemonic();
         assert_eq!(mnemonic, TEST_MNEMONIC);
     }
+    
+    #[test]
+    fn test_reserved_utxo_excluded_until_released() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let clock = Arc::new(crate::clock::MockClock::from_unix(1_700_000_000));
+        
+        let wallet = WalletState::new_wallet_with_clock(&db_path, "password123", TEST_MNEMONIC, clock.clone()).unwrap();
+        let account = wallet.db.get_account(0).unwrap().unwrap();
+        let output_id = wallet.db.store_stealth_output(&test_output(account.id, "0xabc")).unwrap();
+        
+        wallet.reserve_utxo(output_id).unwrap();
+        assert!(wallet.list_spendable_utxos(0).unwrap().is_empty());
+        assert!(wallet.reserve_utxo(output_id).is_err());
+        
+        wallet.release_utxo(output_id).unwrap();
+        assert_eq!(wallet.list_spendable_utxos(0).unwrap().len(), 1);
+        
+        // Reservations lapse once the TTL passes
+        wallet.reserve_utxo(output_id).unwrap();
+        clock.advance(std::time::Duration::from_secs(UTXO_RESERVATION_TTL_SECS));
+        assert_eq!(wallet.list_spendable_utxos(0).unwrap().len(), 1);
+    }
+    
+    #[test]
+    fn test_fingerprint_matches_across_devices() {
+        let dir = tempdir().unwrap();
+        let phone = WalletState::new_wallet(dir.path().join("phone.db"), "password123", TEST_MNEMONIC).unwrap();
+        let laptop = WalletState::new_wallet(dir.path().join("laptop.db"), "other-password", TEST_MNEMONIC).unwrap();
+        
+        assert_eq!(phone.fingerprint(), laptop.fingerprint());
+        assert_eq!(
+            phone.current_account().unwrap().fingerprint(),
+            laptop.current_account().unwrap().fingerprint()
+        );
+        
+        let other_mnemonic = WalletState::generate_mnemonic().unwrap();
+        let other = WalletState::new_wallet(dir.path().join("other.db"), "password123", &other_mnemonic).unwrap();
+        assert_ne!(phone.fingerprint(), other.fingerprint());
+        assert_ne!(
+            phone.current_account().unwrap().fingerprint(),
+            other.current_account().unwrap().fingerprint()
+        );
+    }
+    
+    #[test]
+    fn test_dust_outputs_flagged_and_excluded() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        let account = wallet.db.get_account(0).unwrap().unwrap();
+        
+        for (i, amount) in ["5000", "3"].iter().enumerate() {
+            wallet.db.store_stealth_output(&StealthOutput {
+                amount: amount.to_string(),
+                ..test_output(account.id, &format!("0x{:02x}", i))
+            }).unwrap();
+        }
+        
+        assert_eq!(wallet.flag_dust_outputs(100).unwrap(), 1);
+        assert_eq!(wallet.flag_dust_outputs(100).unwrap(), 0);
+        
+        let dust = wallet.get_dust_outputs(0).unwrap();
+        assert_eq!(dust.len(), 1);
+        assert_eq!(dust[0].amount, "3");
+        
+        let spendable = wallet.list_spendable_utxos(0).unwrap();
+        assert_eq!(spendable.len(), 1);
+        assert_eq!(spendable[0].amount, "5000");
+        assert_eq!(wallet.list_spendable_utxos_including_dust(0).unwrap().len(), 2);
+        
+        // SQLite would read "0.5" as 0; it is rejected instead of flagged
+        wallet.db.store_stealth_output(&StealthOutput {
+            amount: "0.5".to_string(),
+            ..test_output(account.id, "0x02")
+        }).unwrap();
+        assert!(matches!(wallet.flag_dust_outputs(100), Err(CoreError::Storage(_))));
+        assert_eq!(wallet.get_dust_outputs(0).unwrap().len(), 1);
+    }
+    
+    #[test]
+    fn test_deterministic_output_commitment() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        
+        let (c1, b1) = wallet.deterministic_output_commitment(0, 3, 500).unwrap();
+        let (c2, b2) = wallet.deterministic_output_commitment(0, 3, 500).unwrap();
+        assert_eq!(c1.to_bytes(), c2.to_bytes());
+        assert_eq!(b1, b2);
+        
+        let (_, other) = wallet.deterministic_output_commitment(0, 4, 500).unwrap();
+        assert_ne!(b1, other);
+    }
+    
+    #[test]
+    fn test_sign_partial_transaction_needs_authorization() {
+        use crate::partial_transaction::InputSigningInfo;
+        
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        
+        let unsigned = |fee: u64| {
+            let mut builder = TransactionBuilder::new();
+            builder.add_input([1u8; 32], 0, 100, crate::commitments::random_scalar());
+            builder.set_fee(fee);
+            let change_blinding = builder.calculate_change_blinding();
+            builder.add_output_with_blinding(vec![2u8; 32], 100 - fee, change_blinding).unwrap();
+            
+            let account = wallet.key_manager.derive_account(0).unwrap();
+            let public_key = wallet.key_manager.public_key(&account, CoinType::Bitcoin).unwrap();
+            PartialTransaction::new(builder.build().unwrap(), vec![InputSigningInfo {
+                account_index: 0,
+                coin_type: CoinType::Bitcoin,
+                public_key: public_key.serialize().to_vec(),
+            }]).unwrap()
+        };
+        let mut partial = unsigned(10);
+        let other = unsigned(20);
+        
+        // Approval of another transaction signs nothing
+        let challenge = wallet.create_spend_challenge(&other.tx).unwrap();
+        let authorization = wallet.authorize_spend_with_password(&challenge, "password123").unwrap();
+        assert!(matches!(
+            wallet.sign_partial_transaction(&mut partial, authorization),
+            Err(CoreError::InvalidParameter(_))
+        ));
+        assert!(!partial.is_fully_signed());
+        
+        let challenge = wallet.create_spend_challenge(&partial.tx).unwrap();
+        let authorization = wallet.authorize_spend_with_password(&challenge, "password123").unwrap();
+        assert_eq!(wallet.sign_partial_transaction(&mut partial, authorization).unwrap(), 1);
+        assert!(partial.finalize().unwrap().verify().unwrap());
+    }
+    
+    #[test]
+    fn test_authorize_spend_challenge() {
+        use bitcoin::secp256k1::{Secp256k1, SecretKey, Message};
+        use sha2::{Sha256, Digest};
+        
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        
+        let secp = Secp256k1::new();
+        let auth_key = SecretKey::from_slice(&[7u8; 32]).unwrap();
+        let auth_public = PublicKey::from_secret_key(&secp, &auth_key);
+        assert!(wallet.set_spend_auth_key(&auth_public, "wrong").is_err());
+        wallet.set_spend_auth_key(&auth_public, "password123").unwrap();
+        
+        let mut builder = crate::transaction_builder::TransactionBuilder::new();
+        let blinding = crate::commitments::random_scalar();
+        builder.add_input([1u8; 32], 0, 100, blinding);
+        let change_blinding = builder.calculate_change_blinding();
+        builder.add_output_with_blinding(vec![2u8; 32], 90, change_blinding).unwrap();
+        builder.set_fee(10);
+        let mut tx = builder.build().unwrap();
+        
+        let sign = |msg: &[u8]| {
+            let hash = Sha256::digest(msg);
+            let msg = Message::from_digest_slice(&hash).unwrap();
+            secp.sign_ecdsa(&msg, &auth_key).serialize_compact().to_vec()
+        };
+        
+        let challenge = wallet.create_spend_challenge(&tx).unwrap();
+        
+        // With an approver set, the password alone neither authorizes nor
+        // replaces the approver
+        assert!(matches!(
+            wallet.authorize_spend_with_password(&challenge, "password123"),
+            Err(CoreError::Crypto(_))
+        ));
+        let other_public = PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[9u8; 32]).unwrap());
+        assert!(wallet.set_spend_auth_key(&other_public, "password123").is_err());
+        
+        // Approval of a different digest is rejected
+        let mut tampered = challenge;
+        tampered[0] ^= 1;
+        assert!(wallet.authorize_spend(&tampered, &sign(&tampered)).is_err());
+        assert!(wallet.authorize_spend(&challenge, &sign(&tampered)).is_err());
+        
+        let authorization = wallet.authorize_spend(&challenge, &sign(&challenge)).unwrap();
+        
+        // The approval is spent; signing needs a fresh one
+        assert!(wallet.authorize_spend(&challenge, &sign(&challenge)).is_err());
+        
+        // A token only signs the transaction it was issued for
+        let mut other = tx.clone();
+        other.fee += 1;
+        assert!(wallet.sign_transaction(&mut other, &[(0, CoinType::Bitcoin)], authorization).is_err());
+        
+        let challenge = wallet.create_spend_challenge(&tx).unwrap();
+        let authorization = wallet.authorize_spend(&challenge, &sign(&challenge)).unwrap();
+        wallet.sign_transaction(&mut tx, &[(0, CoinType::Bitcoin)], authorization).unwrap();
+        assert!(!tx.inputs[0].signature.is_empty());
+        
+        // Changing the approver needs the current approver's signature
+        let new_key = SecretKey::from_slice(&[8u8; 32]).unwrap();
+        let new_public = PublicKey::from_secret_key(&secp, &new_key);
+        let digest = WalletState::spend_auth_key_digest(&new_public);
+        let self_signed = {
+            let hash = Sha256::digest(digest);
+            let msg = Message::from_digest_slice(&hash).unwrap();
+            secp.sign_ecdsa(&msg, &new_key).serialize_compact().to_vec()
+        };
+        assert!(wallet.rotate_spend_auth_key(&new_public, &self_signed).is_err());
+        wallet.rotate_spend_auth_key(&new_public, &sign(&digest)).unwrap();
+        
+        let challenge = wallet.create_spend_challenge(&tx).unwrap();
+        assert!(wallet.authorize_spend(&challenge, &sign(&challenge)).is_err());
+    }
+    
+    #[test]
+    fn test_recompute_balance_commitment() {
+        use crate::storage::StoredCommitmentProof;
+        
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        let account = wallet.db.get_account(0).unwrap().unwrap();
+        
+        let mut unspent_sum = RistrettoPoint::identity();
+        for (i, value) in [100u64, 250, 40].iter().enumerate() {
+            let output_id = wallet.db.store_stealth_output(&StealthOutput {
+                amount: value.to_string(),
+                ..test_output(account.id, &format!("0x{:02x}", i))
+            }).unwrap();
+            
+            let (commitment, blinding) = wallet.deterministic_output_commitment(0, i as u32, *value).unwrap();
+            wallet.db.store_commitment_proof(&StoredCommitmentProof {
+                id: 0,
+                account_id: account.id,
+                output_id: Some(output_id),
+                commitment: commitment.to_bytes().to_vec(),
+                value: value.to_string(),
+                blinding: blinding.to_bytes().to_vec(),
+                range_proof: None,
+            }).unwrap();
+            
+            // Spend the last output
+            if i == 2 {
+                wallet.db.mark_stealth_output_spent(output_id).unwrap();
+            } else {
+                unspent_sum += commitment.point;
+            }
+        }
+        
+        // Corrupt the stored value
+        wallet.db.set_balance_commitment(0, &[0u8; 32]).unwrap();
+        
+        let recomputed = wallet.recompute_balance_commitment(0).unwrap();
+        assert_eq!(recomputed.point, unspent_sum);
+        assert_eq!(
+            wallet.db.get_balance_commitment(0).unwrap().unwrap(),
+            recomputed.to_bytes().to_vec()
+        );
+    }
+    
+    #[test]
+    fn test_record_replacement() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        
+        let pending = |hash: &str, timestamp: i64| TransactionRecord {
+            tx_hash: hash.to_string(),
+            chain: "bitcoin".to_string(),
+            tx_type: "send".to_string(),
+            amount: "0.1".to_string(),
+            from_address: None,
+            to_address: Some("bc1q...".to_string()),
+            status: "pending".to_string(),
+            timestamp,
+            block_number: None,
+            gas_used: None,
+            fee: None,
+        };
+        
+        wallet.record_transaction(0, pending("0xold", 1700000000)).unwrap();
+        wallet.record_replacement(0, "0xold", pending("0xnew", 1700000100)).unwrap();
+        
+        let history = wallet.get_transaction_history(0, 10).unwrap();
+        let status_of = |hash: &str| history.iter().find(|t| t.tx_hash == hash).unwrap().status.clone();
+        assert_eq!(status_of("0xold"), "replaced");
+        assert_eq!(status_of("0xnew"), "pending");
+        
+        // Only pending transactions can be replaced
+        assert!(wallet.record_replacement(0, "0xold", pending("0xnewer", 1700000200)).is_err());
+        assert_eq!(wallet.get_transaction_history(0, 10).unwrap().len(), 2);
+    }
+    
+    #[test]
+    fn test_bulk_update_confirmations() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        
+        let tx = TransactionRecord {
+            tx_hash: "0xabc123".to_string(),
+            chain: "ethereum".to_string(),
+            tx_type: "send".to_string(),
+            amount: "1.5".to_string(),
+            from_address: Some("0x1234".to_string()),
+            to_address: Some("0x5678".to_string()),
+            status: "confirmed".to_string(),
+            timestamp: 1700000000,
+            block_number: Some(18500000),
+            gas_used: Some("21000".to_string()),
+            fee: None,
+        };
+        wallet.record_transaction(0, tx).unwrap();
+        
+        assert_eq!(wallet.bulk_update_confirmations(18500009).unwrap(), 1);
+        
+        let history = wallet.get_transaction_history(0, 10).unwrap();
+        assert_eq!(history[0].block_number, Some(18500000));
+        assert_eq!(history[0].confirmations, Some(9));
+    }
+    
+    #[test]
+    fn test_total_fees_paid() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        
+        let seed = [
+            ("0x01", "ethereum", "send", Some("21000"), 1000),
+            ("0x02", "ethereum", "send", Some("50000"), 2000),
+            ("0x03", "bitcoin", "send", Some("1500"), 2500),
+            ("0x04", "bitcoin", "send", None, 2600),
+            ("0x05", "ethereum", "receive", Some("99999"), 2700),
+            ("0x06", "ethereum", "send", Some("77777"), 9000),
+            ("0x07", "bitcoin", "send", Some("0.5"), 3000),
+        ];
+        for (hash, chain, tx_type, fee, timestamp) in seed {
+            wallet.record_transaction(0, TransactionRecord {
+                tx_hash: hash.to_string(),
+                chain: chain.to_string(),
+                tx_type: tx_type.to_string(),
+                amount: "1".to_string(),
+                from_address: None,
+                to_address: None,
+                status: "confirmed".to_string(),
+                timestamp,
+                block_number: None,
+                gas_used: Some("21000".to_string()),
+                fee: fee.map(str::to_string),
+            }).unwrap();
+        }
+        
+        let fees = wallet.total_fees_paid(0, 0, 5000).unwrap();
+        assert_eq!(fees.len(), 2);
+        assert_eq!(fees["ethereum"], 71000);
+        assert_eq!(fees["bitcoin"], 1500);
+    }
+    
+    #[test]
+    fn test_active_chains() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        assert!(wallet.active_chains(0).unwrap().is_empty());
+        
+        for (hash, chain) in [("0x01", "ethereum"), ("0x02", "solana"), ("0x03", "ethereum")] {
+            wallet.record_transaction(0, TransactionRecord {
+                tx_hash: hash.to_string(),
+                chain: chain.to_string(),
+                tx_type: "receive".to_string(),
+                amount: "1".to_string(),
+                from_address: None,
+                to_address: None,
+                status: "confirmed".to_string(),
+                timestamp: 1000,
+                block_number: None,
+                gas_used: None,
+                fee: None,
+            }).unwrap();
+        }
+        
+        let chains = wallet.active_chains(0).unwrap();
+        assert_eq!(chains, vec!["ethereum", "solana"]);
+        assert!(!chains.contains(&"bitcoin".to_string()));
+    }
+    
+    #[test]
+    fn test_typed_metadata_matches_raw_keys() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        
+        {
+            let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+            
+            let typed = wallet.db.get_meta(MetadataKey::MnemonicHash).unwrap();
+            let raw = wallet.db.get_metadata("mnemonic_hash").unwrap();
+            assert!(typed.is_some());
+            assert_eq!(typed, raw);
+            assert_eq!(wallet.db.get_meta(MetadataKey::WalletVersion).unwrap().as_deref(), Some("1.0.0"));
+        }
+        
+        // open_wallet finds what the typed API wrote
+        let wallet = WalletState::open_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        assert_eq!(wallet.accounts.len(), 1);
+    }
+    
+    #[cfg(feature = "reserves-proof")]
+    #[test]
+    fn test_prove_reserves() {
+        use crate::storage::StoredCommitmentProof;
+        
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        let account = wallet.db.get_account(0).unwrap().unwrap();
+        
+        for (i, value) in [60u64, 40].iter().enumerate() {
+            let output_id = wallet.db.store_stealth_output(&StealthOutput {
+                amount: value.to_string(),
+                ..test_output(account.id, &format!("0x{:02x}", i))
+            }).unwrap();
+            
+            let (commitment, blinding) = wallet.deterministic_output_commitment(0, i as u32, *value).unwrap();
+            wallet.db.store_commitment_proof(&StoredCommitmentProof {
+                id: 0,
+                account_id: account.id,
+                output_id: Some(output_id),
+                commitment: commitment.to_bytes().to_vec(),
+                value: value.to_string(),
+                blinding: blinding.to_bytes().to_vec(),
+                range_proof: None,
+            }).unwrap();
+        }
+        
+        let proof = wallet.prove_reserves(80).unwrap();
+        assert!(proof.verify());
+        assert_eq!(proof.total_commitment.point, wallet.recompute_balance_commitment(0).unwrap().point);
+        assert!(proof.total_commitment.value.is_none());
+        
+        // Claiming a higher minimum than the proof covers fails verification
+        let mut inflated = proof.clone();
+        inflated.min_amount = 90;
+        assert!(!inflated.verify());
+        
+        assert!(matches!(wallet.prove_reserves(120), Err(CoreError::InvalidParameter(_))));
+    }
+    
+    #[cfg(not(feature = "reserves-proof"))]
+    #[test]
+    fn test_prove_reserves_unsupported() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        
+        assert!(matches!(wallet.prove_reserves(0), Err(CoreError::RangeProof(_))));
+    }
+    
+    #[test]
+    fn test_audit_chain_detects_tampering() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        
+        {
+            let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+            wallet.record_transaction(0, TransactionRecord {
+                tx_hash: "0xabc123".to_string(),
+                chain: "ethereum".to_string(),
+                tx_type: "send".to_string(),
+                amount: "1000000000000000000".to_string(),
+                from_address: None,
+                to_address: None,
+                status: "pending".to_string(),
+                timestamp: 1700000000,
+                block_number: None,
+                gas_used: None,
+                fee: None,
+            }).unwrap();
+            wallet.change_password("password123", "new-password").unwrap();
+            
+            let operations: Vec<String> = wallet.db.get_audit_log().unwrap().into_iter().map(|e| e.operation).collect();
+            assert_eq!(operations, ["account_added", "transaction_recorded", "password_changed"]);
+            assert!(wallet.verify_audit_chain().unwrap());
+            
+            wallet.secure_compact("new-password", "new-password").unwrap();
+            assert!(wallet.verify_audit_chain().unwrap());
+        }
+        
+        // Rewrite an entry and recompute the whole chain without the field key
+        use crate::storage::encrypted_db::audit_hash;
+        let conn = EncryptedDb::raw_connection(&db_path, "new-password");
+        conn.execute("UPDATE audit_log SET detail = '0xdef456' WHERE operation = 'transaction_recorded'", []).unwrap();
+        let entries: Vec<(i64, i64, String, String)> = {
+            let mut stmt = conn.prepare("SELECT id, timestamp, operation, detail FROM audit_log ORDER BY id").unwrap();
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))).unwrap();
+            rows.collect::<std::result::Result<_, _>>().unwrap()
+        };
+        let mut previous = [0u8; 32];
+        for (id, timestamp, operation, detail) in entries {
+            let hash = audit_hash(&[0u8; 32], &previous, timestamp, &operation, &detail);
+            conn.execute("UPDATE audit_log SET hash = ?2 WHERE id = ?1", rusqlite::params![id, hash.to_vec()]).unwrap();
+            previous = hash;
+        }
+        drop(conn);
+        
+        let mut wallet = WalletState::open_wallet(&db_path, "new-password", TEST_MNEMONIC).unwrap();
+        assert!(!wallet.verify_audit_chain().unwrap());
+        
+        // Compaction re-keys the chain only while it still verifies
+        assert!(wallet.secure_compact("new-password", "other-password").is_err());
+    }
+    
+    #[test]
+    fn test_accounts_iter_derives_lazily() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        
+        let mut derived = 0;
+        let accounts: Vec<Account> = wallet.accounts_iter(3)
+            .inspect(|_| derived += 1)
+            .take(5)
+            .collect::<Result<_>>()
+            .unwrap();
+        
+        assert_eq!(derived, 5);
+        for (offset, account) in accounts.iter().enumerate() {
+            let expected = wallet.preview_account(3 + offset as u32).unwrap();
+            assert_eq!(account.index, expected.index);
+            assert_eq!(account.ethereum_address, expected.ethereum_address);
+            assert_eq!(account.bitcoin_address, expected.bitcoin_address);
+        }
+        assert_eq!(wallet.get_accounts().len(), 1);
+        assert_eq!(wallet.accounts_iter(u32::MAX).count(), 0);
+    }
+    
+    #[test]
+    fn test_account_note_survives_reopen() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        
+        {
+            let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+            assert_eq!(wallet.get_account_note(0).unwrap(), None);
+            wallet.set_account_note(0, "cold storage \u{2014} do not spend").unwrap();
+            assert!(wallet.set_account_note(5, "missing").is_err());
+        }
+        
+        let stored: Vec<u8> = EncryptedDb::raw_connection(&db_path, "password123")
+            .query_row("SELECT note FROM accounts WHERE account_index = 0", [], |row| row.get(0))
+            .unwrap();
+        assert!(!String::from_utf8_lossy(&stored).contains("cold storage"));
+        
+        let wallet = WalletState::open_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        assert_eq!(wallet.get_account_note(0).unwrap().as_deref(), Some("cold storage \u{2014} do not spend"));
+        assert!(!serde_json::to_string(wallet.get_account(0).unwrap()).unwrap().contains("cold storage"));
+        
+        wallet.set_account_note(0, "").unwrap();
+        assert_eq!(wallet.get_account_note(0).unwrap(), None);
+    }
+    
+    #[test]
+    fn test_wipe_secrets_blocks_signing_and_export() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        assert!(wallet.sign_message(b"hello", 0, CoinType::Ethereum).is_ok());
+        
+        wallet.wipe_secrets();
+        
+        assert!(matches!(wallet.sign_message(b"hello", 0, CoinType::Ethereum), Err(CoreError::SecretUnavailable)));
+        assert!(matches!(wallet.export_private_keys(0), Err(CoreError::SecretUnavailable)));
+        assert!(matches!(wallet.export_account_auditor_token(0), Err(CoreError::SecretUnavailable)));
+        assert!(wallet.get_accounts().iter().all(|a| a.ethereum_key.is_none()));
+        assert!(!wallet.is_unlocked());
+    }
+    
+    #[test]
+    fn test_audit_blinding_reuse_reports_pairs() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        let account = wallet.db.get_account(0).unwrap().unwrap();
+        
+        let shared = crate::commitments::random_scalar();
+        let mut output_ids = Vec::new();
+        for (i, (value, blinding)) in [(60u64, shared), (40, crate::commitments::random_scalar()), (25, shared)].into_iter().enumerate() {
+            let output_id = wallet.db.store_stealth_output(&StealthOutput {
+                amount: value.to_string(),
+                ..test_output(account.id, &format!("0x{:02x}", i))
+            }).unwrap();
+            wallet.db.store_commitment_proof(&StoredCommitmentProof {
+                id: 0,
+                account_id: account.id,
+                output_id: Some(output_id),
+                commitment: PedersenCommitment::new().commit(value, &blinding).to_bytes().to_vec(),
+                value: value.to_string(),
+                blinding: blinding.to_bytes().to_vec(),
+                range_proof: None,
+            }).unwrap();
+            output_ids.push(output_id);
+        }
+        
+        assert_eq!(wallet.audit_blinding_reuse(0).unwrap(), vec![(output_ids[0], output_ids[2])]);
+        
+        // Spending does not hide earlier reuse
+        wallet.db.mark_stealth_output_spent(output_ids[0]).unwrap();
+        assert_eq!(wallet.audit_blinding_reuse(0).unwrap(), vec![(output_ids[0], output_ids[2])]);
+    }
+    
+    #[test]
+    fn test_sweep_spends_all_outputs() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        let account = wallet.db.get_account(0).unwrap().unwrap();
+        
+        assert!(matches!(wallet.build_sweep_transaction(0, vec![9; 32], 10, 550), Err(CoreError::InvalidParameter(_))));
+        
+        // The third output is coinbase mined at height 500
+        let mut blinding_sum = Scalar::ZERO;
+        let mut output_ids = Vec::new();
+        for (i, value) in [60u64, 40, 50].iter().enumerate() {
+            let output_id = wallet.db.store_stealth_output(&StealthOutput {
+                amount: value.to_string(),
+                output_index: if i == 0 { None } else { Some(3) },
+                coinbase_height: if i == 2 { Some(500) } else { None },
+                ..test_output(account.id, &format!("0x{}", hex::encode([i as u8; 32])))
+            }).unwrap();
+            output_ids.push(output_id);
+            
+            let (commitment, blinding) = wallet.deterministic_output_commitment(0, i as u32, *value).unwrap();
+            if i < 2 {
+                blinding_sum += blinding;
+            }
+            wallet.db.store_commitment_proof(&StoredCommitmentProof {
+                id: 0,
+                account_id: account.id,
+                output_id: Some(output_id),
+                commitment: commitment.to_bytes().to_vec(),
+                value: value.to_string(),
+                blinding: blinding.to_bytes().to_vec(),
+                range_proof: None,
+            }).unwrap();
+        }
+        
+        // An output whose index was never recorded cannot be referenced
+        assert!(matches!(wallet.build_sweep_transaction(0, vec![9; 32], 10, 550), Err(CoreError::InvalidParameter(_))));
+        let conn = EncryptedDb::raw_connection(&db_path, "password123");
+        conn.execute("UPDATE stealth_outputs SET output_index = 1 WHERE id = ?1", [output_ids[0]]).unwrap();
+        drop(conn);
+        
+        // The coinbase output is still immature at height 550
+        let tx = wallet.build_sweep_transaction(0, vec![9; 32], 10, 550).unwrap();
+        assert_eq!(tx.inputs.len(), 2);
+        let mut references: Vec<([u8; 32], u32)> = tx.inputs.iter().map(|input| (input.prev_tx_hash, input.prev_output_index)).collect();
+        references.sort();
+        assert_eq!(references, vec![([0; 32], 1), ([1; 32], 3)]);
+        assert_eq!(tx.outputs.len(), 1);
+        assert_eq!(tx.outputs[0].address, vec![9; 32]);
+        assert_eq!(tx.fee, 10);
+        assert!(tx.verify().unwrap());
+        assert_eq!(crate::commitments::recover_value(&tx.outputs[0].commitment, &blinding_sum, 100), Some(90));
+        
+        // The swept outputs stay reserved, leaving only the coinbase output
+        assert!(wallet.list_spendable_utxos(0).unwrap().iter().all(|o| o.id == output_ids[2]));
+        assert!(wallet.build_sweep_transaction(0, vec![9; 32], 10, 550).is_err());
+        
+        // A rejected sweep reserves nothing
+        for output_id in &output_ids[..2] {
+            wallet.release_utxo(*output_id).unwrap();
+        }
+        assert!(wallet.build_sweep_transaction(0, vec![9; 32], 150, 600).is_err());
+        assert_eq!(wallet.list_spendable_utxos(0).unwrap().len(), 3);
+        
+        let tx = wallet.build_sweep_transaction(0, vec![9; 32], 10, 600).unwrap();
+        assert_eq!(tx.inputs.len(), 3);
+        assert!(tx.verify().unwrap());
+    }
+    
+    #[test]
+    fn test_missing_verifier_requires_migration() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        drop(WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap());
+        
+        let conn = EncryptedDb::raw_connection(&db_path, "password123");
+        conn.execute("DELETE FROM wallet_meta WHERE key IN ('password_salt', 'password_hash')", []).unwrap();
+        drop(conn);
+        
+        // No password is adopted on open, not even the right one
+        assert!(matches!(WalletState::open_wallet(&db_path, "password123", TEST_MNEMONIC), Err(CoreError::Storage(_))));
+        
+        let other_mnemonic = KeyManager::generate_mnemonic().unwrap();
+        assert!(WalletState::migrate_password_verifier(&db_path, "password123", &other_mnemonic).is_err());
+        WalletState::migrate_password_verifier(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        assert!(WalletState::migrate_password_verifier(&db_path, "password123", TEST_MNEMONIC).is_err());
+        
+        assert!(WalletState::open_wallet(&db_path, "wrong", TEST_MNEMONIC).is_err());
+        assert!(WalletState::open_wallet(&db_path, "password123", TEST_MNEMONIC).is_ok());
+    }
+    
+    #[test]
+    fn test_wrong_passwords_trigger_backoff() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let clock = Arc::new(crate::clock::MockClock::from_unix(1_700_000_000));
+        WalletState::new_wallet_with_clock(&db_path, "password123", TEST_MNEMONIC, clock.clone()).unwrap();
+        
+        let open = |password: &str| {
+            WalletState::open_wallet_with_clock(&db_path, password, TEST_MNEMONIC, clock.clone())
+        };
+        
+        // The free attempts fail without delay; the next one starts the backoff
+        for _ in 0..=PASSWORD_FREE_ATTEMPTS {
+            assert!(matches!(open("wrong"), Err(CoreError::InvalidParameter(_))));
+        }
+        
+        // Even the correct password is refused until the delay passes
+        assert!(matches!(open("password123"), Err(CoreError::WalletLocked(30))));
+        
+        clock.advance(std::time::Duration::from_secs(PASSWORD_BACKOFF_BASE_SECS));
+        let mut wallet = open("password123").unwrap();
+        assert_eq!(wallet.db.lockout().unwrap(), Lockout::default());
+        
+        // The counter was reset, so a single mistake is not penalized
+        assert!(matches!(wallet.change_password("wrong", "new-password"), Err(CoreError::InvalidParameter(_))));
+        wallet.change_password("password123", "new-password").unwrap();
+        drop(wallet);
+        
+        assert!(open("password123").is_err());
+        assert!(open("new-password").is_ok());
+    }
+    
+    #[cfg(feature = "sqlcipher")]
+    #[test]
+    fn test_change_password_rekeys_database() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        wallet.change_password("password123", "new-password").unwrap();
+        drop(wallet);
+        
+        let count = |password: &str| EncryptedDb::raw_connection(&db_path, password)
+            .query_row("SELECT COUNT(*) FROM accounts", [], |row| row.get::<_, i64>(0));
+        assert!(count("password123").is_err());
+        assert_eq!(count("new-password").unwrap(), 1);
+    }
+    
+    #[test]
+    fn test_password_backoff_schedule() {
+        assert_eq!(password_backoff_secs(PASSWORD_FREE_ATTEMPTS), None);
+        assert_eq!(password_backoff_secs(PASSWORD_FREE_ATTEMPTS + 1), Some(PASSWORD_BACKOFF_BASE_SECS));
+        assert_eq!(password_backoff_secs(PASSWORD_FREE_ATTEMPTS + 2), Some(PASSWORD_BACKOFF_BASE_SECS * 2));
+        assert_eq!(password_backoff_secs(PASSWORD_MAX_ATTEMPTS), Some(PASSWORD_LOCKOUT_SECS));
+    }
+    
+    #[test]
+    fn test_pin_unlock_and_fallback_to_password() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        
+        let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        let account = wallet.db.get_account(0).unwrap().unwrap();
+        wallet.db.store_stealth_output(&test_output(account.id, "0xabc")).unwrap();
+        let device_secret = [9u8; MIN_DEVICE_SECRET_LEN];
+        assert!(wallet.set_pin("2468", &device_secret[..16]).is_err());
+        wallet.set_pin("2468", &device_secret).unwrap();
+        
+        wallet.lock();
+        assert!(!wallet.is_unlocked());
+        assert!(wallet.db.get_unspent_stealth_outputs(account.id).is_err());
+        assert!(matches!(wallet.sign_message(b"hello", 0, CoinType::Ethereum), Err(CoreError::KeysLocked)));
+        assert!(matches!(wallet.export_private_keys(0), Err(CoreError::KeysLocked)));
+        
+        // The PIN alone does not unwrap the field key
+        assert!(wallet.unlock_with_pin("2468", &[0u8; MIN_DEVICE_SECRET_LEN]).is_err());
+        wallet.unlock_with_pin("2468", &device_secret).unwrap();
+        let outputs = wallet.db.get_unspent_stealth_outputs(account.id).unwrap();
+        assert_eq!(outputs[0].one_time_private, vec![3; 32]);
+        assert!(wallet.sign_message(b"hello", 0, CoinType::Ethereum).is_ok());
+        
+        // Wrong PINs eventually discard the PIN wrapping entirely
+        wallet.lock();
+        for _ in 0..PIN_MAX_ATTEMPTS {
+            assert!(wallet.unlock_with_pin("0000", &device_secret).is_err());
+        }
+        assert!(wallet.unlock_with_pin("2468", &device_secret).is_err());
+        assert!(!wallet.is_unlocked());
+        
+        wallet.unlock_with_password("password123").unwrap();
+        assert!(wallet.is_unlocked());
+        assert!(wallet.sign_message(b"hello", 0, CoinType::Ethereum).is_ok());
+        assert_eq!(wallet.db.get_unspent_stealth_outputs(account.id).unwrap().len(), 1);
+    }
+    
+    #[test]
+    fn test_auditor_token_round_trip() {
+        let owner = StealthMasterKey::generate();
+        let token = owner.export_auditor_token();
+        
+        let auditor = WalletState::import_auditor_token(&token).unwrap();
+        assert_eq!(auditor.spend_public, owner.spend_public);
+        
+        let address = owner.get_stealth_address();
+        let ours = address.generate_one_time_address();
+        let theirs = StealthMasterKey::generate().get_stealth_address().generate_one_time_address();
+        
+        assert_eq!(auditor.scan_transactions(&[theirs, ours]), vec![1]);
+    }
+    
+    #[test]
+    fn test_account_auditor_token_sees_one_account() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.db");
+        let mut wallet = WalletState::new_wallet(&db_path, "password123", TEST_MNEMONIC).unwrap();
+        wallet.add_account(None).unwrap();
+        
+        let token = wallet.export_account_auditor_token(0).unwrap();
+        let auditor = WalletState::import_auditor_token(&token).unwrap();
+        
+        let ours = wallet.generate_stealth_address().unwrap();
+        wallet.switch_account(1).unwrap();
+        let theirs = wallet.generate_stealth_address().unwrap();
+        
+        let transactions = [
+            theirs.generate_one_time_address(),
+            ours.generate_one_time_address(),
+            theirs.generate_one_time_address(),
+        ];
+        assert_eq!(auditor.scan_transactions(&transactions), vec![1]);
+        assert!(wallet.export_account_auditor_token(2).is_err());
+    }
 }
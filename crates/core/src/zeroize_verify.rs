@@ -0,0 +1,110 @@
+//! Zeroization Verification (debug builds only)
+//!
+//! Secret types record, as they drop, whether their secret bytes read back
+//! as zero after wiping. Tests inspect this drop-order canary to confirm
+//! that `Drop` really ran the wipe rather than trusting the derive. Only
+//! the most recent `WIPE_LOG_CAPACITY` records per thread are kept.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+/// Records kept per thread; older ones are discarded
+pub const WIPE_LOG_CAPACITY: usize = 256;
+
+thread_local! {
+    static WIPE_LOG: RefCell<VecDeque<WipeRecord>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// Outcome of wiping one secret value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WipeRecord {
+    /// Type that was dropped
+    pub type_name: &'static str,
+    /// Whether every secret byte was zero after wiping
+    pub wiped: bool,
+}
+
+/// Record the wipe of a `T`, checking that every buffer is all zeros
+pub(crate) fn record<T>(buffers: &[&[u8]]) {
+    let wiped = buffers.iter().all(|buf| buf.iter().all(|b| *b == 0));
+    
+    WIPE_LOG.with(|log| {
+        let mut log = log.borrow_mut();
+        if log.len() == WIPE_LOG_CAPACITY {
+            log.pop_front();
+        }
+        log.push_back(WipeRecord {
+            type_name: std::any::type_name::<T>(),
+            wiped,
+        })
+    });
+}
+
+/// Bytes of a heap allocation, including spare capacity
+///
+/// # Safety
+///
+/// `ptr` must point to an allocation of at least `capacity` bytes that has
+/// just been wiped with `Zeroize` (which writes the whole capacity, so every
+/// byte is initialized).
+pub(crate) unsafe fn allocation_bytes<'a>(ptr: *const u8, capacity: usize) -> &'a [u8] {
+    std::slice::from_raw_parts(ptr, capacity)
+}
+
+/// Take and clear the wipe records of the current thread, oldest first
+pub fn take_wipe_log() -> Vec<WipeRecord> {
+    WIPE_LOG.with(|log| std::mem::take(&mut *log.borrow_mut()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExportedKeys, KeyManager, StealthMasterKey};
+    
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+    
+    fn wipes_of<T>(log: &[WipeRecord]) -> Vec<bool> {
+        log.iter()
+            .filter(|r| r.type_name == std::any::type_name::<T>())
+            .map(|r| r.wiped)
+            .collect()
+    }
+    
+    #[test]
+    fn test_stealth_master_key_wiped_on_drop() {
+        take_wipe_log();
+        drop(StealthMasterKey::generate());
+        
+        assert_eq!(wipes_of::<StealthMasterKey>(&take_wipe_log()), vec![true]);
+    }
+    
+    #[test]
+    fn test_exported_keys_wiped_on_drop() {
+        take_wipe_log();
+        drop(ExportedKeys {
+            ethereum: "4c0883a69102937d6231471b5dbb6204fe512961708279f2e3e8a5d4b8e3e6d1".to_string(),
+            solana: "a".repeat(64),
+            bitcoin: "b".repeat(64),
+        });
+        
+        assert_eq!(wipes_of::<ExportedKeys>(&take_wipe_log()), vec![true]);
+    }
+    
+    #[test]
+    fn test_key_manager_wiped_on_drop() {
+        take_wipe_log();
+        drop(KeyManager::new_from_mnemonic(TEST_MNEMONIC).unwrap());
+        
+        assert_eq!(wipes_of::<KeyManager>(&take_wipe_log()), vec![true]);
+    }
+    
+    #[test]
+    fn test_wipe_log_is_bounded() {
+        take_wipe_log();
+        for _ in 0..WIPE_LOG_CAPACITY + 10 {
+            drop(StealthMasterKey::generate());
+        }
+        
+        assert_eq!(take_wipe_log().len(), WIPE_LOG_CAPACITY);
+    }
+}